@@ -10,10 +10,9 @@ use std::io::{self, Read, Write};
 use std::num::FromPrimitive;
 use test::Bencher;
 
-use serde::de::{self, Deserialize, Deserializer};
 use serde::json::ser::escape_str;
 use serde::json;
-use serde::ser::{self, Serialize, Serializer};
+use serde::ser::Serialize;
 
 use rustc_serialize::Encodable;
 
@@ -30,7 +29,8 @@ struct Http {
     request_uri: String,
 }
 
-#[derive(Copy, Debug, PartialEq, FromPrimitive)]
+#[derive(Copy, Debug, PartialEq, FromPrimitive, Serialize, Deserialize)]
+#[serde(repr = "u8")]
 enum HttpProtocol {
     HTTP_PROTOCOL_UNKNOWN,
     HTTP10,
@@ -52,25 +52,8 @@ impl rustc_serialize::Decodable for HttpProtocol {
     }
 }
 
-impl ser::Serialize for HttpProtocol {
-    #[inline]
-    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
-        where S: ser::Serializer,
-    {
-        serializer.visit_u8(*self as u8)
-    }
-}
-
-impl de::Deserialize for HttpProtocol {
-    #[inline]
-    fn deserialize<
-        S: Deserializer,
-    >(state: &mut S) -> Result<HttpProtocol, S::Error> {
-        state.visit(de::impls::PrimitiveVisitor::new())
-    }
-}
-
-#[derive(Copy, Debug, PartialEq, FromPrimitive)]
+#[derive(Copy, Debug, PartialEq, FromPrimitive, Serialize, Deserialize)]
+#[serde(repr = "u8")]
 enum HttpMethod {
     METHOD_UNKNOWN,
     GET,
@@ -100,25 +83,8 @@ impl rustc_serialize::Decodable for HttpMethod {
     }
 }
 
-impl ser::Serialize for HttpMethod {
-    #[inline]
-    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
-        where S: ser::Serializer,
-    {
-        serializer.visit_u8(*self as u8)
-    }
-}
-
-impl de::Deserialize for HttpMethod {
-    #[inline]
-    fn deserialize<
-        S: de::Deserializer,
-    >(state: &mut S) -> Result<HttpMethod, S::Error> {
-        state.visit(de::impls::PrimitiveVisitor::new())
-    }
-}
-
-#[derive(Copy, Debug, PartialEq, FromPrimitive)]
+#[derive(Copy, Debug, PartialEq, FromPrimitive, Serialize, Deserialize)]
+#[serde(repr = "u8")]
 enum CacheStatus {
     CACHESTATUS_UNKNOWN,
     Miss,
@@ -141,24 +107,6 @@ impl rustc_serialize::Decodable for CacheStatus {
     }
 }
 
-impl ser::Serialize for CacheStatus {
-    #[inline]
-    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
-        where S: ser::Serializer,
-    {
-        serializer.visit_u8(*self as u8)
-    }
-}
-
-impl de::Deserialize for CacheStatus {
-    #[inline]
-    fn deserialize<
-        S: de::Deserializer,
-    >(state: &mut S) -> Result<CacheStatus, S::Error> {
-        state.visit(de::impls::PrimitiveVisitor::new())
-    }
-}
-
 #[derive(Debug, PartialEq, RustcEncodable, RustcDecodable, Serialize, Deserialize)]
 struct Origin {
     ip: String,
@@ -167,7 +115,8 @@ struct Origin {
     protocol: OriginProtocol,
 }
 
-#[derive(Copy, Debug, PartialEq, FromPrimitive)]
+#[derive(Copy, Debug, PartialEq, FromPrimitive, Serialize, Deserialize)]
+#[serde(repr = "u8")]
 enum OriginProtocol {
     ORIGIN_PROTOCOL_UNKNOWN,
     HTTP,
@@ -189,25 +138,8 @@ impl rustc_serialize::Decodable for OriginProtocol {
     }
 }
 
-impl ser::Serialize for OriginProtocol {
-    #[inline]
-    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
-        where S: ser::Serializer,
-    {
-        serializer.visit_u8(*self as u8)
-    }
-}
-
-impl de::Deserialize for OriginProtocol {
-    #[inline]
-    fn deserialize<
-        S: de::Deserializer,
-    >(state: &mut S) -> Result<OriginProtocol, S::Error> {
-        state.visit(de::impls::PrimitiveVisitor::new())
-    }
-}
-
-#[derive(Copy, Debug, PartialEq, FromPrimitive)]
+#[derive(Copy, Debug, PartialEq, FromPrimitive, Serialize, Deserialize)]
+#[serde(repr = "u8")]
 enum ZonePlan {
     ZONEPLAN_UNKNOWN,
     FREE,
@@ -231,25 +163,8 @@ impl rustc_serialize::Decodable for ZonePlan {
     }
 }
 
-impl ser::Serialize for ZonePlan {
-    #[inline]
-    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
-        where S: ser::Serializer,
-    {
-        serializer.visit_u8(*self as u8)
-    }
-}
-
-impl de::Deserialize for ZonePlan {
-    #[inline]
-    fn deserialize<
-        S: de::Deserializer,
-    >(state: &mut S) -> Result<ZonePlan, S::Error> {
-        state.visit(de::impls::PrimitiveVisitor::new())
-    }
-}
-
-#[derive(Copy, Debug, PartialEq, FromPrimitive)]
+#[derive(Copy, Debug, PartialEq, FromPrimitive, Serialize, Deserialize)]
+#[serde(repr = "u8")]
 enum Country {
 	UNKNOWN,
 	A1,
@@ -524,24 +439,6 @@ impl rustc_serialize::Decodable for Country {
     }
 }
 
-impl ser::Serialize for Country {
-    #[inline]
-    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
-        where S: ser::Serializer,
-    {
-        serializer.visit_u8(*self as u8)
-    }
-}
-
-impl de::Deserialize for Country {
-    #[inline]
-    fn deserialize<
-        S: de::Deserializer,
-    >(state: &mut S) -> Result<Country, S::Error> {
-        state.visit(de::impls::PrimitiveVisitor::new())
-    }
-}
-
 #[derive(Debug, PartialEq, RustcEncodable, RustcDecodable, Serialize, Deserialize)]
 struct Log {
     timestamp: i64,
@@ -731,18 +628,18 @@ fn bench_encoder(b: &mut Bencher) {
 #[test]
 fn test_serializer() {
     let log = Log::new();
-    let json = json::to_vec(&log);
+    let json = json::to_vec(&log).unwrap();
     assert_eq!(json, JSON_STR.as_bytes());
 }
 
 #[bench]
 fn bench_serializer(b: &mut Bencher) {
     let log = Log::new();
-    let json = json::to_vec(&log);
+    let json = json::to_vec(&log).unwrap();
     b.bytes = json.len() as u64;
 
     b.iter(|| {
-        let _ = json::to_vec(&log);
+        let _ = json::to_vec(&log).unwrap();
     });
 }
 
@@ -760,7 +657,7 @@ fn test_serializer_vec() {
 #[bench]
 fn bench_serializer_vec(b: &mut Bencher) {
     let log = Log::new();
-    let json = json::to_vec(&log);
+    let json = json::to_vec(&log).unwrap();
     b.bytes = json.len() as u64;
 
     let mut wr = Vec::with_capacity(1024);
@@ -777,7 +674,7 @@ fn bench_serializer_vec(b: &mut Bencher) {
 #[bench]
 fn bench_serializer_slice(b: &mut Bencher) {
     let log = Log::new();
-    let json = json::to_vec(&log);
+    let json = json::to_vec(&log).unwrap();
     b.bytes = json.len() as u64;
 
     let mut buf = [0; 1024];
@@ -810,7 +707,7 @@ fn test_serializer_my_mem_writer0() {
 #[bench]
 fn bench_serializer_my_mem_writer0(b: &mut Bencher) {
     let log = Log::new();
-    let json = json::to_vec(&log);
+    let json = json::to_vec(&log).unwrap();
     b.bytes = json.len() as u64;
 
     let mut wr = MyMemWriter0::with_capacity(1024);
@@ -842,7 +739,7 @@ fn test_serializer_my_mem_writer1() {
 #[bench]
 fn bench_serializer_my_mem_writer1(b: &mut Bencher) {
     let log = Log::new();
-    let json = json::to_vec(&log);
+    let json = json::to_vec(&log).unwrap();
     b.bytes = json.len() as u64;
 
     let mut wr = MyMemWriter1::with_capacity(1024);
@@ -1204,3 +1101,30 @@ fn bench_deserializer(b: &mut Bencher) {
         let _log: Log = json::from_str(JSON_STR).unwrap();
     });
 }
+
+#[bench]
+fn bench_deserializer_slice(b: &mut Bencher) {
+    b.bytes = JSON_STR.len() as u64;
+
+    b.iter(|| {
+        let _log: Log = json::de::from_slice(JSON_STR.as_bytes()).unwrap();
+    });
+}
+
+#[bench]
+fn bench_deserializer_reader(b: &mut Bencher) {
+    b.bytes = JSON_STR.len() as u64;
+
+    b.iter(|| {
+        let _log: Log = json::de::from_reader(JSON_STR.as_bytes()).unwrap();
+    });
+}
+
+#[bench]
+fn bench_deserializer_iter(b: &mut Bencher) {
+    b.bytes = JSON_STR.len() as u64;
+
+    b.iter(|| {
+        let _log: Log = json::de::from_iter(JSON_STR.bytes().map(Ok)).unwrap();
+    });
+}
@@ -741,3 +741,43 @@ fn bench_deserializer_1_5(b: &mut Bencher) {
         assert_eq!(value, Ok(outer));
     })
 }
+
+//////////////////////////////////////////////////////////////////////////////
+
+const JSON_1_5: &'static str = r#"{"inner":[{"a":null,"b":5,"c":{"1":"a","2":null,"3":"b","4":null,"5":"c"}}]}"#;
+
+#[bench]
+fn bench_json_decoder_1_5(b: &mut Bencher) {
+    b.bytes = JSON_1_5.len() as u64;
+
+    b.iter(|| {
+        let _outer: Outer = rustc_serialize::json::decode(JSON_1_5).unwrap();
+    })
+}
+
+#[bench]
+fn bench_json_deserializer_slice_1_5(b: &mut Bencher) {
+    b.bytes = JSON_1_5.len() as u64;
+
+    b.iter(|| {
+        let _outer: Outer = serde::json::from_str(JSON_1_5).unwrap();
+    })
+}
+
+#[bench]
+fn bench_json_deserializer_reader_1_5(b: &mut Bencher) {
+    b.bytes = JSON_1_5.len() as u64;
+
+    b.iter(|| {
+        let _outer: Outer = serde::json::de::from_reader(JSON_1_5.as_bytes()).unwrap();
+    })
+}
+
+#[bench]
+fn bench_json_deserializer_iter_1_5(b: &mut Bencher) {
+    b.bytes = JSON_1_5.len() as u64;
+
+    b.iter(|| {
+        let _outer: Outer = serde::json::de::from_iter(JSON_1_5.bytes().map(Ok)).unwrap();
+    })
+}
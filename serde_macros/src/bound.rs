@@ -0,0 +1,354 @@
+use std::collections::HashSet;
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::parse::token;
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::visit;
+
+use aster;
+
+use field::{self, Direction};
+
+/// Returns a copy of `generics` with the where-predicates that a derived impl needs.
+///
+/// A `#[serde(bound = "...")]` attribute on the container replaces all of the inferred bounds.
+/// Otherwise every field that passes `filter` adds its own `bound` attribute, or else a
+/// `trait_path` bound on each type parameter and associated type that its type mentions.
+pub fn with_bound(
+    cx: &ExtCtxt,
+    item: &ast::Item,
+    generics: &ast::Generics,
+    direction: Direction,
+    filter: &Fn(&ast::StructField) -> bool,
+    trait_path: &str,
+) -> ast::Generics {
+    let mut generics = generics.clone();
+
+    let predicates = match field::container_bound(cx, item, direction) {
+        Some(predicates) => predicates,
+        None => field_bounds(cx, item, &generics, direction, filter, trait_path),
+    };
+
+    generics.where_clause.predicates.extend(predicates.into_iter());
+    generics
+}
+
+/// Like `with_bound`, but ignores the container's `bound` attribute. Used for the bounds that
+/// come in addition to the main trait, such as `Default` for skipped fields.
+pub fn with_field_bound(
+    cx: &ExtCtxt,
+    item: &ast::Item,
+    generics: &ast::Generics,
+    direction: Direction,
+    filter: &Fn(&ast::StructField) -> bool,
+    trait_path: &str,
+) -> ast::Generics {
+    let mut generics = generics.clone();
+
+    let predicates = if field::container_bound(cx, item, direction).is_some() {
+        vec![]
+    } else {
+        field_bounds(cx, item, &generics, direction, filter, trait_path)
+    };
+
+    generics.where_clause.predicates.extend(predicates.into_iter());
+    generics
+}
+
+/// Returns a copy of `generics` with the where-predicates `predicates`, unless the container's
+/// `bound` attribute replaces them. Used instead of `with_bound` when the fields aren't
+/// serialized or deserialized themselves, such as for the `into` and `from` conversions, so no
+/// bounds are inferred from them.
+pub fn with_predicates(
+    cx: &ExtCtxt,
+    item: &ast::Item,
+    generics: &ast::Generics,
+    direction: Direction,
+    predicates: &str,
+) -> ast::Generics {
+    let mut generics = generics.clone();
+
+    let predicates = match field::container_bound(cx, item, direction) {
+        Some(predicates) => predicates,
+        None => {
+            match field::parse_where_predicates(cx, predicates) {
+                Some(predicates) => predicates,
+                None => cx.bug("failed to parse conversion bounds"),
+            }
+        }
+    };
+
+    generics.where_clause.predicates.extend(predicates.into_iter());
+    generics
+}
+
+fn field_bounds(
+    cx: &ExtCtxt,
+    item: &ast::Item,
+    generics: &ast::Generics,
+    direction: Direction,
+    filter: &Fn(&ast::StructField) -> bool,
+    trait_path: &str,
+) -> Vec<ast::WherePredicate> {
+    let ty_params: HashSet<ast::Name> = generics.ty_params.iter()
+        .map(|ty_param| ty_param.ident.name)
+        .collect();
+
+    let mut predicates = vec![];
+    let mut finder = FindTyParams {
+        ty_params: &ty_params,
+        bounded: vec![],
+    };
+
+    for (ty, field) in item_fields(item) {
+        if let Some(field) = field {
+            if !filter(field) {
+                continue;
+            }
+
+            if let Some(field_predicates) = field::field_bound(cx, field, direction) {
+                predicates.extend(field_predicates.into_iter());
+                continue;
+            }
+        }
+
+        visit::Visitor::visit_ty(&mut finder, &**ty);
+    }
+
+    if !finder.bounded.is_empty() {
+        let inferred: Vec<String> = finder.bounded.iter()
+            .map(|bounded| format!("{}: {}", bounded, trait_path))
+            .collect();
+
+        match field::parse_where_predicates(cx, &inferred.connect(", ")) {
+            Some(inferred) => predicates.extend(inferred.into_iter()),
+            None => cx.bug("failed to parse inferred bounds"),
+        }
+    }
+
+    predicates
+}
+
+/// Returns the lifetime parameters that the fields of a struct or enum borrow from the input:
+/// those of `&'a str` and `&'a [u8]` fields, and all of those that a `#[serde(borrow)]` field
+/// mentions. Fields that mention other lifetime parameters are deserialized as owned values.
+///
+/// Other fields that hold references, such as an `Option<&'a str>`, can't be deserialized at
+/// all, and are reported here rather than as a missing trait impl in the generated code.
+pub fn borrowed_lifetimes(
+    cx: &ExtCtxt,
+    item: &ast::Item,
+    generics: &ast::Generics,
+) -> Vec<String> {
+    let builder = aster::AstBuilder::new();
+
+    let lifetimes: HashSet<ast::Name> = generics.lifetimes.iter()
+        .map(|lifetime_def| lifetime_def.lifetime.name)
+        .collect();
+
+    let mut finder = FindLifetimes {
+        lifetimes: &lifetimes,
+        borrowed: vec![],
+    };
+
+    for (ty, field) in item_fields(item) {
+        match field {
+            Some(field) if field::skip_deserializing(field) => {}
+            Some(field) if field::deserialize_with(cx, &builder, field).is_some() => {}
+            Some(field) if field::borrow(field) => {
+                if slice_lifetime(ty).is_none() && !is_cow(ty) {
+                    cx.span_fatal(
+                        field.span,
+                        &format!("#[serde(borrow)] is only supported on `&str`, `&[u8]` and \
+                                  `Cow` fields, not on `{}`",
+                                 pprust::ty_to_string(ty)));
+                }
+
+                visit::Visitor::visit_ty(&mut finder, &**ty);
+            }
+            _ => {
+                if let Some(lifetime) = slice_lifetime(ty) {
+                    finder.add(lifetime);
+                } else if has_ref(ty) {
+                    cx.span_fatal(
+                        ty.span,
+                        &format!("cannot deserialize a field of type `{}`: only `&str` and \
+                                  `&[u8]` fields, and `Cow` fields with #[serde(borrow)], can \
+                                  borrow from the input",
+                                 pprust::ty_to_string(ty)));
+                }
+            }
+        }
+    }
+
+    finder.borrowed.iter()
+        .map(|name| token::get_name(*name).to_string())
+        .collect()
+}
+
+/// Returns the lifetime of a `&'a str` or `&'a [u8]`, which can only be deserialized by borrowing.
+fn slice_lifetime(ty: &ast::Ty) -> Option<&ast::Lifetime> {
+    if let ast::TyRptr(Some(ref lifetime), ref mut_ty) = ty.node {
+        let is_slice = match mut_ty.ty.node {
+            ast::TyPath(None, ref path) => is_ident(path, "str"),
+            ast::TyVec(ref elem_ty) => {
+                match elem_ty.node {
+                    ast::TyPath(None, ref path) => is_ident(path, "u8"),
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+
+        if is_slice {
+            return Some(lifetime);
+        }
+    }
+
+    None
+}
+
+/// Returns the type of the first field that borrows from the input, to name it in errors.
+pub fn first_borrowed_ty(item: &ast::Item) -> Option<&P<ast::Ty>> {
+    item_fields(item).into_iter()
+        .find(|&(ty, field)| {
+            match field {
+                Some(field) if field::skip_deserializing(field) => false,
+                Some(field) if field::borrow(field) => true,
+                _ => slice_lifetime(ty).is_some(),
+            }
+        })
+        .map(|(ty, _)| ty)
+}
+
+/// Returns true if the type is a `Cow`, as far as can be told from its path.
+pub fn is_cow(ty: &ast::Ty) -> bool {
+    match ty.node {
+        ast::TyPath(_, ref path) => {
+            path.segments.last().map_or(false, |segment| {
+                segment.identifier.to_string() == "Cow"
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the type holds a reference outside of `PhantomData`.
+fn has_ref(ty: &ast::Ty) -> bool {
+    let mut finder = FindRefs { found: false };
+    visit::Visitor::visit_ty(&mut finder, ty);
+    finder.found
+}
+
+fn is_ident(path: &ast::Path, ident: &str) -> bool {
+    !path.global && path.segments.len() == 1 && path.segments[0].identifier.to_string() == ident
+}
+
+/// Returns the types of the fields of a struct or of all the variants of an enum, with the field
+/// itself when it can carry attributes.
+fn item_fields(item: &ast::Item) -> Vec<(&P<ast::Ty>, Option<&ast::StructField>)> {
+    let mut fields = vec![];
+
+    match item.node {
+        ast::ItemStruct(ref struct_def, _) => {
+            for field in struct_def.fields.iter() {
+                fields.push((&field.node.ty, Some(field)));
+            }
+        }
+        ast::ItemEnum(ref enum_def, _) => {
+            for variant in enum_def.variants.iter() {
+                match variant.node.kind {
+                    ast::TupleVariantKind(ref args) => {
+                        for arg in args.iter() {
+                            fields.push((&arg.ty, None));
+                        }
+                    }
+                    ast::StructVariantKind(ref struct_def) => {
+                        for field in struct_def.fields.iter() {
+                            fields.push((&field.node.ty, Some(field)));
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    fields
+}
+
+/// Collects the type parameters, and associated types of them such as `T::Item`, that a type
+/// mentions. Anything inside `PhantomData` is skipped, since `PhantomData<T>` can be serialized
+/// and deserialized whatever `T` is.
+struct FindTyParams<'a> {
+    ty_params: &'a HashSet<ast::Name>,
+    bounded: Vec<String>,
+}
+
+impl<'a, 'v> visit::Visitor<'v> for FindTyParams<'a> {
+    fn visit_path(&mut self, path: &'v ast::Path, _id: ast::NodeId) {
+        if let Some(segment) = path.segments.last() {
+            if segment.identifier.to_string() == "PhantomData" {
+                return;
+            }
+        }
+
+        if !path.global && self.ty_params.contains(&path.segments[0].identifier.name) {
+            let bounded = pprust::path_to_string(path);
+
+            if !self.bounded.contains(&bounded) {
+                self.bounded.push(bounded);
+            }
+        }
+
+        visit::walk_path(self, path);
+    }
+}
+
+/// Collects the lifetime parameters that a type mentions.
+struct FindLifetimes<'a> {
+    lifetimes: &'a HashSet<ast::Name>,
+    borrowed: Vec<ast::Name>,
+}
+
+impl<'a> FindLifetimes<'a> {
+    fn add(&mut self, lifetime: &ast::Lifetime) {
+        if self.lifetimes.contains(&lifetime.name) && !self.borrowed.contains(&lifetime.name) {
+            self.borrowed.push(lifetime.name);
+        }
+    }
+}
+
+impl<'a, 'v> visit::Visitor<'v> for FindLifetimes<'a> {
+    fn visit_lifetime_ref(&mut self, lifetime: &'v ast::Lifetime) {
+        self.add(lifetime);
+    }
+}
+
+/// Looks for a reference in a type, skipping anything inside `PhantomData`, which is never
+/// deserialized.
+struct FindRefs {
+    found: bool,
+}
+
+impl<'v> visit::Visitor<'v> for FindRefs {
+    fn visit_ty(&mut self, ty: &'v ast::Ty) {
+        if let ast::TyRptr(..) = ty.node {
+            self.found = true;
+        } else {
+            visit::walk_ty(self, ty);
+        }
+    }
+
+    fn visit_path(&mut self, path: &'v ast::Path, _id: ast::NodeId) {
+        if let Some(segment) = path.segments.last() {
+            if segment.identifier.to_string() == "PhantomData" {
+                return;
+            }
+        }
+
+        visit::walk_path(self, path);
+    }
+}
@@ -7,14 +7,16 @@ use syntax::ast::{
     EnumDef,
 };
 use syntax::ast;
-use syntax::codemap::Span;
+use syntax::codemap::{Span, DUMMY_SP};
 use syntax::ext::base::ExtCtxt;
 use syntax::ext::build::AstBuilder;
+use syntax::print::pprust;
 use syntax::ptr::P;
 
 use aster;
 
-use field;
+use bound;
+use field::{self, Direction, EnumRepr, EnumTag};
 
 pub fn expand_derive_deserialize(
     cx: &mut ExtCtxt,
@@ -31,16 +33,133 @@ pub fn expand_derive_deserialize(
         _ => cx.bug("expected ItemStruct or ItemEnum in #[derive(Deserialize)]")
     };
 
-    let impl_generics = builder.from_generics(generics.clone())
-        .add_ty_param_bound(
-            builder.path().global().ids(&["serde", "de", "Deserialize"]).build()
-        )
+    // A type whose fields borrow from the input for a lifetime implements `DeserializeBorrowed`
+    // for it instead. Types whose lifetime parameters are only used by owned fields, such as a
+    // `PhantomData<&'a ()>` or a `Cow<'a, str>` without `#[serde(borrow)]`, implement
+    // `Deserialize` as usual.
+    let mut borrowed_lifetimes = bound::borrowed_lifetimes(cx, item, generics);
+    let lifetime = match borrowed_lifetimes.len() {
+        0 => None,
+        1 => borrowed_lifetimes.pop(),
+        _ => {
+            cx.span_fatal(item.span, "#[derive(Deserialize)] fields can only borrow from the \
+                                      input for one lifetime")
+        }
+    };
+
+    let trait_path = match lifetime {
+        Some(ref lifetime) => format!("::serde::de::DeserializeBorrowed<{}>", lifetime),
+        None => "::serde::de::Deserialize".to_string(),
+    };
+
+    let self_ty = builder.ty().path()
+        .segment(item.ident).with_generics(generics.clone()).build()
         .build();
+    let self_ty = pprust::ty_to_string(&self_ty);
+
+    let impl_generics = if let Some(from_ty) = field::from_type(cx, item) {
+        // Only the value converted from is deserialized, so the fields need nothing.
+        let from_ty = pprust::ty_to_string(&from_ty);
+        bound::with_predicates(
+            cx,
+            item,
+            generics,
+            Direction::Deserialize,
+            &format!("{}: ::serde::de::Deserialize, {}: ::std::convert::From<{}>",
+                     from_ty, self_ty, from_ty),
+        )
+    } else if let Some(try_from_ty) = field::try_from_type(cx, item) {
+        let try_from_ty = pprust::ty_to_string(&try_from_ty);
+        bound::with_predicates(
+            cx,
+            item,
+            generics,
+            Direction::Deserialize,
+            &format!("{}: ::serde::de::Deserialize, {}: ::serde::de::TryFrom<{}>",
+                     try_from_ty, self_ty, try_from_ty),
+        )
+    } else if field::transparent(cx, item) {
+        // The single field is deserialized as is, so bound its type rather than its parameters.
+        bound::with_predicates(
+            cx,
+            item,
+            generics,
+            Direction::Deserialize,
+            &format!("{}: {}",
+                     pprust::ty_to_string(&field::transparent_type(cx, item)), trait_path),
+        )
+    } else {
+        // Fields that are skipped or deserialized with a custom function don't need
+        // `Deserialize`.
+        let impl_generics = bound::with_bound(
+            cx,
+            item,
+            generics,
+            Direction::Deserialize,
+            &|field: &ast::StructField| {
+                !field::skip_deserializing(field) &&
+                    field::deserialize_with(cx, &builder, field).is_none()
+            },
+            &trait_path,
+        );
+
+        // Skipped fields without a default of their own are filled in with
+        // `Default::default()`.
+        let container_default = field::container_default(item);
+        bound::with_field_bound(
+            cx,
+            item,
+            &impl_generics,
+            Direction::Deserialize,
+            &|field: &ast::StructField| {
+                field::skip_deserializing(field) &&
+                    !container_default &&
+                    field::default_value(cx, &builder, field).is_none()
+            },
+            "::std::default::Default",
+        )
+    };
 
     let ty = builder.ty().path()
         .segment(item.ident).with_generics(impl_generics.clone()).build()
         .build();
 
+    let where_clause = &impl_generics.where_clause;
+
+    if let Some(ref lifetime) = lifetime {
+        let body = deserialize_borrowed_body(
+            cx,
+            &builder,
+            item,
+            &impl_generics,
+            ty.clone(),
+            lifetime,
+        );
+
+        let deserialize_borrowed = builder.path().global()
+            .id("serde").id("de").segment("DeserializeBorrowed").lifetime(&**lifetime).build()
+            .build();
+
+        let borrowed_deserializer = builder.path().global()
+            .id("serde").id("de").segment("BorrowedDeserializer").lifetime(&**lifetime).build()
+            .build();
+
+        let impl_item = quote_item!(cx,
+            #[automatically_derived]
+            impl $impl_generics $deserialize_borrowed for $ty $where_clause {
+                fn deserialize_borrowed<__D>(deserializer: &mut __D)
+                                             -> ::std::result::Result<$ty, __D::Error>
+                    where __D: $borrowed_deserializer,
+                {
+                    $body
+                }
+            }
+        ).unwrap();
+
+        push(impl_item);
+        return;
+    }
+
     let body = deserialize_body(
         cx,
         &builder,
@@ -49,8 +168,6 @@ pub fn expand_derive_deserialize(
         ty.clone(),
     );
 
-    let where_clause = &impl_generics.where_clause;
-
     let impl_item = quote_item!(cx,
         #[automatically_derived]
         impl $impl_generics ::serde::de::Deserialize for $ty $where_clause {
@@ -72,6 +189,27 @@ fn deserialize_body(
     impl_generics: &ast::Generics,
     ty: P<ast::Ty>,
 ) -> P<ast::Expr> {
+    if field::transparent(cx, item) {
+        return deserialize_transparent(cx, item);
+    }
+
+    if let Some(from_ty) = field::from_type(cx, item) {
+        return quote_expr!(cx, {
+            let value: $from_ty = try!(::serde::de::Deserialize::deserialize(deserializer));
+            Ok(::std::convert::From::from(value))
+        });
+    }
+
+    if let Some(try_from_ty) = field::try_from_type(cx, item) {
+        return quote_expr!(cx, {
+            let value: $try_from_ty = try!(::serde::de::Deserialize::deserialize(deserializer));
+            match ::serde::de::TryFrom::try_from(value) {
+                Ok(value) => Ok(value),
+                Err(err) => Err(::serde::de::Error::custom(format!("{}", err))),
+            }
+        });
+    }
+
     match item.node {
         ast::ItemStruct(ref struct_def, _) => {
             deserialize_item_struct(
@@ -87,16 +225,127 @@ fn deserialize_body(
             deserialize_item_enum(
                 cx,
                 builder,
-                item.ident,
+                item,
                 impl_generics,
                 ty,
                 enum_def,
+                field::deny_unknown_fields(item),
             )
         }
         _ => cx.bug("expected ItemStruct or ItemEnum in #[derive(Deserialize)]")
     }
 }
 
+/// Deserializes a struct whose fields borrow from the input for `lifetime`.
+fn deserialize_borrowed_body(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    item: &Item,
+    impl_generics: &ast::Generics,
+    ty: P<ast::Ty>,
+    lifetime: &str,
+) -> P<ast::Expr> {
+    let borrowed_ty = match bound::first_borrowed_ty(item) {
+        Some(ty) => pprust::ty_to_string(ty),
+        None => cx.bug("expected a field that borrows from the input"),
+    };
+
+    let struct_def = match item.node {
+        ast::ItemStruct(ref struct_def, _) => struct_def,
+        _ => {
+            cx.span_fatal(
+                item.span,
+                &format!("cannot deserialize a field of type `{}` in an enum: only structs can \
+                          borrow from the input",
+                         borrowed_ty))
+        }
+    };
+
+    if field::transparent(cx, item) ||
+        field::from_type(cx, item).is_some() ||
+        field::try_from_type(cx, item).is_some()
+    {
+        cx.span_fatal(item.span, "a struct that borrows from the input cannot use transparent, \
+                                  from or try_from");
+    }
+
+    let named = struct_def.fields.iter().all(|field| {
+        match field.node.kind {
+            ast::NamedField(..) => true,
+            ast::UnnamedField(_) => false,
+        }
+    });
+
+    if !named {
+        if struct_def.fields.len() == 1 {
+            return deserialize_borrowed_newtype_struct(cx, item.ident, &struct_def.fields[0]);
+        }
+
+        cx.span_fatal(
+            item.span,
+            &format!("cannot deserialize a field of type `{}` in a tuple struct: only newtype \
+                      structs and structs with named fields can borrow from the input",
+                     borrowed_ty));
+    }
+
+    let type_name = field::container_name(cx, item, Direction::Deserialize);
+
+    deserialize_struct(
+        cx,
+        builder,
+        item.ident,
+        builder.expr().str(&*type_name),
+        impl_generics,
+        ty,
+        struct_def,
+        field::container_default(item),
+        field::deny_unknown_fields(item),
+        field::rename_all(cx, item),
+        Some(lifetime),
+    )
+}
+
+/// Deserializes a newtype struct that borrows from the input from what its field deserializes
+/// from, the way it is serialized.
+fn deserialize_borrowed_newtype_struct(
+    cx: &ExtCtxt,
+    type_ident: Ident,
+    field: &ast::StructField,
+) -> P<ast::Expr> {
+    // A plain `Cow` is always deserialized as owned, so it goes through `BorrowedCow`.
+    let value = if bound::is_cow(&field.node.ty) {
+        quote_expr!(cx, {
+            let value: ::serde::de::impls::BorrowedCow<_> =
+                try!(::serde::de::DeserializeBorrowed::deserialize_borrowed(deserializer));
+            value.0
+        })
+    } else {
+        quote_expr!(cx, try!(::serde::de::DeserializeBorrowed::deserialize_borrowed(deserializer)))
+    };
+
+    quote_expr!(cx, Ok($type_ident($value)))
+}
+
+/// Deserializes a `#[serde(transparent)]` struct from what its only field deserializes from.
+fn deserialize_transparent(cx: &ExtCtxt, item: &Item) -> P<ast::Expr> {
+    let struct_def = match item.node {
+        ast::ItemStruct(ref struct_def, _) => struct_def,
+        _ => cx.bug("expected ItemStruct in #[serde(transparent)]"),
+    };
+
+    let type_ident = item.ident;
+
+    let value = match struct_def.fields[0].node.kind {
+        ast::NamedField(name, _) => quote_expr!(cx, $type_ident { $name: value }),
+        ast::UnnamedField(_) => quote_expr!(cx, $type_ident(value)),
+    };
+
+    quote_expr!(cx, {
+        let value = try!(::serde::de::Deserialize::deserialize(deserializer));
+        Ok($value)
+    })
+}
+
 fn deserialize_item_struct(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
@@ -115,12 +364,26 @@ fn deserialize_item_struct(
         }
     }
 
+    let type_name = field::container_name(cx, item, Direction::Deserialize);
+    let type_name = builder.expr().str(&*type_name);
+
     match (named_fields.is_empty(), unnamed_fields == 0) {
         (true, true) => {
             deserialize_unit_struct(
                 cx,
                 &builder,
                 item.ident,
+                type_name,
+            )
+        }
+        (true, false) if unnamed_fields == 1 => {
+            deserialize_newtype_struct(
+                cx,
+                &builder,
+                item.ident,
+                type_name,
+                impl_generics,
+                ty,
             )
         }
         (true, false) => {
@@ -128,6 +391,7 @@ fn deserialize_item_struct(
                 cx,
                 &builder,
                 item.ident,
+                type_name,
                 impl_generics,
                 ty,
                 unnamed_fields,
@@ -138,9 +402,14 @@ fn deserialize_item_struct(
                 cx,
                 &builder,
                 item.ident,
+                type_name,
                 impl_generics,
                 ty,
                 struct_def,
+                field::container_default(item),
+                field::deny_unknown_fields(item),
+                field::rename_all(cx, item),
+                None,
             )
         }
         (false, false) => {
@@ -155,20 +424,27 @@ fn deserialize_visitor(
     builder: &aster::AstBuilder,
     trait_generics: &ast::Generics,
 ) -> (P<ast::Item>, P<ast::Ty>, P<ast::Expr>) {
-    if trait_generics.ty_params.is_empty() {
+    if trait_generics.ty_params.is_empty() && trait_generics.lifetimes.is_empty() {
         (
             builder.item().tuple_struct("__Visitor").build(),
             builder.ty().id("__Visitor"),
             builder.expr().id("__Visitor"),
         )
     } else {
+        // Lifetimes are held as `PhantomData<&'a ()>`.
+        let lifetime_tys = trait_generics.lifetimes.iter().map(|lifetime_def| {
+            builder.ty().phantom_data().ref_()
+                .lifetime(lifetime_def.lifetime.name)
+                .build_ty(builder.ty().unit())
+        });
+
         (
             builder.item().tuple_struct("__Visitor")
                 .generics().with(trait_generics.clone()).build()
                 .with_tys(
-                    trait_generics.ty_params.iter().map(|ty_param| {
+                    lifetime_tys.chain(trait_generics.ty_params.iter().map(|ty_param| {
                         builder.ty().phantom_data().id(ty_param.ident)
-                    })
+                    }))
                 )
                 .build(),
             builder.ty().path()
@@ -176,9 +452,8 @@ fn deserialize_visitor(
                 .build(),
             builder.expr().call().id("__Visitor")
                 .with_args(
-                    trait_generics.ty_params.iter().map(|_| {
-                        builder.expr().phantom_data()
-                    })
+                    (0 .. trait_generics.lifetimes.len() + trait_generics.ty_params.len())
+                        .map(|_| builder.expr().phantom_data())
                 )
                 .build(),
         )
@@ -189,8 +464,9 @@ fn deserialize_unit_struct(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
     type_ident: Ident,
+    type_name: P<ast::Expr>,
 ) -> P<ast::Expr> {
-    let type_name = builder.expr().str(type_ident);
+    let expecting = builder.expr().str(&*format!("unit struct {}", type_ident));
 
     quote_expr!(cx, {
         struct __Visitor;
@@ -198,6 +474,10 @@ fn deserialize_unit_struct(
         impl ::serde::de::Visitor for __Visitor {
             type Value = $type_ident;
 
+            fn expecting(&self) -> &'static str {
+                $expecting
+            }
+
             #[inline]
             fn visit_unit<E>(&mut self) -> ::std::result::Result<$type_ident, E>
                 where E: ::serde::de::Error,
@@ -222,6 +502,7 @@ fn deserialize_tuple_struct(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
     type_ident: Ident,
+    type_name: P<ast::Expr>,
     impl_generics: &ast::Generics,
     ty: P<ast::Ty>,
     fields: usize,
@@ -240,7 +521,7 @@ fn deserialize_tuple_struct(
         fields,
     );
 
-    let type_name = builder.expr().str(type_ident);
+    let expecting = builder.expr().str(&*format!("tuple struct {}", type_ident));
 
     quote_expr!(cx, {
         $visitor_item
@@ -248,6 +529,10 @@ fn deserialize_tuple_struct(
         impl $impl_generics ::serde::de::Visitor for $visitor_ty $where_clause {
             type Value = $ty;
 
+            fn expecting(&self) -> &'static str {
+                $expecting
+            }
+
             fn visit_seq<__V>(&mut self, mut visitor: __V) -> ::std::result::Result<$ty, __V::Error>
                 where __V: ::serde::de::SeqVisitor,
             {
@@ -259,12 +544,68 @@ fn deserialize_tuple_struct(
     })
 }
 
+fn deserialize_newtype_struct(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    type_ident: Ident,
+    type_name: P<ast::Expr>,
+    impl_generics: &ast::Generics,
+    ty: P<ast::Ty>,
+) -> P<ast::Expr> {
+    let where_clause = &impl_generics.where_clause;
+
+    let (visitor_item, visitor_ty, visitor_expr) = deserialize_visitor(
+        builder,
+        impl_generics,
+    );
+
+    let visit_seq_expr = deserialize_seq(
+        cx,
+        builder,
+        builder.path().id(type_ident).build(),
+        1,
+    );
+
+    let expecting = builder.expr().str(&*format!("newtype struct {}", type_ident));
+
+    quote_expr!(cx, {
+        $visitor_item
+
+        impl $impl_generics ::serde::de::Visitor for $visitor_ty $where_clause {
+            type Value = $ty;
+
+            fn expecting(&self) -> &'static str {
+                $expecting
+            }
+
+            fn visit_newtype_struct<__E>(&mut self, deserializer: &mut __E)
+                                         -> ::std::result::Result<$ty, __E::Error>
+                where __E: ::serde::de::Deserializer,
+            {
+                let value = try!(::serde::de::Deserialize::deserialize(deserializer));
+                Ok($type_ident(value))
+            }
+
+            fn visit_seq<__V>(&mut self, mut visitor: __V) -> ::std::result::Result<$ty, __V::Error>
+                where __V: ::serde::de::SeqVisitor,
+            {
+                $visit_seq_expr
+            }
+        }
+
+        deserializer.visit_newtype_struct($type_name, $visitor_expr)
+    })
+}
+
 fn deserialize_seq(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
     struct_path: ast::Path,
     fields: usize,
 ) -> P<ast::Expr> {
+    let expected = format!("a sequence of length {}", fields);
+    let expected = builder.expr().str(&*expected);
+
     let let_values: Vec<P<ast::Stmt>> = (0 .. fields)
         .map(|i| {
             let name = builder.id(format!("__field{}", i));
@@ -272,7 +613,7 @@ fn deserialize_seq(
                 let $name = match try!(visitor.visit()) {
                     Some(value) => value,
                     None => {
-                        return Err(::serde::de::Error::end_of_stream_error());
+                        return Err(::serde::de::Error::invalid_length($i, $expected));
                     }
                 };
             ).unwrap()
@@ -297,9 +638,14 @@ fn deserialize_struct(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
     type_ident: Ident,
+    type_name: P<ast::Expr>,
     impl_generics: &ast::Generics,
     ty: P<ast::Ty>,
     struct_def: &StructDef,
+    container_default: bool,
+    deny_unknown_fields: bool,
+    rename_all: Option<field::RenameRule>,
+    lifetime: Option<&str>,
 ) -> P<ast::Expr> {
     let where_clause = &impl_generics.where_clause;
 
@@ -311,11 +657,54 @@ fn deserialize_struct(
     let (field_visitor, visit_map_expr) = deserialize_struct_visitor(
         cx,
         builder,
+        impl_generics,
+        ty.clone(),
         struct_def,
         builder.path().id(type_ident).build(),
+        container_default,
+        deny_unknown_fields,
+        rename_all,
+        lifetime.is_some(),
     );
 
-    let type_name = builder.expr().str(type_ident);
+    let expecting = builder.expr().str(&*format!("struct {}", type_ident));
+
+    // A struct that borrows from the input can only be built from a map whose values borrow too.
+    if let Some(lifetime) = lifetime {
+        let borrowed_visitor = builder.path().global()
+            .id("serde").id("de").segment("BorrowedVisitor").lifetime(lifetime).build()
+            .build();
+
+        let borrowed_map_visitor = builder.path().global()
+            .id("serde").id("de").segment("BorrowedMapVisitor").lifetime(lifetime).build()
+            .build();
+
+        return quote_expr!(cx, {
+            $field_visitor
+
+            $visitor_item
+
+            impl $impl_generics ::serde::de::Visitor for $visitor_ty $where_clause {
+                type Value = $ty;
+
+                fn expecting(&self) -> &'static str {
+                    $expecting
+                }
+            }
+
+            impl $impl_generics $borrowed_visitor for $visitor_ty $where_clause {
+                #[inline]
+                fn visit_borrowed_map<__V>(&mut self, mut visitor: __V)
+                                           -> ::std::result::Result<$ty, __V::Error>
+                    where __V: $borrowed_map_visitor,
+                {
+                    $visit_map_expr
+                }
+            }
+
+            deserializer.visit_borrowed($visitor_expr)
+        });
+    }
 
     quote_expr!(cx, {
         $field_visitor
@@ -325,6 +714,10 @@ fn deserialize_struct(
         impl $impl_generics ::serde::de::Visitor for $visitor_ty $where_clause {
             type Value = $ty;
 
+            fn expecting(&self) -> &'static str {
+                $expecting
+            }
+
             #[inline]
             fn visit_map<__V>(&mut self, mut visitor: __V) -> ::std::result::Result<$ty, __V::Error>
                 where __V: ::serde::de::MapVisitor,
@@ -340,21 +733,54 @@ fn deserialize_struct(
 fn deserialize_item_enum(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
-    type_ident: Ident,
+    item: &Item,
     impl_generics: &ast::Generics,
     ty: P<ast::Ty>,
     enum_def: &EnumDef,
+    deny_unknown_fields: bool,
 ) -> P<ast::Expr> {
     let where_clause = &impl_generics.where_clause;
 
-    let type_name = builder.expr().str(type_ident);
+    let type_ident = item.ident;
+    let type_name = field::container_name(cx, item, Direction::Deserialize);
+    let type_name = builder.expr().str(&*type_name);
+    let rename_all = field::rename_all(cx, item);
+
+    if let Some(repr) = field::enum_repr(cx, item) {
+        return deserialize_c_like_enum(cx, builder, item, ty, enum_def, rename_all, repr);
+    }
+
+    let tag = field::enum_tag(cx, item);
+
+    if let EnumTag::Untagged = tag {
+        return deserialize_untagged_enum(
+            cx,
+            builder,
+            type_ident,
+            impl_generics,
+            ty,
+            enum_def,
+            deny_unknown_fields,
+        );
+    }
 
     let variant_visitor = deserialize_field_visitor(
         cx,
         builder,
         enum_def.variants.iter()
-            .map(|variant| builder.expr().str(variant.node.name))
-            .collect()
+            .map(|variant| {
+                let name = field::variant_name(cx, variant, rename_all, Direction::Deserialize);
+                builder.expr().str(&*name)
+            })
+            .collect(),
+        enum_def.variants.iter()
+            .map(|variant| {
+                field::variant_aliases(cx, variant).iter()
+                    .map(|alias| builder.expr().str(&**alias))
+                    .collect()
+            })
+            .collect(),
+        UnknownKey::Error,
     );
 
     // Match arms to extract a variant from a string
@@ -365,19 +791,80 @@ fn deserialize_item_enum(
                 .id("__Field").id(format!("__field{}", i))
                 .build();
 
-            let expr = deserialize_variant(
-                cx,
-                builder,
-                type_ident,
-                impl_generics,
-                ty.clone(),
-                variant,
-            );
+            let expr = match (&tag, &variant.node.kind) {
+                // The tag was the only entry an internally tagged unit variant needs.
+                (&EnumTag::Internal(_), &ast::TupleVariantKind(ref args)) if args.is_empty() => {
+                    let variant_ident = variant.node.name;
+                    if deny_unknown_fields {
+                        quote_expr!(cx, {
+                            try!(visitor.deny_entries());
+                            Ok($type_ident::$variant_ident)
+                        })
+                    } else {
+                        quote_expr!(cx, Ok($type_ident::$variant_ident))
+                    }
+                }
+                (&EnumTag::Internal(_), &ast::TupleVariantKind(_)) => {
+                    cx.span_fatal(
+                        variant.span,
+                        "internally tagged enums cannot contain tuple variants",
+                    )
+                }
+                _ => {
+                    deserialize_variant(
+                        cx,
+                        builder,
+                        type_ident,
+                        impl_generics,
+                        ty.clone(),
+                        variant,
+                        deny_unknown_fields,
+                    )
+                }
+            };
 
             quote_arm!(cx, $variant_name => { $expr })
         })
         .collect();
 
+    // The tag may come after the variant's data, so the data is buffered until the tag is found,
+    // and then replayed into the variant's visitor.
+    let tagged_visitor_expr = match tag {
+        EnumTag::External | EnumTag::Untagged => None,
+        EnumTag::Internal(ref tag) => {
+            let tag = builder.expr().str(&**tag);
+            Some(quote_expr!(cx,
+                ::serde::de::content::TaggedContentVisitor::<__Field>::new($tag)
+            ))
+        }
+        EnumTag::Adjacent(ref tag, ref content) => {
+            let tag = builder.expr().str(&**tag);
+            let content = builder.expr().str(&**content);
+            Some(quote_expr!(cx,
+                ::serde::de::content::AdjacentlyTaggedContentVisitor::<__Field>::new($tag, $content)
+            ))
+        }
+    };
+
+    if let Some(tagged_visitor_expr) = tagged_visitor_expr {
+        return quote_expr!(cx, {
+            $variant_visitor
+
+            #[allow(unused_imports)]
+            use ::serde::de::VariantVisitor;
+
+            let __tagged = try!(deserializer.visit_named_map($type_name, $tagged_visitor_expr));
+
+            let mut visitor = ::serde::de::content::ContentDeserializer::<__D::Error>::new(
+                __tagged.content,
+            );
+
+            match __tagged.tag {
+                $variant_arms
+            }
+        });
+    }
+
     let (visitor_item, visitor_ty, visitor_expr) = deserialize_visitor(
         builder,
         impl_generics,
@@ -404,6 +891,163 @@ fn deserialize_item_enum(
     })
 }
 
+/// Deserializes a fieldless enum from either its discriminant or its variant name.
+fn deserialize_c_like_enum(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    item: &Item,
+    ty: P<ast::Ty>,
+    enum_def: &EnumDef,
+    rename_all: Option<field::RenameRule>,
+    repr: EnumRepr,
+) -> P<ast::Expr> {
+    let type_ident = item.ident;
+
+    // Enums without a `repr` attribute have `isize` discriminants.
+    let int_ty = match repr {
+        EnumRepr::Int(ref int_ty) => builder.ty().id(int_ty),
+        EnumRepr::Str => builder.ty().id("isize"),
+    };
+
+    let discriminant_arms: Vec<ast::Arm> = enum_def.variants.iter()
+        .map(|variant| {
+            let variant_ident = variant.node.name;
+
+            quote_arm!(cx,
+                Some(discriminant) if discriminant == $type_ident::$variant_ident as $int_ty => {
+                    Ok($type_ident::$variant_ident)
+                }
+            )
+        })
+        .collect();
+
+    let name_arms: Vec<ast::Arm> = enum_def.variants.iter()
+        .flat_map(|variant| {
+            let variant_ident = variant.node.name;
+            let name = field::variant_name(cx, variant, rename_all, Direction::Deserialize);
+
+            Some(name).into_iter()
+                .chain(field::variant_aliases(cx, variant).into_iter())
+                .map(|name| {
+                    let name = builder.expr().str(&*name);
+                    quote_arm!(cx, $name => { Ok($type_ident::$variant_ident) })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let type_name = field::container_name(cx, item, Direction::Deserialize);
+    let type_name = builder.expr().str(&*type_name);
+    let expecting = builder.expr().str(&*format!("enum {}", type_ident));
+
+    quote_expr!(cx, {
+        struct __Visitor;
+
+        impl ::serde::de::Visitor for __Visitor {
+            type Value = $ty;
+
+            fn expecting(&self) -> &'static str {
+                $expecting
+            }
+
+            fn visit_i64<__E>(&mut self, value: i64) -> ::std::result::Result<$ty, __E>
+                where __E: ::serde::de::Error,
+            {
+                let discriminant: Option<$int_ty> = ::std::num::FromPrimitive::from_i64(value);
+
+                match discriminant {
+                    $discriminant_arms
+                    _ => {
+                        let msg = format!("{} is not a discriminant of enum {}", value, $type_name);
+                        Err(::serde::de::Error::invalid_value(&msg))
+                    }
+                }
+            }
+
+            fn visit_u64<__E>(&mut self, value: u64) -> ::std::result::Result<$ty, __E>
+                where __E: ::serde::de::Error,
+            {
+                let discriminant: Option<$int_ty> = ::std::num::FromPrimitive::from_u64(value);
+
+                match discriminant {
+                    $discriminant_arms
+                    _ => {
+                        let msg = format!("{} is not a discriminant of enum {}", value, $type_name);
+                        Err(::serde::de::Error::invalid_value(&msg))
+                    }
+                }
+            }
+
+            fn visit_str<__E>(&mut self, value: &str) -> ::std::result::Result<$ty, __E>
+                where __E: ::serde::de::Error,
+            {
+                match value {
+                    $name_arms
+                    _ => {
+                        let msg = format!("unknown variant `{}` of enum {}", value, $type_name);
+                        Err(::serde::de::Error::invalid_value(&msg))
+                    }
+                }
+            }
+        }
+
+        deserializer.visit(__Visitor)
+    })
+}
+
+/// Buffers the input, then tries each variant against it in order, returning the first that
+/// deserializes successfully.
+fn deserialize_untagged_enum(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    type_ident: Ident,
+    impl_generics: &ast::Generics,
+    ty: P<ast::Ty>,
+    enum_def: &EnumDef,
+    deny_unknown_fields: bool,
+) -> P<ast::Expr> {
+    let attempts: Vec<P<ast::Stmt>> = enum_def.variants.iter()
+        .map(|variant| {
+            let expr = deserialize_variant(
+                cx,
+                builder,
+                type_ident,
+                impl_generics,
+                ty.clone(),
+                variant,
+                deny_unknown_fields,
+            );
+
+            quote_stmt!(cx,
+                if let Ok(value) = (|| -> ::std::result::Result<$ty, __D::Error> {
+                    #[allow(unused_imports)]
+                    use ::serde::de::VariantVisitor;
+
+                    let mut visitor = ::serde::de::content::ContentDeserializer::<__D::Error>::new(
+                        __content.clone(),
+                    );
+                    $expr
+                })() {
+                    return Ok(value);
+                }
+            ).unwrap()
+        })
+        .collect();
+
+    let error = builder.expr().str(
+        &*format!("data did not match any variant of untagged enum {}", type_ident));
+
+    quote_expr!(cx, {
+        let __content: ::serde::de::content::Content = try!(
+            ::serde::de::Deserialize::deserialize(deserializer)
+        );
+
+        $attempts
+
+        Err(::serde::de::Error::custom($error))
+    })
+}
+
 fn deserialize_variant(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
@@ -411,6 +1055,7 @@ fn deserialize_variant(
     generics: &ast::Generics,
     ty: P<ast::Ty>,
     variant: &ast::Variant,
+    deny_unknown_fields: bool,
 ) -> P<ast::Expr> {
     let variant_ident = variant.node.name;
 
@@ -421,6 +1066,12 @@ fn deserialize_variant(
                 Ok($type_ident::$variant_ident)
             })
         }
+        ast::TupleVariantKind(ref args) if args.len() == 1 => {
+            quote_expr!(cx, {
+                let value = try!(visitor.visit_newtype());
+                Ok($type_ident::$variant_ident(value))
+            })
+        }
         ast::TupleVariantKind(ref args) => {
             deserialize_tuple_variant(
                 cx,
@@ -441,6 +1092,7 @@ fn deserialize_variant(
                 generics,
                 ty,
                 struct_def,
+                deny_unknown_fields,
             )
         }
     }
@@ -469,12 +1121,19 @@ fn deserialize_tuple_variant(
         fields,
     );
 
+    let expecting = builder.expr().str(
+        &*format!("tuple variant {}::{}", type_ident, variant_ident));
+
     quote_expr!(cx, {
         $visitor_item
 
         impl $generics ::serde::de::Visitor for $visitor_ty $where_clause {
             type Value = $ty;
 
+            fn expecting(&self) -> &'static str {
+                $expecting
+            }
+
             fn visit_seq<__V>(&mut self, mut visitor: __V) -> ::std::result::Result<$ty, __V::Error>
                 where __V: ::serde::de::SeqVisitor,
             {
@@ -494,14 +1153,21 @@ fn deserialize_struct_variant(
     generics: &ast::Generics,
     ty: P<ast::Ty>,
     struct_def: &ast::StructDef,
+    deny_unknown_fields: bool,
 ) -> P<ast::Expr> {
     let where_clause = &generics.where_clause;
 
     let (field_visitor, field_expr) = deserialize_struct_visitor(
         cx,
         builder,
+        generics,
+        ty.clone(),
         struct_def,
         builder.path().id(type_ident).id(variant_ident).build(),
+        false,
+        deny_unknown_fields,
+        None,
+        false,
     );
 
     let (visitor_item, visitor_ty, visitor_expr) = deserialize_visitor(
@@ -509,6 +1175,9 @@ fn deserialize_struct_variant(
         generics,
     );
 
+    let expecting = builder.expr().str(
+        &*format!("struct variant {}::{}", type_ident, variant_ident));
+
     quote_expr!(cx, {
         $field_visitor
 
@@ -517,6 +1186,10 @@ fn deserialize_struct_variant(
         impl $generics ::serde::de::Visitor for $visitor_ty $where_clause {
             type Value = $ty;
 
+            fn expecting(&self) -> &'static str {
+                $expecting
+            }
+
             fn visit_map<__V>(&mut self, mut visitor: __V) -> ::std::result::Result<$ty, __V::Error>
                 where __V: ::serde::de::MapVisitor,
             {
@@ -528,31 +1201,72 @@ fn deserialize_struct_variant(
     })
 }
 
+/// What the generated `__Field` visitor does with a key it doesn't recognize.
+enum UnknownKey {
+    /// Fail with an unknown variant error naming the expected variants.
+    Error,
+    /// Produce `__Field::__ignore`, so the value can be skipped.
+    Ignore,
+    /// Fail with an `unknown_field` error that lists the expected keys.
+    Deny,
+}
+
+/// Generates the `__Field` enum and its `Deserialize` impl, which maps each of `field_exprs` or
+/// its aliases to a variant.
 fn deserialize_field_visitor(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
     field_exprs: Vec<P<ast::Expr>>,
+    alias_exprs: Vec<Vec<P<ast::Expr>>>,
+    unknown_key: UnknownKey,
 ) -> Vec<P<ast::Item>> {
     // Create the field names for the fields.
     let field_idents: Vec<ast::Ident> = (0 .. field_exprs.len())
         .map(|i| builder.id(format!("__field{}", i)))
         .collect();
 
+    let ignore_variant = match unknown_key {
+        UnknownKey::Ignore => Some(builder.variant("__ignore").tuple().build()),
+        UnknownKey::Error | UnknownKey::Deny => None,
+    };
+
     let field_enum = builder.item()
         .attr().allow(&["non_camel_case_types"])
         .enum_("__Field")
         .with_variants(
-            field_idents.iter().map(|field_ident| {
-                builder.variant(field_ident).tuple().build()
-            })
+            field_idents.iter()
+                .map(|field_ident| builder.variant(field_ident).tuple().build())
+                .chain(ignore_variant.into_iter())
         )
         .build();
 
+    let unknown_expr = match unknown_key {
+        UnknownKey::Error => {
+            let variants_expr = cx.expr_vec_slice(DUMMY_SP, field_exprs.clone());
+
+            quote_expr!(cx, {
+                const VARIANTS: &'static [&'static str] = $variants_expr;
+                Err(::serde::de::Error::unknown_variant(value, VARIANTS))
+            })
+        }
+        UnknownKey::Ignore => quote_expr!(cx, Ok(__Field::__ignore)),
+        UnknownKey::Deny => {
+            let fields_expr = cx.expr_vec_slice(DUMMY_SP, field_exprs.clone());
+
+            quote_expr!(cx, {
+                const FIELDS: &'static [&'static str] = $fields_expr;
+                Err(::serde::de::Error::unknown_field(value, FIELDS))
+            })
+        }
+    };
+
     // Match arms to extract a field from a string
     let field_arms: Vec<_> = field_idents.iter()
-        .zip(field_exprs.into_iter())
-        .map(|(field_ident, field_expr)| {
-            quote_arm!(cx, $field_expr => { Ok(__Field::$field_ident) })
+        .zip(field_exprs.into_iter().zip(alias_exprs.into_iter()))
+        .flat_map(|(field_ident, (field_expr, alias_exprs))| {
+            Some(field_expr).into_iter()
+                .chain(alias_exprs.into_iter())
+                .map(move |expr| quote_arm!(cx, $expr => { Ok(__Field::$field_ident) }))
         })
         .collect();
 
@@ -575,7 +1289,7 @@ fn deserialize_field_visitor(
                         {
                             match value {
                                 $field_arms
-                                _ => Err(::serde::de::Error::syntax_error()),
+                                _ => $unknown_expr,
                             }
                         }
                     }
@@ -590,64 +1304,257 @@ fn deserialize_field_visitor(
 fn deserialize_struct_visitor(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
+    generics: &ast::Generics,
+    ty: P<ast::Ty>,
     struct_def: &ast::StructDef,
     struct_path: ast::Path,
+    container_default: bool,
+    deny_unknown_fields: bool,
+    rename_all: Option<field::RenameRule>,
+    borrowed: bool,
 ) -> (Vec<P<ast::Item>>, P<ast::Expr>) {
+    // Fields marked `skip_deserializing` or `flatten` are not recognized as keys.
+    let field_exprs = field::struct_field_strs(
+        cx,
+        builder,
+        struct_def,
+        rename_all,
+        Direction::Deserialize,
+    );
+
+    let alias_exprs = field::struct_field_aliases(builder, struct_def);
+
+    let (field_exprs, alias_exprs): (Vec<_>, Vec<_>) = field_exprs.into_iter()
+        .zip(alias_exprs.into_iter())
+        .zip(struct_def.fields.iter())
+        .filter(|&(_, field)| is_field_key(field))
+        .map(|(exprs, _)| exprs)
+        .unzip();
+
+    if deny_unknown_fields {
+        if let Some(field) = struct_def.fields.iter().find(|field| field::flatten(field)) {
+            cx.span_fatal(field.span, "#[serde(flatten)] cannot be used with deny_unknown_fields");
+        }
+    }
+
+    let unknown_key = if deny_unknown_fields {
+        UnknownKey::Deny
+    } else {
+        UnknownKey::Ignore
+    };
+
     let field_visitor = deserialize_field_visitor(
         cx,
         builder,
-        field::struct_field_strs(cx, builder, struct_def),
+        field_exprs,
+        alias_exprs,
+        unknown_key,
     );
 
     let visit_map_expr = deserialize_map(
         cx,
         builder,
+        generics,
+        ty,
         struct_path,
         struct_def,
+        container_default,
+        deny_unknown_fields,
+        rename_all,
+        borrowed,
     );
 
     (field_visitor, visit_map_expr)
 }
 
+/// Returns true if the field is deserialized from an entry with its own key.
+fn is_field_key(field: &ast::StructField) -> bool {
+    !field::skip_deserializing(field) && !field::flatten(field)
+}
+
 fn deserialize_map(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
+    generics: &ast::Generics,
+    ty: P<ast::Ty>,
     struct_path: ast::Path,
     struct_def: &StructDef,
+    container_default: bool,
+    deny_unknown_fields: bool,
+    rename_all: Option<field::RenameRule>,
+    borrowed: bool,
 ) -> P<ast::Expr> {
     // Create the field names for the fields.
     let field_names: Vec<ast::Ident> = (0 .. struct_def.fields.len())
         .map(|i| builder.id(format!("__field{}", i)))
         .collect();
 
-    // Declare each field.
+    let has_flatten = struct_def.fields.iter()
+        .any(|field| field::flatten(field) && !field::skip_deserializing(field));
+
+    // Declare each field that will be deserialized.
     let let_values: Vec<P<ast::Stmt>> = field_names.iter()
-        .map(|field_name| quote_stmt!(cx, let mut $field_name = None;).unwrap())
+        .zip(struct_def.fields.iter())
+        .filter(|&(_, field)| is_field_key(field))
+        .map(|(field_name, _)| quote_stmt!(cx, let mut $field_name = None;).unwrap())
         .collect();
 
-    // Match arms to extract a value for a field.
+    // Unknown keys are skipped, unless `deny_unknown_fields` already rejected them. When there
+    // are flattened fields, they are kept for those fields instead.
+    let ignore_arm = if deny_unknown_fields {
+        None
+    } else if has_flatten {
+        Some(quote_arm!(cx,
+            __Field::__ignore => {
+                __collect.push(Some((__key, try!(visitor.visit_value()))));
+            }
+        ))
+    } else {
+        Some(quote_arm!(cx,
+            __Field::__ignore => {
+                try!(visitor.visit_value::<::serde::de::impls::IgnoredAny>());
+            }
+        ))
+    };
+
+    let key_names = field::struct_field_strs(
+        cx,
+        builder,
+        struct_def,
+        rename_all,
+        Direction::Deserialize,
+    );
+
+    // Match arms to extract a value for a field. The `__Field` variants are numbered by the
+    // fields that are deserialized, skipping the others. A key that appears twice is an error
+    // rather than overwriting the first value.
     let value_arms: Vec<ast::Arm> = field_names.iter()
-        .map(|field_name| {
-            quote_arm!(cx,
-                __Field::$field_name => {
-                    $field_name = Some(try!(visitor.visit_value()));
+        .zip(key_names.into_iter())
+        .zip(struct_def.fields.iter())
+        .filter(|&(_, field)| is_field_key(field))
+        .enumerate()
+        .map(|(i, ((field_name, key_name), field))| {
+            let variant_name = builder.id(format!("__field{}", i));
+
+            let check_duplicate = quote_stmt!(cx,
+                if $field_name.is_some() {
+                    return Err(::serde::de::Error::duplicate_field($key_name));
                 }
-            )
+            ).unwrap();
+
+            match field::deserialize_with(cx, builder, field) {
+                Some(path) => {
+                    let (wrapper_items, wrapper_ty) = deserialize_with(
+                        cx,
+                        builder,
+                        generics,
+                        ty.clone(),
+                        field.node.ty.clone(),
+                        path,
+                    );
+
+                    quote_arm!(cx,
+                        __Field::$variant_name => {
+                            $check_duplicate
+                            $wrapper_items
+                            let value: $wrapper_ty = try!(visitor.visit_value());
+                            $field_name = Some(value.value);
+                        }
+                    )
+                }
+                None if field::borrow(field) => {
+                    if !borrowed {
+                        cx.span_fatal(field.span, "#[serde(borrow)] requires a field whose type \
+                                                   has a lifetime parameter");
+                    }
+
+                    // A plain `Cow` is always deserialized as owned, so it goes through
+                    // `BorrowedCow` to borrow when it can.
+                    if bound::is_cow(&field.node.ty) {
+                        quote_arm!(cx,
+                            __Field::$variant_name => {
+                                $check_duplicate
+                                let value: ::serde::de::impls::BorrowedCow<_> =
+                                    try!(visitor.visit_borrowed_value());
+                                $field_name = Some(value.0);
+                            }
+                        )
+                    } else {
+                        quote_arm!(cx,
+                            __Field::$variant_name => {
+                                $check_duplicate
+                                $field_name = Some(try!(visitor.visit_borrowed_value()));
+                            }
+                        )
+                    }
+                }
+                None if borrowed => {
+                    quote_arm!(cx,
+                        __Field::$variant_name => {
+                            $check_duplicate
+                            $field_name = Some(try!(visitor.visit_borrowed_value()));
+                        }
+                    )
+                }
+                None => {
+                    quote_arm!(cx,
+                        __Field::$variant_name => {
+                            $check_duplicate
+                            $field_name = Some(try!(visitor.visit_value()));
+                        }
+                    )
+                }
+            }
         })
+        .chain(ignore_arm.into_iter())
         .collect();
 
+    // With a container-level `#[serde(default)]`, missing fields are moved out of the
+    // container's `Default` value.
+    let let_default: Vec<P<ast::Stmt>> = if container_default {
+        vec![quote_stmt!(cx, let __default: $ty = ::std::default::Default::default();).unwrap()]
+    } else {
+        vec![]
+    };
+
     let extract_values: Vec<P<ast::Stmt>> = field_names.iter()
         .zip(struct_def.fields.iter())
         .map(|(field_name, field)| {
-            let name_str = match field.node.kind {
-                ast::NamedField(name, _) => builder.expr().str(name),
+            let name = match field.node.kind {
+                ast::NamedField(name, _) => name,
                 ast::UnnamedField(_) => panic!("struct contains unnamed fields"),
             };
 
-            let missing_expr = if field::default_value(field) {
-                quote_expr!(cx, ::std::default::Default::default())
-            } else {
-                quote_expr!(cx, try!(visitor.missing_field($name_str)))
+            let default_expr = match field::default_value(cx, builder, field) {
+                Some(default_expr) => Some(default_expr),
+                None if container_default => Some(quote_expr!(cx, __default.$name)),
+                None => None,
+            };
+
+            if field::skip_deserializing(field) {
+                let default_expr = default_expr.unwrap_or_else(|| {
+                    quote_expr!(cx, ::std::default::Default::default())
+                });
+
+                return quote_stmt!(cx, let $field_name = $default_expr;).unwrap();
+            }
+
+            if field::flatten(field) {
+                return quote_stmt!(cx,
+                    let $field_name = try!(::serde::de::Deserialize::deserialize(
+                        &mut ::serde::de::content::FlatMapDeserializer::<__V::Error>::new(
+                            &mut __collect,
+                        ),
+                    ));
+                ).unwrap();
+            }
+
+            let missing_expr = match default_expr {
+                Some(default_expr) => default_expr,
+                None => {
+                    let name_str = builder.expr().str(name);
+                    quote_expr!(cx, try!(visitor.missing_field($name_str)))
+                }
             };
 
             quote_stmt!(cx,
@@ -675,14 +1582,53 @@ fn deserialize_map(
         )
         .build();
 
+    // With flattened fields, each key is first buffered so that it can be kept if it isn't one
+    // of the struct's own.
+    let visit_keys = if has_flatten {
+        quote_expr!(cx, {
+            while let Some(__key) = try!(visitor.visit_key::<::serde::de::content::Content>()) {
+                let key = try!(::serde::de::Deserialize::deserialize(
+                    &mut ::serde::de::content::ContentDeserializer::<__V::Error>::new(
+                        __key.clone(),
+                    ),
+                ));
+
+                match key {
+                    $value_arms
+                }
+            }
+        })
+    } else {
+        quote_expr!(cx, {
+            while let Some(key) = try!(visitor.visit_key()) {
+                match key {
+                    $value_arms
+                }
+            }
+        })
+    };
+
+    let let_collect: Vec<P<ast::Stmt>> = if has_flatten {
+        vec![
+            quote_stmt!(cx,
+                let mut __collect: Vec<Option<(
+                    ::serde::de::content::Content,
+                    ::serde::de::content::Content,
+                )>> = Vec::new();
+            ).unwrap(),
+        ]
+    } else {
+        vec![]
+    };
+
     quote_expr!(cx, {
         $let_values
 
-        while let Some(key) = try!(visitor.visit_key()) {
-            match key {
-                $value_arms
-            }
-        }
+        $let_collect
+
+        $visit_keys
+
+        $let_default
 
         $extract_values
 
@@ -691,3 +1637,49 @@ fn deserialize_map(
         Ok($result)
     })
 }
+
+/// Returns the items of a type whose `Deserialize` impl calls the field's `deserialize_with`
+/// function, along with the type itself.
+fn deserialize_with(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    generics: &ast::Generics,
+    ty: P<ast::Ty>,
+    field_ty: P<ast::Ty>,
+    path: ast::Path,
+) -> (Vec<P<ast::Item>>, P<ast::Ty>) {
+    let where_clause = &generics.where_clause;
+
+    let deserialize_expr = builder.expr().call()
+        .build_path(path)
+        .with_args(Some(quote_expr!(cx, deserializer)))
+        .build();
+
+    let wrapper_ty = builder.ty().path()
+        .segment("__DeserializeWith").with_generics(generics.clone()).build()
+        .build();
+
+    let wrapper_items = vec![
+        quote_item!(cx,
+            struct __DeserializeWith $generics $where_clause {
+                value: $field_ty,
+                phantom: ::std::marker::PhantomData<$ty>,
+            }
+        ).unwrap(),
+
+        quote_item!(cx,
+            impl $generics ::serde::de::Deserialize for $wrapper_ty $where_clause {
+                fn deserialize<__D>(deserializer: &mut __D) -> ::std::result::Result<Self, __D::Error>
+                    where __D: ::serde::de::Deserializer,
+                {
+                    Ok(__DeserializeWith {
+                        value: try!($deserialize_expr),
+                        phantom: ::std::marker::PhantomData,
+                    })
+                }
+            }
+        ).unwrap(),
+    ];
+
+    (wrapper_items, wrapper_ty)
+}
@@ -1,52 +1,123 @@
+use std::ascii::AsciiExt;
+
 use syntax::ast;
 use syntax::attr;
 use syntax::ext::base::ExtCtxt;
+use syntax::parse::{self, token};
 use syntax::ptr::P;
 
 use aster;
 
-fn field_rename(field: &ast::StructField) -> Option<&ast::Lit> {
-    field.node.attrs.iter()
-        .find(|sa| {
-            if let ast::MetaList(ref n, _) = sa.node.value.node {
-                n == &"serde"
+/// Returns the items of the `#[serde(...)]` attributes, marking the attributes as used.
+fn serde_meta_items(attrs: &[ast::Attribute]) -> Vec<&ast::MetaItem> {
+    let mut items = vec![];
+
+    for sa in attrs.iter() {
+        if let ast::MetaList(ref n, ref vals) = sa.node.value.node {
+            if n == &"serde" {
+                attr::mark_used(&sa);
+                items.extend(vals.iter().map(|mi| &**mi));
+            }
+        }
+    }
+
+    items
+}
+
+/// Returns true if there is a `#[serde(word)]` attribute.
+fn has_meta_word(attrs: &[ast::Attribute], word: &str) -> bool {
+    serde_meta_items(attrs).iter()
+        .any(|mi| {
+            if let ast::MetaWord(ref n) = mi.node {
+                n == &word
             } else {
                 false
             }
         })
-        .and_then(|sa| {
-            if let ast::MetaList(_, ref vals) = sa.node.value.node {
-                attr::mark_used(&sa);
-                vals.iter().fold(None, |v, mi| {
-                    if let ast::MetaNameValue(ref n, ref lit) = mi.node {
-                        if n == &"rename" {
-                            Some(lit)
-                        } else {
-                            v
-                        }
-                    } else {
-                        v
-                    }
-                })
-            } else {
-                None
+}
+
+/// Returns the literals of all the `#[serde(name = "...")]` attributes, in order.
+fn meta_name_values<'a>(attrs: &'a [ast::Attribute], name: &str) -> Vec<&'a ast::Lit> {
+    serde_meta_items(attrs).into_iter()
+        .filter_map(|mi| {
+            match mi.node {
+                ast::MetaNameValue(ref n, ref lit) if n == &name => Some(lit),
+                _ => None,
             }
         })
+        .collect()
+}
+
+/// Returns the literal of the last `#[serde(name = "...")]` attribute.
+fn meta_name_value<'a>(attrs: &'a [ast::Attribute], name: &str) -> Option<&'a ast::Lit> {
+    meta_name_values(attrs, name).pop()
+}
+
+/// Which half of the derived code a name is wanted for.
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Serialize,
+    Deserialize,
+}
+
+/// Returns the literal of the last `#[serde(name = "...")]` attribute, or of the matching half
+/// of a `#[serde(name(serialize = "...", deserialize = "..."))]` attribute.
+fn directional_name_value<'a>(
+    attrs: &'a [ast::Attribute],
+    name: &str,
+    direction: Direction,
+) -> Option<&'a ast::Lit> {
+    let direction = match direction {
+        Direction::Serialize => "serialize",
+        Direction::Deserialize => "deserialize",
+    };
+
+    serde_meta_items(attrs).into_iter()
+        .filter_map(|mi| {
+            match mi.node {
+                ast::MetaNameValue(ref n, ref lit) if n == &name => Some(lit),
+                ast::MetaList(ref n, ref items) if n == &name => {
+                    items.iter()
+                        .filter_map(|mi| {
+                            match mi.node {
+                                ast::MetaNameValue(ref n, ref lit) if n == &direction => Some(lit),
+                                _ => None,
+                            }
+                        })
+                        .last()
+                }
+                _ => None,
+            }
+        })
+        .last()
+}
+
+/// Returns the literal of the last `#[serde(rename = "...")]` attribute, or of the matching
+/// half of a `#[serde(rename(serialize = "...", deserialize = "..."))]` attribute.
+fn rename<'a>(attrs: &'a [ast::Attribute], direction: Direction) -> Option<&'a ast::Lit> {
+    directional_name_value(attrs, "rename", direction)
 }
 
 pub fn struct_field_strs(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
     struct_def: &ast::StructDef,
+    rename_all: Option<RenameRule>,
+    direction: Direction,
 ) -> Vec<P<ast::Expr>> {
     struct_def.fields.iter()
         .map(|field| {
-            match field_rename(field) {
+            match rename(&field.node.attrs, direction) {
                 Some(rename) => builder.expr().build_lit(P(rename.clone())),
                 None => {
                     match field.node.kind {
                         ast::NamedField(name, _) => {
-                            builder.expr().str(name)
+                            match rename_all {
+                                Some(rule) => {
+                                    builder.expr().str(&*rule.apply_to_field(&name.to_string()))
+                                }
+                                None => builder.expr().str(name),
+                            }
                         }
                         ast::UnnamedField(_) => {
                             cx.bug("struct has named and unnamed fields")
@@ -58,26 +129,505 @@ pub fn struct_field_strs(
         .collect()
 }
 
-pub fn default_value(field: &ast::StructField) -> bool {
-    field.node.attrs.iter()
-        .any(|sa| {
-             if let ast::MetaItem_::MetaList(ref n, ref vals) = sa.node.value.node {
-                 if n == &"serde" {
-                     attr::mark_used(&sa);
-                     vals.iter()
-                         .map(|mi|
-                              if let ast::MetaItem_::MetaWord(ref n) = mi.node {
-                                  n == &"default"
-                              } else {
-                                  false
-                              })
-                         .any(|x| x)
-                 } else {
-                     false
-                 }
-             }
-             else {
-                 false
-             }
+/// Returns the other names each field is accepted under when deserializing, given by
+/// `#[serde(alias = "...")]` attributes.
+pub fn struct_field_aliases(
+    builder: &aster::AstBuilder,
+    struct_def: &ast::StructDef,
+) -> Vec<Vec<P<ast::Expr>>> {
+    struct_def.fields.iter()
+        .map(|field| {
+            meta_name_values(&field.node.attrs, "alias").into_iter()
+                .map(|alias| builder.expr().build_lit(P(alias.clone())))
+                .collect()
         })
+        .collect()
+}
+
+/// Returns the name of a struct or enum, given by `#[serde(rename = "...")]` or its identifier.
+pub fn container_name(cx: &ExtCtxt, item: &ast::Item, direction: Direction) -> String {
+    match rename(&item.attrs, direction) {
+        Some(lit) => lit_str(cx, lit).to_string(),
+        None => item.ident.to_string(),
+    }
+}
+
+/// Returns the name of an enum variant, given by `#[serde(rename = "...")]`, or else its
+/// identifier with the enum's `rename_all` rule applied.
+pub fn variant_name(
+    cx: &ExtCtxt,
+    variant: &ast::Variant,
+    rename_all: Option<RenameRule>,
+    direction: Direction,
+) -> String {
+    match rename(&variant.node.attrs, direction) {
+        Some(lit) => lit_str(cx, lit).to_string(),
+        None => {
+            let name = variant.node.name.to_string();
+
+            match rename_all {
+                Some(rule) => rule.apply_to_variant(&name),
+                None => name,
+            }
+        }
+    }
+}
+
+/// Returns the other names a variant is accepted under when deserializing, given by
+/// `#[serde(alias = "...")]` attributes.
+pub fn variant_aliases(cx: &ExtCtxt, variant: &ast::Variant) -> Vec<String> {
+    meta_name_values(&variant.node.attrs, "alias").into_iter()
+        .map(|lit| lit_str(cx, lit).to_string())
+        .collect()
+}
+
+/// Returns the case convention of a `#[serde(rename_all = "...")]` container attribute.
+pub fn rename_all(cx: &ExtCtxt, item: &ast::Item) -> Option<RenameRule> {
+    meta_name_value(&item.attrs, "rename_all").map(|lit| {
+        let s = lit_str(cx, lit);
+
+        match RenameRule::from_str(s) {
+            Some(rule) => rule,
+            None => cx.span_fatal(lit.span, &format!("unknown rename rule \"{}\"", s)),
+        }
+    })
+}
+
+/// How the variants of an enum are represented.
+pub enum EnumTag {
+    /// `{"Variant": ...}`, the default.
+    External,
+
+    /// `{"tag": "Variant", ...}`, with the variant's fields alongside the tag. Given by
+    /// `#[serde(tag = "tag")]`.
+    Internal(String),
+
+    /// `{"tag": "Variant", "content": ...}`. Given by
+    /// `#[serde(tag = "tag", content = "content")]`.
+    Adjacent(String, String),
+
+    /// Just the variant's data, given by `#[serde(untagged)]`.
+    Untagged,
+}
+
+/// Returns the representation of an enum's variants, given by its `tag`, `content` and
+/// `untagged` attributes.
+pub fn enum_tag(cx: &ExtCtxt, item: &ast::Item) -> EnumTag {
+    let tag = meta_name_value(&item.attrs, "tag");
+    let content = meta_name_value(&item.attrs, "content");
+
+    if has_meta_word(&item.attrs, "untagged") {
+        if let Some(lit) = tag {
+            cx.span_fatal(lit.span, "untagged enums cannot have a tag");
+        }
+
+        return EnumTag::Untagged;
+    }
+
+    match (tag, content) {
+        (Some(tag), Some(content)) => {
+            EnumTag::Adjacent(lit_str(cx, tag).to_string(), lit_str(cx, content).to_string())
+        }
+        (Some(tag), None) => EnumTag::Internal(lit_str(cx, tag).to_string()),
+        (None, Some(content)) => {
+            cx.span_fatal(content.span, "#[serde(content = \"...\")] requires a tag")
+        }
+        (None, None) => EnumTag::External,
+    }
+}
+
+/// How a fieldless enum is represented in place of `{"Variant": []}`.
+pub enum EnumRepr {
+    /// The variant's discriminant as the given integer type, given by `#[serde(repr = "u8")]`.
+    Int(String),
+
+    /// The variant's name, given by `#[serde(as_str)]`.
+    Str,
+}
+
+/// Returns the representation of a fieldless enum given by its `repr` or `as_str` attribute.
+/// Either form is accepted when deserializing.
+pub fn enum_repr(cx: &ExtCtxt, item: &ast::Item) -> Option<EnumRepr> {
+    let repr = meta_name_value(&item.attrs, "repr");
+    let as_str = has_meta_word(&item.attrs, "as_str");
+
+    let repr = match (repr, as_str) {
+        (None, false) => { return None; }
+        (Some(lit), true) => {
+            cx.span_fatal(lit.span, "#[serde(repr = \"...\")] and #[serde(as_str)] cannot be \
+                                     combined")
+        }
+        (Some(lit), false) => {
+            let s = lit_str(cx, lit);
+
+            match s {
+                "i8" | "i16" | "i32" | "i64" | "isize" |
+                "u8" | "u16" | "u32" | "u64" | "usize" => EnumRepr::Int(s.to_string()),
+                _ => cx.span_fatal(lit.span, &format!("unknown integer type \"{}\"", s)),
+            }
+        }
+        (None, true) => EnumRepr::Str,
+    };
+
+    let fieldless = match item.node {
+        ast::ItemEnum(ref enum_def, _) => {
+            enum_def.variants.iter().all(|variant| {
+                match variant.node.kind {
+                    ast::TupleVariantKind(ref args) => args.is_empty(),
+                    ast::StructVariantKind(_) => false,
+                }
+            })
+        }
+        _ => false,
+    };
+
+    if !fieldless {
+        cx.span_fatal(item.span, "#[serde(repr = \"...\")] and #[serde(as_str)] require an enum \
+                                  whose variants have no fields");
+    }
+
+    if has_meta_word(&item.attrs, "untagged") || meta_name_value(&item.attrs, "tag").is_some() {
+        cx.span_fatal(item.span, "#[serde(repr = \"...\")] and #[serde(as_str)] cannot be \
+                                  combined with a tag");
+    }
+
+    Some(repr)
+}
+
+/// A case convention that `rename_all` applies to the fields of a struct or the variants of an
+/// enum. Rust fields are assumed to be in snake_case and variants in PascalCase.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    LowerCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+}
+
+impl RenameRule {
+    fn from_str(s: &str) -> Option<RenameRule> {
+        match s {
+            "lowercase" => Some(RenameRule::LowerCase),
+            "PascalCase" => Some(RenameRule::PascalCase),
+            "camelCase" => Some(RenameRule::CamelCase),
+            "snake_case" => Some(RenameRule::SnakeCase),
+            "SCREAMING_SNAKE_CASE" => Some(RenameRule::ScreamingSnakeCase),
+            "kebab-case" => Some(RenameRule::KebabCase),
+            _ => None,
+        }
+    }
+
+    pub fn apply_to_field(&self, field: &str) -> String {
+        match *self {
+            RenameRule::LowerCase | RenameRule::SnakeCase => field.to_string(),
+            RenameRule::PascalCase => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+
+                pascal
+            }
+            RenameRule::CamelCase => {
+                let pascal = RenameRule::PascalCase.apply_to_field(field);
+                lowercase_first(&pascal)
+            }
+            RenameRule::ScreamingSnakeCase => field.to_ascii_uppercase(),
+            RenameRule::KebabCase => field.replace("_", "-"),
+        }
+    }
+
+    pub fn apply_to_variant(&self, variant: &str) -> String {
+        match *self {
+            RenameRule::PascalCase => variant.to_string(),
+            RenameRule::LowerCase => variant.to_ascii_lowercase(),
+            RenameRule::CamelCase => lowercase_first(variant),
+            RenameRule::SnakeCase => {
+                let mut snake = String::new();
+
+                for (i, ch) in variant.chars().enumerate() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+
+                snake
+            }
+            RenameRule::ScreamingSnakeCase => {
+                RenameRule::SnakeCase.apply_to_variant(variant).to_ascii_uppercase()
+            }
+            RenameRule::KebabCase => {
+                RenameRule::SnakeCase.apply_to_variant(variant).replace("_", "-")
+            }
+        }
+    }
+}
+
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(first) => {
+            let mut lower = first.to_ascii_lowercase().to_string();
+            lower.extend(chars);
+            lower
+        }
+        None => String::new(),
+    }
+}
+
+/// Returns the expression that fills in the field when it is missing from the input, given by
+/// `#[serde(default)]` or `#[serde(default = "path")]`.
+pub fn default_value(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    field: &ast::StructField,
+) -> Option<P<ast::Expr>> {
+    let path = if has_meta_word(&field.node.attrs, "default") {
+        builder.path().global().ids(&["std", "default", "Default", "default"]).build()
+    } else {
+        match meta_name_value(&field.node.attrs, "default") {
+            Some(lit) => lit_to_path(cx, builder, lit),
+            None => { return None; }
+        }
+    };
+
+    Some(builder.expr().call().build_path(path).build())
+}
+
+/// Returns true if the container has a `#[serde(default)]` attribute, which fills in missing
+/// fields from the container's own `Default` impl.
+pub fn container_default(item: &ast::Item) -> bool {
+    has_meta_word(&item.attrs, "default")
+}
+
+/// Returns true if the container has a `#[serde(transparent)]` attribute, which serializes and
+/// deserializes a struct with a single field as that field.
+pub fn transparent(cx: &ExtCtxt, item: &ast::Item) -> bool {
+    if !has_meta_word(&item.attrs, "transparent") {
+        return false;
+    }
+
+    for name in ["from", "try_from", "into"].iter() {
+        if let Some(lit) = meta_name_value(&item.attrs, name) {
+            cx.span_fatal(
+                lit.span,
+                &format!("#[serde(transparent)] cannot be combined with #[serde({} = \"...\")]",
+                         name));
+        }
+    }
+
+    match item.node {
+        ast::ItemStruct(ref struct_def, _) if struct_def.fields.len() == 1 => true,
+        _ => cx.span_fatal(item.span, "#[serde(transparent)] requires a struct with one field"),
+    }
+}
+
+/// Returns the type of the single field of a `#[serde(transparent)]` struct.
+pub fn transparent_type(cx: &ExtCtxt, item: &ast::Item) -> P<ast::Ty> {
+    match item.node {
+        ast::ItemStruct(ref struct_def, _) => struct_def.fields[0].node.ty.clone(),
+        _ => cx.bug("expected ItemStruct in #[serde(transparent)]"),
+    }
+}
+
+/// Returns the type given by `#[serde(from = "T")]`, which the container is deserialized as and
+/// then converted from with `From`.
+pub fn from_type(cx: &ExtCtxt, item: &ast::Item) -> Option<P<ast::Ty>> {
+    let from = meta_name_value(&item.attrs, "from");
+
+    if let (Some(_), Some(lit)) = (from, meta_name_value(&item.attrs, "try_from")) {
+        cx.span_fatal(lit.span, "#[serde(from = \"...\")] and #[serde(try_from = \"...\")] \
+                                 cannot be combined");
+    }
+
+    from.map(|lit| lit_to_ty(cx, lit))
+}
+
+/// Returns the type given by `#[serde(try_from = "T")]`, which the container is deserialized as
+/// and then converted from with `serde::de::TryFrom`.
+pub fn try_from_type(cx: &ExtCtxt, item: &ast::Item) -> Option<P<ast::Ty>> {
+    meta_name_value(&item.attrs, "try_from").map(|lit| lit_to_ty(cx, lit))
+}
+
+/// Returns the type given by `#[serde(into = "T")]`, which the container is converted into with
+/// `Into` and then serialized as. Serializing only borrows the container, so it must also
+/// implement `Clone`.
+pub fn into_type(cx: &ExtCtxt, item: &ast::Item) -> Option<P<ast::Ty>> {
+    meta_name_value(&item.attrs, "into").map(|lit| lit_to_ty(cx, lit))
+}
+
+/// Returns the where-predicates given by a container's `#[serde(bound = "...")]` or
+/// `#[serde(bound(serialize = "...", deserialize = "..."))]` attribute, which replace the bounds
+/// that would otherwise be inferred from its fields.
+pub fn container_bound(
+    cx: &ExtCtxt,
+    item: &ast::Item,
+    direction: Direction,
+) -> Option<Vec<ast::WherePredicate>> {
+    directional_name_value(&item.attrs, "bound", direction)
+        .map(|lit| lit_to_where_predicates(cx, lit))
+}
+
+/// Returns the where-predicates given by a field's `bound` attribute, which replace the bounds
+/// that would otherwise be inferred from the field's type.
+pub fn field_bound(
+    cx: &ExtCtxt,
+    field: &ast::StructField,
+    direction: Direction,
+) -> Option<Vec<ast::WherePredicate>> {
+    directional_name_value(&field.node.attrs, "bound", direction)
+        .map(|lit| lit_to_where_predicates(cx, lit))
+}
+
+/// Returns true if the container has a `#[serde(deny_unknown_fields)]` attribute, which makes
+/// deserialization fail on keys that don't name a field.
+pub fn deny_unknown_fields(item: &ast::Item) -> bool {
+    has_meta_word(&item.attrs, "deny_unknown_fields")
+}
+
+/// Returns true if the field has a `#[serde(skip_serializing)]` attribute, which leaves it out
+/// of the serialized output.
+pub fn skip_serializing(field: &ast::StructField) -> bool {
+    has_meta_word(&field.node.attrs, "skip_serializing")
+}
+
+/// Returns true if the field has a `#[serde(skip_deserializing)]` attribute, which never reads
+/// it from the input and fills it in with its default value instead.
+pub fn skip_deserializing(field: &ast::StructField) -> bool {
+    has_meta_word(&field.node.attrs, "skip_deserializing")
+}
+
+/// Returns true if the field has a `#[serde(flatten)]` attribute, which serializes and
+/// deserializes the field's own entries as entries of the containing struct.
+pub fn flatten(field: &ast::StructField) -> bool {
+    has_meta_word(&field.node.attrs, "flatten")
+}
+
+/// Returns true if the field has a `#[serde(borrow)]` attribute, which makes the field borrow from
+/// the input for the lifetimes in its type. A `Cow` field borrows when it can.
+pub fn borrow(field: &ast::StructField) -> bool {
+    has_meta_word(&field.node.attrs, "borrow")
+}
+
+/// Returns the path of the function named by `#[serde(skip_serializing_if = "path")]`, which
+/// decides whether the field is left out when serializing.
+pub fn skip_serializing_if(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    field: &ast::StructField,
+) -> Option<ast::Path> {
+    meta_name_value(&field.node.attrs, "skip_serializing_if")
+        .map(|lit| lit_to_path(cx, builder, lit))
+}
+
+/// Returns the path of the function named by `#[serde(serialize_with = "path")]`, which
+/// serializes the field in place of its `Serialize` impl.
+pub fn serialize_with(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    field: &ast::StructField,
+) -> Option<ast::Path> {
+    meta_name_value(&field.node.attrs, "serialize_with")
+        .map(|lit| lit_to_path(cx, builder, lit))
+}
+
+/// Returns the path of the function named by `#[serde(deserialize_with = "path")]`, which
+/// deserializes the field in place of its `Deserialize` impl.
+pub fn deserialize_with(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    field: &ast::StructField,
+) -> Option<ast::Path> {
+    meta_name_value(&field.node.attrs, "deserialize_with")
+        .map(|lit| lit_to_path(cx, builder, lit))
+}
+
+/// Parses a string literal such as `"Option::is_none"` or `"::std::vec::Vec::is_empty"` into a
+/// path.
+fn lit_to_path(cx: &ExtCtxt, builder: &aster::AstBuilder, lit: &ast::Lit) -> ast::Path {
+    let s = lit_str(cx, lit);
+
+    let segments: Vec<&str> = s.trim_left_matches("::").split("::").collect();
+
+    if segments.iter().any(|segment| segment.is_empty()) {
+        cx.span_fatal(lit.span, &format!("invalid path \"{}\"", s));
+    }
+
+    let path = if s.starts_with("::") {
+        builder.path().global()
+    } else {
+        builder.path()
+    };
+
+    path.ids(segments).build()
+}
+
+/// Parses a string literal such as `"String"` or `"Vec<u8>"` into a type.
+fn lit_to_ty(cx: &ExtCtxt, lit: &ast::Lit) -> P<ast::Ty> {
+    let s = lit_str(cx, lit);
+
+    let mut parser = parse::new_parser_from_source_str(
+        cx.parse_sess(),
+        cx.cfg(),
+        "<serde attribute>".to_string(),
+        s.to_string(),
+    );
+
+    let ty = parser.parse_ty();
+
+    if parser.token != token::Eof {
+        cx.span_fatal(lit.span, &format!("invalid type \"{}\"", s));
+    }
+
+    ty
+}
+
+/// Parses a string literal such as `"T: Serialize, T::Item: Clone"` into where-predicates. An
+/// empty string gives no predicates.
+fn lit_to_where_predicates(cx: &ExtCtxt, lit: &ast::Lit) -> Vec<ast::WherePredicate> {
+    let s = lit_str(cx, lit);
+
+    match parse_where_predicates(cx, s) {
+        Some(predicates) => predicates,
+        None => cx.span_fatal(lit.span, &format!("invalid bound \"{}\"", s)),
+    }
+}
+
+/// Parses comma-separated where-predicates, returning `None` if `s` is not entirely made of
+/// them.
+pub fn parse_where_predicates(cx: &ExtCtxt, s: &str) -> Option<Vec<ast::WherePredicate>> {
+    let mut parser = parse::new_parser_from_source_str(
+        cx.parse_sess(),
+        cx.cfg(),
+        "<serde bound>".to_string(),
+        format!("where {}", s),
+    );
+
+    let where_clause = parser.parse_where_clause();
+
+    if parser.token == token::Eof {
+        Some(where_clause.predicates)
+    } else {
+        None
+    }
+}
+
+fn lit_str<'a>(cx: &ExtCtxt, lit: &'a ast::Lit) -> &'a str {
+    match lit.node {
+        ast::LitStr(ref s, _) => &s[..],
+        _ => cx.span_fatal(lit.span, "expected a string literal"),
+    }
 }
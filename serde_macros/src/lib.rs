@@ -10,6 +10,7 @@ use syntax::ext::base::Decorator;
 use syntax::parse::token;
 use rustc::plugin::Registry;
 
+mod bound;
 mod ser;
 mod de;
 mod field;
@@ -9,11 +9,13 @@ use syntax::ast;
 use syntax::codemap::Span;
 use syntax::ext::base::ExtCtxt;
 use syntax::ext::build::AstBuilder;
+use syntax::print::pprust;
 use syntax::ptr::P;
 
 use aster;
 
-use field::struct_field_strs;
+use bound;
+use field::{self, Direction, EnumRepr, EnumTag, struct_field_strs};
 
 pub fn expand_derive_serialize(
     cx: &mut ExtCtxt,
@@ -30,11 +32,47 @@ pub fn expand_derive_serialize(
         _ => cx.bug("expected ItemStruct or ItemEnum in #[derive(Serialize)]")
     };
 
-    let impl_generics = builder.from_generics(generics.clone())
-        .add_ty_param_bound(
-            builder.path().global().ids(&["serde", "ser", "Serialize"]).build()
+    let impl_generics = if let Some(into_ty) = field::into_type(cx, item) {
+        // Only the converted value is serialized, so the fields need nothing. The conversion
+        // consumes a clone of `self`.
+        let self_ty = builder.ty().path()
+            .segment(item.ident).with_generics(generics.clone()).build()
+            .build();
+        let self_ty = pprust::ty_to_string(&self_ty);
+        let into_ty = pprust::ty_to_string(&into_ty);
+        bound::with_predicates(
+            cx,
+            item,
+            generics,
+            Direction::Serialize,
+            &format!("{}: ::std::clone::Clone + ::std::convert::Into<{}>, \
+                      {}: ::serde::ser::Serialize",
+                     self_ty, into_ty, into_ty),
         )
-        .build();
+    } else if field::transparent(cx, item) {
+        // The single field is serialized as is, so bound its type rather than its parameters.
+        bound::with_predicates(
+            cx,
+            item,
+            generics,
+            Direction::Serialize,
+            &format!("{}: ::serde::ser::Serialize",
+                     pprust::ty_to_string(&field::transparent_type(cx, item))),
+        )
+    } else {
+        // Fields that are skipped or serialized with a custom function don't need `Serialize`.
+        bound::with_bound(
+            cx,
+            item,
+            generics,
+            Direction::Serialize,
+            &|field: &ast::StructField| {
+                !field::skip_serializing(field) &&
+                    field::serialize_with(cx, &builder, field).is_none()
+            },
+            "::serde::ser::Serialize",
+        )
+    };
 
     let ty = builder.ty().path()
         .segment(item.ident).with_generics(impl_generics.clone()).build()
@@ -71,6 +109,17 @@ fn serialize_body(
     impl_generics: &ast::Generics,
     ty: P<ast::Ty>,
 ) -> P<ast::Expr> {
+    if field::transparent(cx, item) {
+        return serialize_transparent(cx, item);
+    }
+
+    if let Some(into_ty) = field::into_type(cx, item) {
+        return quote_expr!(cx, {
+            let value: $into_ty = ::std::convert::Into::into(::std::clone::Clone::clone(self));
+            ::serde::ser::Serialize::serialize(&value, serializer)
+        });
+    }
+
     match item.node {
         ast::ItemStruct(ref struct_def, _) => {
             serialize_item_struct(
@@ -86,7 +135,7 @@ fn serialize_body(
             serialize_item_enum(
                 cx,
                 builder,
-                item.ident,
+                item,
                 impl_generics,
                 enum_def,
             )
@@ -95,6 +144,23 @@ fn serialize_body(
     }
 }
 
+/// Serializes a `#[serde(transparent)]` struct as its only field.
+fn serialize_transparent(cx: &ExtCtxt, item: &Item) -> P<ast::Expr> {
+    let struct_def = match item.node {
+        ast::ItemStruct(ref struct_def, _) => struct_def,
+        _ => cx.bug("expected ItemStruct in #[serde(transparent)]"),
+    };
+
+    match struct_def.fields[0].node.kind {
+        ast::NamedField(name, _) => {
+            quote_expr!(cx, ::serde::ser::Serialize::serialize(&self.$name, serializer))
+        }
+        ast::UnnamedField(_) => {
+            quote_expr!(cx, ::serde::ser::Serialize::serialize(&self.0, serializer))
+        }
+    }
+}
+
 fn serialize_item_struct(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
@@ -113,19 +179,27 @@ fn serialize_item_struct(
         }
     }
 
+    let type_name = field::container_name(cx, item, Direction::Serialize);
+    let type_name = builder.expr().str(&*type_name);
+
     match (named_fields.is_empty(), unnamed_fields == 0) {
         (true, true) => {
             serialize_unit_struct(
                 cx,
-                &builder,
-                item.ident,
+                type_name,
+            )
+        }
+        (true, false) if unnamed_fields == 1 => {
+            serialize_newtype_struct(
+                cx,
+                type_name,
             )
         }
         (true, false) => {
             serialize_tuple_struct(
                 cx,
                 &builder,
-                item.ident,
+                type_name,
                 impl_generics,
                 ty,
                 unnamed_fields,
@@ -135,11 +209,12 @@ fn serialize_item_struct(
             serialize_struct(
                 cx,
                 &builder,
-                item.ident,
+                type_name,
                 impl_generics,
                 ty,
                 struct_def,
                 named_fields,
+                field::rename_all(cx, item),
             )
         }
         (false, false) => {
@@ -150,18 +225,22 @@ fn serialize_item_struct(
 
 fn serialize_unit_struct(
     cx: &ExtCtxt,
-    builder: &aster::AstBuilder,
-    type_ident: Ident
+    type_name: P<ast::Expr>,
 ) -> P<ast::Expr> {
-    let type_name = builder.expr().str(type_ident);
-
     quote_expr!(cx, serializer.visit_named_unit($type_name))
 }
 
+fn serialize_newtype_struct(
+    cx: &ExtCtxt,
+    type_name: P<ast::Expr>,
+) -> P<ast::Expr> {
+    quote_expr!(cx, serializer.visit_newtype_struct($type_name, &self.0))
+}
+
 fn serialize_tuple_struct(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
-    type_ident: Ident,
+    type_name: P<ast::Expr>,
     impl_generics: &ast::Generics,
     ty: P<ast::Ty>,
     fields: usize,
@@ -179,8 +258,6 @@ fn serialize_tuple_struct(
         impl_generics,
     );
 
-    let type_name = builder.expr().str(type_ident);
-
     quote_expr!(cx, {
         $visitor_struct
         $visitor_impl
@@ -194,11 +271,12 @@ fn serialize_tuple_struct(
 fn serialize_struct(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
-    type_ident: Ident,
+    type_name: P<ast::Expr>,
     impl_generics: &ast::Generics,
     ty: P<ast::Ty>,
     struct_def: &StructDef,
     fields: Vec<Ident>,
+    rename_all: Option<field::RenameRule>,
 ) -> P<ast::Expr> {
     let value_ty = builder.ty()
         .ref_()
@@ -212,10 +290,10 @@ fn serialize_struct(
         struct_def,
         impl_generics,
         fields.iter().map(|field| quote_expr!(cx, &self.value.$field)),
+        rename_all,
+        None,
     );
 
-    let type_name = builder.expr().str(type_ident);
-
     quote_expr!(cx, {
         $visitor_struct
         $visitor_impl
@@ -229,18 +307,32 @@ fn serialize_struct(
 fn serialize_item_enum(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
-    type_ident: Ident,
+    item: &Item,
     impl_generics: &ast::Generics,
     enum_def: &ast::EnumDef,
 ) -> P<ast::Expr> {
+    let type_name = field::container_name(cx, item, Direction::Serialize);
+    let rename_all = field::rename_all(cx, item);
+
+    if let Some(repr) = field::enum_repr(cx, item) {
+        return serialize_c_like_enum(cx, builder, item.ident, enum_def, rename_all, repr);
+    }
+
+    let tag = field::enum_tag(cx, item);
+
     let arms: Vec<ast::Arm> = enum_def.variants.iter()
         .map(|variant| {
+            let variant_name = field::variant_name(cx, variant, rename_all, Direction::Serialize);
+
             serialize_variant(
                 cx,
                 builder,
-                type_ident,
+                item.ident,
+                builder.expr().str(&*type_name),
                 impl_generics,
                 variant,
+                builder.expr().str(&*variant_name),
+                &tag,
             )
         })
         .collect();
@@ -252,16 +344,70 @@ fn serialize_item_enum(
     )
 }
 
+/// Serializes a fieldless enum as just its discriminant or its variant name.
+fn serialize_c_like_enum(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    type_ident: Ident,
+    enum_def: &ast::EnumDef,
+    rename_all: Option<field::RenameRule>,
+    repr: EnumRepr,
+) -> P<ast::Expr> {
+    let arms: Vec<ast::Arm> = enum_def.variants.iter()
+        .map(|variant| {
+            let variant_ident = variant.node.name;
+
+            match repr {
+                EnumRepr::Int(ref int_ty) => {
+                    let method = builder.id(format!("visit_{}", int_ty));
+                    let int_ty = builder.ty().id(int_ty);
+
+                    quote_arm!(cx,
+                        $type_ident::$variant_ident => {
+                            ::serde::ser::Serializer::$method(
+                                serializer,
+                                $type_ident::$variant_ident as $int_ty,
+                            )
+                        },
+                    )
+                }
+                EnumRepr::Str => {
+                    let variant_name = field::variant_name(
+                        cx,
+                        variant,
+                        rename_all,
+                        Direction::Serialize,
+                    );
+                    let variant_name = builder.expr().str(&*variant_name);
+
+                    quote_arm!(cx,
+                        $type_ident::$variant_ident => {
+                            ::serde::ser::Serializer::visit_str(serializer, $variant_name)
+                        },
+                    )
+                }
+            }
+        })
+        .collect();
+
+    quote_expr!(cx,
+        match *self {
+            $arms
+        }
+    )
+}
+
 fn serialize_variant(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
     type_ident: Ident,
+    type_name: P<ast::Expr>,
     generics: &ast::Generics,
     variant: &ast::Variant,
+    variant_name: P<ast::Expr>,
+    tag: &EnumTag,
 ) -> ast::Arm {
-    let type_name = builder.expr().str(type_ident);
     let variant_ident = variant.node.name;
-    let variant_name = builder.expr().str(variant_ident);
 
     match variant.node.kind {
         ast::TupleVariantKind(ref args) if args.is_empty() => {
@@ -269,17 +415,51 @@ fn serialize_variant(
                 .id(type_ident).id(variant_ident).build()
                 .build();
 
-            quote_arm!(cx,
-                $pat => {
-                    ::serde::ser::Serializer::visit_enum_unit(
-                        serializer,
-                        $type_name,
-                        $variant_name,
+            match *tag {
+                EnumTag::External => {
+                    quote_arm!(cx,
+                        $pat => {
+                            ::serde::ser::Serializer::visit_enum_unit(
+                                serializer,
+                                $type_name,
+                                $variant_name,
+                            )
+                        },
                     )
-                },
-            )
+                }
+                EnumTag::Untagged => {
+                    quote_arm!(cx,
+                        $pat => {
+                            ::serde::ser::Serializer::visit_unit(serializer)
+                        },
+                    )
+                }
+                EnumTag::Internal(ref tag) | EnumTag::Adjacent(ref tag, _) => {
+                    let tag = builder.expr().str(&**tag);
+
+                    quote_arm!(cx,
+                        $pat => {
+                            ::serde::ser::Serializer::visit_named_map(
+                                serializer,
+                                $type_name,
+                                ::serde::ser::impls::MapIteratorVisitor::new(
+                                    Some(($tag, $variant_name)).into_iter(),
+                                    Some(1),
+                                ),
+                            )
+                        },
+                    )
+                }
+            }
         }
         ast::TupleVariantKind(ref args) => {
+            if let EnumTag::Internal(_) = *tag {
+                cx.span_fatal(
+                    variant.span,
+                    "internally tagged enums cannot contain tuple variants",
+                );
+            }
+
             let fields: Vec<ast::Ident> = (0 .. args.len())
                 .map(|i| builder.id(format!("__field{}", i)))
                 .collect();
@@ -297,6 +477,7 @@ fn serialize_variant(
                 generics,
                 args,
                 fields,
+                tag,
             );
 
             quote_arm!(cx, $pat => { $expr })
@@ -332,6 +513,7 @@ fn serialize_variant(
                 generics,
                 struct_def,
                 fields,
+                tag,
             );
 
             quote_arm!(cx, $pat => { $expr })
@@ -339,6 +521,48 @@ fn serialize_variant(
     }
 }
 
+fn serialize_newtype_variant(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    type_name: P<ast::Expr>,
+    variant_name: P<ast::Expr>,
+    generics: &ast::Generics,
+    ty: P<ast::Ty>,
+    field: Ident,
+    tag: &EnumTag,
+) -> P<ast::Expr> {
+    match *tag {
+        EnumTag::External => {
+            quote_expr!(cx, serializer.visit_newtype_variant($type_name, $variant_name, $field))
+        }
+        EnumTag::Adjacent(ref tag, ref content) => {
+            let value_ty = builder.ty().ref_().lifetime("'__a").build_ty(ty);
+
+            serialize_adjacently_tagged(
+                cx,
+                builder,
+                generics,
+                type_name,
+                variant_name,
+                tag,
+                content,
+                value_ty,
+                builder.expr().id(field),
+                vec![],
+                quote_expr!(cx, ::serde::ser::Serialize::serialize(self.value, serializer)),
+            )
+        }
+        // An untagged newtype variant is just its field, so that an untagged enum can hold values
+        // of different types.
+        EnumTag::Untagged => {
+            quote_expr!(cx, ::serde::ser::Serialize::serialize($field, serializer))
+        }
+        EnumTag::Internal(_) => {
+            cx.bug("internally tagged tuple variants are rejected by serialize_variant")
+        }
+    }
+}
+
 fn serialize_tuple_variant(
     cx: &ExtCtxt,
     builder: &aster::AstBuilder,
@@ -347,7 +571,21 @@ fn serialize_tuple_variant(
     generics: &ast::Generics,
     args: &[ast::VariantArg],
     fields: Vec<Ident>,
+    tag: &EnumTag,
 ) -> P<ast::Expr> {
+    if fields.len() == 1 {
+        return serialize_newtype_variant(
+            cx,
+            builder,
+            type_name,
+            variant_name,
+            generics,
+            args[0].ty.clone(),
+            fields[0],
+            tag,
+        );
+    }
+
     let value_ty = builder.ty().tuple()
         .with_tys(
             args.iter().map(|arg| {
@@ -372,19 +610,51 @@ fn serialize_tuple_variant(
     let (visitor_struct, visitor_impl) = serialize_tuple_struct_visitor(
         cx,
         builder,
-        value_ty,
+        value_ty.clone(),
         args.len(),
         generics,
     );
 
-    quote_expr!(cx, {
-        $visitor_struct
-        $visitor_impl
-        serializer.visit_enum_seq($type_name, $variant_name, Visitor {
-            value: $value_expr,
-            state: 0,
-        })
-    })
+    match *tag {
+        EnumTag::External => {
+            quote_expr!(cx, {
+                $visitor_struct
+                $visitor_impl
+                serializer.visit_enum_seq($type_name, $variant_name, Visitor {
+                    value: $value_expr,
+                    state: 0,
+                })
+            })
+        }
+        EnumTag::Adjacent(ref tag, ref content) => {
+            serialize_adjacently_tagged(
+                cx,
+                builder,
+                generics,
+                type_name,
+                variant_name,
+                tag,
+                content,
+                value_ty,
+                value_expr,
+                vec![visitor_struct, visitor_impl],
+                quote_expr!(cx, serializer.visit_seq(Visitor { value: self.value, state: 0 })),
+            )
+        }
+        EnumTag::Untagged => {
+            quote_expr!(cx, {
+                $visitor_struct
+                $visitor_impl
+                serializer.visit_seq(Visitor {
+                    value: $value_expr,
+                    state: 0,
+                })
+            })
+        }
+        EnumTag::Internal(_) => {
+            cx.bug("internally tagged tuple variants are rejected by serialize_variant")
+        }
+    }
 }
 
 fn serialize_struct_variant(
@@ -395,6 +665,7 @@ fn serialize_struct_variant(
     generics: &ast::Generics,
     struct_def: &ast::StructDef,
     fields: Vec<Ident>,
+    tag: &EnumTag,
 ) -> P<ast::Expr> {
     let value_ty = builder.ty().tuple()
         .with_tys(
@@ -420,22 +691,146 @@ fn serialize_struct_variant(
     let (visitor_struct, visitor_impl) = serialize_struct_visitor(
         cx,
         builder,
-        value_ty,
+        value_ty.clone(),
         struct_def,
         generics,
         (0 .. fields.len()).map(|i| {
             builder.expr()
                 .tup_field(i)
                 .field("value").self_()
-        })
+        }),
+        None,
+        match *tag {
+            EnumTag::External | EnumTag::Adjacent(..) | EnumTag::Untagged => None,
+            EnumTag::Internal(ref tag) => Some((builder.expr().str(&**tag), variant_name.clone())),
+        },
     );
 
+    match *tag {
+        EnumTag::External => {
+            quote_expr!(cx, {
+                $visitor_struct
+                $visitor_impl
+                serializer.visit_enum_map($type_name, $variant_name, Visitor {
+                    value: $value_expr,
+                    state: 0,
+                })
+            })
+        }
+        EnumTag::Internal(_) | EnumTag::Untagged => {
+            quote_expr!(cx, {
+                $visitor_struct
+                $visitor_impl
+                serializer.visit_named_map($type_name, Visitor {
+                    value: $value_expr,
+                    state: 0,
+                })
+            })
+        }
+        EnumTag::Adjacent(ref tag, ref content) => {
+            serialize_adjacently_tagged(
+                cx,
+                builder,
+                generics,
+                type_name,
+                variant_name,
+                tag,
+                content,
+                value_ty,
+                value_expr,
+                vec![visitor_struct, visitor_impl],
+                quote_expr!(cx, serializer.visit_map(Visitor { value: self.value, state: 0 })),
+            )
+        }
+    }
+}
+
+/// Serializes a variant as a map from `tag` to the variant name and from `content` to the
+/// variant's data, which `visit_content_expr` serializes from `self.value` with the visitor
+/// `visitor_items`.
+fn serialize_adjacently_tagged(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    generics: &ast::Generics,
+    type_name: P<ast::Expr>,
+    variant_name: P<ast::Expr>,
+    tag: &str,
+    content: &str,
+    value_ty: P<ast::Ty>,
+    value_expr: P<ast::Expr>,
+    visitor_items: Vec<P<ast::Item>>,
+    visit_content_expr: P<ast::Expr>,
+) -> P<ast::Expr> {
+    let visitor_impl_generics = builder.from_generics(generics.clone())
+        .add_lifetime_bound("'__a")
+        .lifetime_name("'__a")
+        .build();
+
+    let where_clause = &visitor_impl_generics.where_clause;
+
+    let visitor_generics = builder.from_generics(visitor_impl_generics.clone())
+        .strip_bounds()
+        .build();
+
+    let content_ty = builder.ty().path()
+        .segment("__AdjacentContent").with_generics(visitor_generics.clone()).build()
+        .build();
+
+    let tagged_ty = builder.ty().path()
+        .segment("__AdjacentlyTagged").with_generics(visitor_generics.clone()).build()
+        .build();
+
+    let tag = builder.expr().str(tag);
+    let content = builder.expr().str(content);
+
     quote_expr!(cx, {
-        $visitor_struct
-        $visitor_impl
-        serializer.visit_enum_map($type_name, $variant_name, Visitor {
-            value: $value_expr,
+        $visitor_items
+
+        struct __AdjacentContent $visitor_impl_generics $where_clause {
+            value: $value_ty,
+        }
+
+        impl $visitor_impl_generics ::serde::ser::Serialize for $content_ty $where_clause {
+            fn serialize<__S>(&self, serializer: &mut __S) -> ::std::result::Result<(), __S::Error>
+                where __S: ::serde::ser::Serializer,
+            {
+                $visit_content_expr
+            }
+        }
+
+        struct __AdjacentlyTagged $visitor_impl_generics $where_clause {
+            state: usize,
+            value: $value_ty,
+        }
+
+        impl $visitor_impl_generics ::serde::ser::MapVisitor for $tagged_ty $where_clause {
+            #[inline]
+            fn visit<S>(&mut self, serializer: &mut S) -> ::std::result::Result<Option<()>, S::Error>
+                where S: ::serde::ser::Serializer,
+            {
+                match self.state {
+                    0 => {
+                        self.state += 1;
+                        Ok(Some(try!(serializer.visit_map_elt($tag, $variant_name))))
+                    }
+                    1 => {
+                        self.state += 1;
+                        let content = __AdjacentContent { value: self.value };
+                        Ok(Some(try!(serializer.visit_map_elt($content, content))))
+                    }
+                    _ => Ok(None),
+                }
+            }
+
+            #[inline]
+            fn len(&self) -> Option<usize> {
+                Some(2)
+            }
+        }
+
+        serializer.visit_named_map($type_name, __AdjacentlyTagged {
             state: 0,
+            value: $value_expr,
         })
     })
 }
@@ -512,21 +907,79 @@ fn serialize_struct_visitor<I>(
     struct_def: &StructDef,
     generics: &ast::Generics,
     value_exprs: I,
+    rename_all: Option<field::RenameRule>,
+    tag: Option<(P<ast::Expr>, P<ast::Expr>)>,
 ) -> (P<ast::Item>, P<ast::Item>)
     where I: Iterator<Item=P<ast::Expr>>,
 {
-    let len = struct_def.fields.len();
+    let visitor_impl_generics = builder.from_generics(generics.clone())
+        .add_lifetime_bound("'__a")
+        .lifetime_name("'__a")
+        .build();
 
-    let key_exprs = struct_field_strs(cx, builder, struct_def);
+    let where_clause = &visitor_impl_generics.where_clause;
+
+    let visitor_generics = builder.from_generics(visitor_impl_generics.clone())
+        .strip_bounds()
+        .build();
 
-    let arms: Vec<ast::Arm> = key_exprs.iter()
+    let key_exprs = struct_field_strs(cx, builder, struct_def, rename_all, Direction::Serialize);
+
+    // Fields marked `skip_serializing` are left out entirely, while those with a
+    // `skip_serializing_if` predicate are checked every time they would be visited.
+    let mut fields: Vec<_> = struct_def.fields.iter()
+        .zip(key_exprs.into_iter())
         .zip(value_exprs)
+        .filter(|&((field, _), _)| !field::skip_serializing(field))
+        .map(|((field, key_expr), value_expr)| {
+            let skip_expr = field::skip_serializing_if(cx, builder, field).map(|path| {
+                builder.expr().call()
+                    .build_path(path)
+                    .with_args(Some(value_expr.clone()))
+                    .build()
+            });
+
+            let value_expr = match field::serialize_with(cx, builder, field) {
+                Some(path) => {
+                    serialize_with(
+                        cx,
+                        builder,
+                        &visitor_impl_generics,
+                        &visitor_generics,
+                        value_ty.clone(),
+                        field.node.ty.clone(),
+                        path,
+                        value_expr,
+                    )
+                }
+                None => value_expr,
+            };
+
+            (skip_expr, key_expr, value_expr, field::flatten(field))
+        })
+        .collect();
+
+    // An internally tagged variant writes its tag before its fields.
+    if let Some((tag_expr, variant_name)) = tag {
+        fields.insert(0, (None, tag_expr, variant_name, false));
+    }
+
+    let arms: Vec<ast::Arm> = fields.iter()
         .enumerate()
-        .map(|(i, (key_expr, value_expr))| {
-            quote_arm!(cx,
-                $i => {
-                    self.state += 1;
-                    Ok(
+        .map(|(i, &(ref skip_expr, ref key_expr, ref value_expr, flatten))| {
+            // A flattened field writes all of its own entries, then moves on to the next field.
+            let visit_expr = if flatten {
+                quote_expr!(cx,
+                    try!(
+                        ::serde::ser::Serialize::serialize(
+                            &$value_expr,
+                            &mut ::serde::ser::impls::FlatMapSerializer::new(serializer),
+                        )
+                    )
+                )
+            } else {
+                quote_expr!(cx,
+                    return Ok(
                         Some(
                             try!(
                                 serializer.visit_map_elt(
@@ -536,21 +989,48 @@ fn serialize_struct_visitor<I>(
                             )
                         )
                     )
+                )
+            };
+
+            match *skip_expr {
+                Some(ref skip_expr) => {
+                    quote_arm!(cx,
+                        $i => {
+                            self.state += 1;
+                            if !$skip_expr {
+                                $visit_expr;
+                            }
+                        }
+                    )
                 }
-            )
+                None => {
+                    quote_arm!(cx,
+                        $i => {
+                            self.state += 1;
+                            $visit_expr;
+                        }
+                    )
+                }
+            }
         })
         .collect();
 
-    let visitor_impl_generics = builder.from_generics(generics.clone())
-        .add_lifetime_bound("'__a")
-        .lifetime_name("'__a")
-        .build();
-
-    let where_clause = &visitor_impl_generics.where_clause;
+    // The number of entries a flattened field writes isn't known up front.
+    let len = if fields.iter().any(|&(_, _, _, flatten)| flatten) {
+        quote_expr!(cx, None)
+    } else {
+        let len = fields.iter()
+            .fold(quote_expr!(cx, 0), |len, &(ref skip_expr, _, _, _)| {
+                match *skip_expr {
+                    Some(ref skip_expr) => {
+                        quote_expr!(cx, $len + if $skip_expr { 0 } else { 1 })
+                    }
+                    None => quote_expr!(cx, $len + 1),
+                }
+            });
 
-    let visitor_generics = builder.from_generics(visitor_impl_generics.clone())
-        .strip_bounds()
-        .build();
+        quote_expr!(cx, Some($len))
+    };
 
     (
         quote_item!(cx,
@@ -569,17 +1049,66 @@ fn serialize_struct_visitor<I>(
                 fn visit<S>(&mut self, serializer: &mut S) -> ::std::result::Result<Option<()>, S::Error>
                     where S: ::serde::ser::Serializer,
                 {
-                    match self.state {
-                        $arms
-                        _ => Ok(None),
+                    loop {
+                        match self.state {
+                            $arms
+                            _ => { return Ok(None); }
+                        }
                     }
                 }
 
                 #[inline]
                 fn len(&self) -> Option<usize> {
-                    Some($len)
+                    $len
                 }
             }
         ).unwrap(),
     )
 }
+
+/// Wraps a field's value in a type whose `Serialize` impl calls the field's `serialize_with`
+/// function.
+fn serialize_with(
+    cx: &ExtCtxt,
+    builder: &aster::AstBuilder,
+    visitor_impl_generics: &ast::Generics,
+    visitor_generics: &ast::Generics,
+    value_ty: P<ast::Ty>,
+    field_ty: P<ast::Ty>,
+    path: ast::Path,
+    value_expr: P<ast::Expr>,
+) -> P<ast::Expr> {
+    let where_clause = &visitor_impl_generics.where_clause;
+
+    let serialize_expr = builder.expr().call()
+        .build_path(path)
+        .with_args(vec![
+            quote_expr!(cx, self.value),
+            quote_expr!(cx, serializer),
+        ])
+        .build();
+
+    let wrapper_ty = builder.ty().path()
+        .segment("__SerializeWith").with_generics(visitor_generics.clone()).build()
+        .build();
+
+    quote_expr!(cx, {
+        struct __SerializeWith $visitor_impl_generics $where_clause {
+            value: &'__a $field_ty,
+            phantom: ::std::marker::PhantomData<$value_ty>,
+        }
+
+        impl $visitor_impl_generics ::serde::ser::Serialize for $wrapper_ty $where_clause {
+            fn serialize<__S>(&self, serializer: &mut __S) -> ::std::result::Result<(), __S::Error>
+                where __S: ::serde::ser::Serializer,
+            {
+                $serialize_expr
+            }
+        }
+
+        __SerializeWith {
+            value: $value_expr,
+            phantom: ::std::marker::PhantomData,
+        }
+    })
+}
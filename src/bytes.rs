@@ -126,6 +126,10 @@ pub struct ByteBufVisitor;
 impl de::Visitor for ByteBufVisitor {
     type Value = ByteBuf;
 
+    fn expecting(&self) -> &'static str {
+        "bytes"
+    }
+
     #[inline]
     fn visit_unit<E>(&mut self) -> Result<ByteBuf, E>
         where E: de::Error,
@@ -0,0 +1,851 @@
+//! A buffered, format-independent copy of a deserialized value.
+//!
+//! Some derived `Deserialize` impls have to look at a value before they know how to deserialize
+//! it, such as an internally tagged enum whose tag may come after the other fields. They first
+//! deserialize the value into a `Content`, then replay it through a `ContentDeserializer`, which
+//! reports errors in the original `Deserializer`'s error type.
+
+use std::marker::PhantomData;
+use std::slice;
+use std::vec;
+
+use de;
+
+/// Any value a `Deserializer` can produce, kept in memory.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Content {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    Unit,
+    None,
+    Some(Box<Content>),
+    Seq(Vec<Content>),
+    Map(Vec<(Content, Content)>),
+}
+
+impl Content {
+    fn type_(&self) -> de::Type {
+        match *self {
+            Content::Bool(_) => de::Type::Bool,
+            Content::I64(_) => de::Type::I64,
+            Content::U64(_) => de::Type::U64,
+            Content::F64(_) => de::Type::F64,
+            Content::String(_) => de::Type::String,
+            Content::Bytes(_) => de::Type::Bytes,
+            Content::Unit => de::Type::Unit,
+            Content::None | Content::Some(_) => de::Type::Option,
+            Content::Seq(_) => de::Type::Seq,
+            Content::Map(_) => de::Type::Map,
+        }
+    }
+
+    /// Names the map entry with this key in the path of an error.
+    fn key_name(&self) -> String {
+        match *self {
+            Content::String(ref key) => key.clone(),
+            Content::Bool(key) => key.to_string(),
+            Content::I64(key) => key.to_string(),
+            Content::U64(key) => key.to_string(),
+            Content::F64(key) => key.to_string(),
+            ref key => format!("{:?}", key),
+        }
+    }
+}
+
+impl de::Deserialize for Content {
+    #[inline]
+    fn deserialize<D>(deserializer: &mut D) -> Result<Content, D::Error>
+        where D: de::Deserializer,
+    {
+        deserializer.visit(ContentVisitor)
+    }
+}
+
+struct ContentVisitor;
+
+impl de::Visitor for ContentVisitor {
+    type Value = Content;
+
+    fn expecting(&self) -> &'static str {
+        "any value"
+    }
+
+    #[inline]
+    fn visit_bool<E>(&mut self, value: bool) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::Bool(value))
+    }
+
+    #[inline]
+    fn visit_i64<E>(&mut self, value: i64) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::I64(value))
+    }
+
+    #[inline]
+    fn visit_u64<E>(&mut self, value: u64) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::U64(value))
+    }
+
+    #[inline]
+    fn visit_f64<E>(&mut self, value: f64) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::F64(value))
+    }
+
+    #[inline]
+    fn visit_str<E>(&mut self, value: &str) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::String(value.to_string()))
+    }
+
+    #[inline]
+    fn visit_string<E>(&mut self, value: String) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::String(value))
+    }
+
+    #[inline]
+    fn visit_bytes<E>(&mut self, value: &[u8]) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::Bytes(value.to_vec()))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E>(&mut self, value: Vec<u8>) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::Bytes(value))
+    }
+
+    #[inline]
+    fn visit_unit<E>(&mut self) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::Unit)
+    }
+
+    #[inline]
+    fn visit_none<E>(&mut self) -> Result<Content, E>
+        where E: de::Error,
+    {
+        Ok(Content::None)
+    }
+
+    #[inline]
+    fn visit_some<D>(&mut self, deserializer: &mut D) -> Result<Content, D::Error>
+        where D: de::Deserializer,
+    {
+        let content = try!(de::Deserialize::deserialize(deserializer));
+        Ok(Content::Some(Box::new(content)))
+    }
+
+    #[inline]
+    fn visit_seq<V>(&mut self, mut visitor: V) -> Result<Content, V::Error>
+        where V: de::SeqVisitor,
+    {
+        let mut values = Vec::with_capacity(visitor.size_hint().0);
+
+        while let Some(value) = try!(visitor.visit()) {
+            values.push(value);
+        }
+
+        try!(visitor.end());
+        Ok(Content::Seq(values))
+    }
+
+    #[inline]
+    fn visit_map<V>(&mut self, mut visitor: V) -> Result<Content, V::Error>
+        where V: de::MapVisitor,
+    {
+        let mut entries = Vec::with_capacity(visitor.size_hint().0);
+
+        while let Some(entry) = try!(visitor.visit()) {
+            entries.push(entry);
+        }
+
+        try!(visitor.end());
+        Ok(Content::Map(entries))
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Replays a `Content` into a `Visitor`, raising errors of type `E`.
+///
+/// It also acts as the `VariantVisitor` of an enum whose variant has already been chosen, where
+/// `visit_value` deserializes the variant's data from the buffered content.
+pub struct ContentDeserializer<E> {
+    content: Option<Content>,
+    marker: PhantomData<E>,
+}
+
+impl<E> ContentDeserializer<E> {
+    pub fn new(content: Content) -> ContentDeserializer<E> {
+        ContentDeserializer {
+            content: Some(content),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> ContentDeserializer<E>
+    where E: de::Error,
+{
+    /// Checks that the content is a map without entries. Used by internally tagged unit variants
+    /// that deny unknown fields, since the tag should have been their only entry.
+    pub fn deny_entries(&mut self) -> Result<(), E> {
+        match self.content.take() {
+            Some(Content::Map(entries)) => {
+                match entries.into_iter().next() {
+                    Some((key, _)) => Err(de::Error::unknown_field(&key.key_name(), &[])),
+                    None => Ok(()),
+                }
+            }
+            Some(content) => Err(de::Error::invalid_type("unit variant", content.type_())),
+            None => Err(de::Error::end_of_stream_error()),
+        }
+    }
+}
+
+impl<E> de::Deserializer for ContentDeserializer<E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    fn visit<V>(&mut self, mut visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        let content = match self.content.take() {
+            Some(content) => content,
+            None => { return Err(de::Error::end_of_stream_error()); }
+        };
+
+        match content {
+            Content::Bool(value) => visitor.visit_bool(value),
+            Content::I64(value) => visitor.visit_i64(value),
+            Content::U64(value) => visitor.visit_u64(value),
+            Content::F64(value) => visitor.visit_f64(value),
+            Content::String(value) => visitor.visit_string(value),
+            Content::Bytes(value) => visitor.visit_byte_buf(value),
+            Content::Unit => visitor.visit_unit(),
+            Content::None => visitor.visit_none(),
+            Content::Some(content) => {
+                visitor.visit_some(&mut ContentDeserializer::<E>::new(*content))
+            }
+            Content::Seq(values) => {
+                let len = values.len();
+                visitor.visit_seq(SeqDeserializer::<E>::new(values, len))
+            }
+            Content::Map(entries) => {
+                let len = entries.len();
+                visitor.visit_map(MapDeserializer::<E>::new(entries, len))
+            }
+        }
+    }
+
+    fn visit_option<V>(&mut self, mut visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        match self.content.take() {
+            Some(Content::None) | Some(Content::Unit) => visitor.visit_none(),
+            Some(Content::Some(content)) => {
+                visitor.visit_some(&mut ContentDeserializer::<E>::new(*content))
+            }
+            Some(content) => visitor.visit_some(&mut ContentDeserializer::<E>::new(content)),
+            None => Err(de::Error::end_of_stream_error()),
+        }
+    }
+
+    /// Enums are expected in the externally tagged form, either the variant name alone or a map
+    /// with a single entry from the variant name to its data.
+    fn visit_enum<V>(&mut self, _name: &str, mut visitor: V) -> Result<V::Value, E>
+        where V: de::EnumVisitor,
+    {
+        let (variant, value) = match self.content.take() {
+            Some(Content::String(variant)) => (Content::String(variant), None),
+            Some(Content::Map(entries)) => {
+                if entries.len() != 1 {
+                    return Err(de::Error::invalid_length(entries.len(), "a map of length 1"));
+                }
+
+                let (variant, value) = entries.into_iter().next().unwrap();
+                (variant, Some(value))
+            }
+            Some(content) => {
+                return Err(de::Error::invalid_type("enum", content.type_()));
+            }
+            None => { return Err(de::Error::end_of_stream_error()); }
+        };
+
+        visitor.visit(EnumDeserializer::<E> {
+            variant: Some(variant),
+            value: value,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<E> de::VariantVisitor for ContentDeserializer<E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    /// The variant is chosen before the `ContentDeserializer` is created, so there is no variant
+    /// to visit.
+    fn visit_variant<V>(&mut self) -> Result<V, E>
+        where V: de::Deserialize,
+    {
+        Err(de::Error::syntax_error())
+    }
+
+    fn visit_value<V>(&mut self, visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        de::Deserializer::visit(self, visitor)
+    }
+
+    fn visit_newtype<T>(&mut self) -> Result<T, E>
+        where T: de::Deserialize,
+    {
+        de::Deserialize::deserialize(self)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+struct SeqDeserializer<E> {
+    iter: vec::IntoIter<Content>,
+    len: usize,
+    index: usize,
+    marker: PhantomData<E>,
+}
+
+impl<E> SeqDeserializer<E> {
+    fn new(values: Vec<Content>, len: usize) -> SeqDeserializer<E> {
+        SeqDeserializer {
+            iter: values.into_iter(),
+            len: len,
+            index: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> de::SeqVisitor for SeqDeserializer<E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    fn visit<T>(&mut self) -> Result<Option<T>, E>
+        where T: de::Deserialize,
+    {
+        match self.iter.next() {
+            Some(value) => {
+                self.len -= 1;
+                let mut de = ContentDeserializer::<E>::new(value);
+                match de::Deserialize::deserialize(&mut de) {
+                    Ok(value) => {
+                        self.index += 1;
+                        Ok(Some(value))
+                    }
+                    Err(err) => Err(de::Error::at_index(err, self.index)),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn end(&mut self) -> Result<(), E> {
+        if self.len == 0 {
+            Ok(())
+        } else {
+            let expected = format!("a sequence of length {}", self.index);
+            Err(de::Error::invalid_length(self.index + self.len, &expected))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+struct MapDeserializer<E> {
+    iter: vec::IntoIter<(Content, Content)>,
+    key: Option<Content>,
+    value: Option<Content>,
+    len: usize,
+    count: usize,
+    marker: PhantomData<E>,
+}
+
+impl<E> MapDeserializer<E> {
+    fn new(entries: Vec<(Content, Content)>, len: usize) -> MapDeserializer<E> {
+        MapDeserializer {
+            iter: entries.into_iter(),
+            key: None,
+            value: None,
+            len: len,
+            count: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> de::MapVisitor for MapDeserializer<E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    fn visit_key<T>(&mut self) -> Result<Option<T>, E>
+        where T: de::Deserialize,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.len -= 1;
+                self.count += 1;
+                self.value = Some(value);
+
+                // Keep the key, so errors in its value can name it.
+                let mut de = ContentDeserializer::<E>::new(key.clone());
+                self.key = Some(key);
+                Ok(Some(try!(de::Deserialize::deserialize(&mut de))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn visit_value<T>(&mut self) -> Result<T, E>
+        where T: de::Deserialize,
+    {
+        match self.value.take() {
+            Some(value) => {
+                let mut de = ContentDeserializer::<E>::new(value);
+                match de::Deserialize::deserialize(&mut de) {
+                    Ok(value) => Ok(value),
+                    Err(err) => {
+                        let key = match self.key {
+                            Some(ref key) => key.key_name(),
+                            None => String::new(),
+                        };
+                        Err(de::Error::at_key(err, &key))
+                    }
+                }
+            }
+            None => Err(de::Error::syntax_error()),
+        }
+    }
+
+    fn end(&mut self) -> Result<(), E> {
+        if self.len == 0 {
+            Ok(())
+        } else {
+            let expected = format!("a map of length {}", self.count);
+            Err(de::Error::invalid_length(self.count + self.len, &expected))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+
+    fn missing_field<V>(&mut self, field: &'static str) -> Result<V, E>
+        where V: de::Deserialize,
+    {
+        // Types like `Option` can still be created from a missing field.
+        let mut de = ContentDeserializer::<E>::new(Content::None);
+
+        match de::Deserialize::deserialize(&mut de) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(de::Error::missing_field_error(field)),
+        }
+    }
+}
+
+struct EnumDeserializer<E> {
+    variant: Option<Content>,
+    value: Option<Content>,
+    marker: PhantomData<E>,
+}
+
+impl<E> de::VariantVisitor for EnumDeserializer<E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    fn visit_variant<V>(&mut self) -> Result<V, E>
+        where V: de::Deserialize,
+    {
+        match self.variant.take() {
+            Some(variant) => {
+                de::Deserialize::deserialize(&mut ContentDeserializer::<E>::new(variant))
+            }
+            None => Err(de::Error::syntax_error()),
+        }
+    }
+
+    fn visit_value<V>(&mut self, mut visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        match self.value.take() {
+            Some(value) => {
+                de::Deserializer::visit(&mut ContentDeserializer::<E>::new(value), visitor)
+            }
+            None => visitor.visit_unit(),
+        }
+    }
+
+    fn visit_newtype<T>(&mut self) -> Result<T, E>
+        where T: de::Deserialize,
+    {
+        match self.value.take() {
+            Some(value) => de::Deserialize::deserialize(&mut ContentDeserializer::<E>::new(value)),
+            None => Err(de::Error::invalid_type("newtype variant", de::Type::Unit)),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// The result of `TaggedContentVisitor` and `AdjacentlyTaggedContentVisitor`: the value of the
+/// tag entry, and the buffered content of the variant.
+pub struct TaggedContent<T> {
+    pub tag: T,
+    pub content: Content,
+}
+
+/// Visits a map, deserializing the entry with the key `tag_name` as a `T` and buffering the
+/// others, wherever the tag appears among them. Used by internally tagged enums.
+pub struct TaggedContentVisitor<T> {
+    tag_name: &'static str,
+    marker: PhantomData<T>,
+}
+
+impl<T> TaggedContentVisitor<T> {
+    pub fn new(tag_name: &'static str) -> TaggedContentVisitor<T> {
+        TaggedContentVisitor {
+            tag_name: tag_name,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> de::Visitor for TaggedContentVisitor<T>
+    where T: de::Deserialize,
+{
+    type Value = TaggedContent<T>;
+
+    fn expecting(&self) -> &'static str {
+        "internally tagged enum"
+    }
+
+    fn visit_map<V>(&mut self, mut visitor: V) -> Result<TaggedContent<T>, V::Error>
+        where V: de::MapVisitor,
+    {
+        let mut tag = None;
+        let mut entries = Vec::with_capacity(visitor.size_hint().0);
+
+        while let Some(key) = try!(visitor.visit_key()) {
+            let is_tag = match key {
+                Content::String(ref key) => &key[..] == self.tag_name,
+                _ => false,
+            };
+
+            if is_tag {
+                if tag.is_some() {
+                    return Err(de::Error::duplicate_field(self.tag_name));
+                }
+
+                tag = Some(try!(visitor.visit_value()));
+            } else {
+                entries.push((key, try!(visitor.visit_value())));
+            }
+        }
+
+        try!(visitor.end());
+
+        match tag {
+            Some(tag) => Ok(TaggedContent { tag: tag, content: Content::Map(entries) }),
+            None => Err(de::Error::missing_field_error(self.tag_name)),
+        }
+    }
+}
+
+/// Visits a map, deserializing the entry with the key `tag_name` as a `T` and buffering the value
+/// of the entry with the key `content_name`, in either order. Other entries are ignored. Used by
+/// adjacently tagged enums.
+///
+/// The content is `Content::Unit` when its entry is missing, as it is for unit variants.
+pub struct AdjacentlyTaggedContentVisitor<T> {
+    tag_name: &'static str,
+    content_name: &'static str,
+    marker: PhantomData<T>,
+}
+
+impl<T> AdjacentlyTaggedContentVisitor<T> {
+    pub fn new(
+        tag_name: &'static str,
+        content_name: &'static str,
+    ) -> AdjacentlyTaggedContentVisitor<T> {
+        AdjacentlyTaggedContentVisitor {
+            tag_name: tag_name,
+            content_name: content_name,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> de::Visitor for AdjacentlyTaggedContentVisitor<T>
+    where T: de::Deserialize,
+{
+    type Value = TaggedContent<T>;
+
+    fn expecting(&self) -> &'static str {
+        "adjacently tagged enum"
+    }
+
+    fn visit_map<V>(&mut self, mut visitor: V) -> Result<TaggedContent<T>, V::Error>
+        where V: de::MapVisitor,
+    {
+        let mut tag = None;
+        let mut content = None;
+
+        while let Some(key) = try!(visitor.visit_key::<Content>()) {
+            let key = match key {
+                Content::String(key) => key,
+                _ => {
+                    try!(visitor.visit_value::<de::impls::IgnoredAny>());
+                    continue;
+                }
+            };
+
+            if &key[..] == self.tag_name {
+                if tag.is_some() {
+                    return Err(de::Error::duplicate_field(self.tag_name));
+                }
+
+                tag = Some(try!(visitor.visit_value()));
+            } else if &key[..] == self.content_name {
+                if content.is_some() {
+                    return Err(de::Error::duplicate_field(self.content_name));
+                }
+
+                content = Some(try!(visitor.visit_value()));
+            } else {
+                try!(visitor.visit_value::<de::impls::IgnoredAny>());
+            }
+        }
+
+        try!(visitor.end());
+
+        match tag {
+            Some(tag) => {
+                Ok(TaggedContent {
+                    tag: tag,
+                    content: content.unwrap_or(Content::Unit),
+                })
+            }
+            None => Err(de::Error::missing_field_error(self.tag_name)),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Deserializes a `#[serde(flatten)]` field from the entries of its parent's map that none of the
+/// parent's own fields claimed.
+///
+/// An entry is used up when the field deserializes its value. Entries the field skips over with
+/// `IgnoredAny`, such as unknown keys of a flattened struct, are left for the parent's later
+/// flattened fields.
+pub struct FlatMapDeserializer<'a, E> {
+    entries: &'a mut Vec<Option<(Content, Content)>>,
+    marker: PhantomData<E>,
+}
+
+impl<'a, E> FlatMapDeserializer<'a, E> {
+    pub fn new(entries: &'a mut Vec<Option<(Content, Content)>>) -> FlatMapDeserializer<'a, E> {
+        FlatMapDeserializer {
+            entries: entries,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns how many entries have not been used up yet.
+    fn remaining(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_some()).count()
+    }
+}
+
+impl<'a, E> de::Deserializer for FlatMapDeserializer<'a, E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    fn visit<V>(&mut self, mut visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        visitor.visit_map(FlatMapVisitor::<E> {
+            iter: self.entries.iter_mut(),
+            entry: None,
+            marker: PhantomData,
+        })
+    }
+
+    /// A flattened `Option` is `None` if its value can't be deserialized from the remaining
+    /// entries, or if it doesn't use any of them. Either way the entries are left for the fields
+    /// that come after it.
+    fn visit_option<V>(&mut self, mut visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        let entries = self.entries.clone();
+        let remaining = self.remaining();
+
+        match visitor.visit_some(self) {
+            Ok(value) => {
+                if self.remaining() < remaining {
+                    return Ok(value);
+                }
+            }
+            Err(_) => {
+                *self.entries = entries;
+            }
+        }
+
+        visitor.visit_none()
+    }
+}
+
+struct FlatMapVisitor<'a, E> {
+    iter: slice::IterMut<'a, Option<(Content, Content)>>,
+    entry: Option<&'a mut Option<(Content, Content)>>,
+    marker: PhantomData<E>,
+}
+
+impl<'a, E> de::MapVisitor for FlatMapVisitor<'a, E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    fn visit_key<T>(&mut self) -> Result<Option<T>, E>
+        where T: de::Deserialize,
+    {
+        while let Some(entry) = self.iter.next() {
+            let key = match *entry {
+                Some((ref key, _)) => key.clone(),
+                None => { continue; }
+            };
+
+            self.entry = Some(entry);
+
+            let mut de = ContentDeserializer::<E>::new(key);
+            return Ok(Some(try!(de::Deserialize::deserialize(&mut de))));
+        }
+
+        Ok(None)
+    }
+
+    fn visit_value<T>(&mut self) -> Result<T, E>
+        where T: de::Deserialize,
+    {
+        let entry = match self.entry.take() {
+            Some(entry) => entry,
+            None => { return Err(de::Error::syntax_error()); }
+        };
+
+        let (key, value) = match entry.take() {
+            Some(entry) => entry,
+            None => { return Err(de::Error::syntax_error()); }
+        };
+
+        let mut de = FlatValueDeserializer::<E> {
+            content: Some(value),
+            marker: PhantomData,
+        };
+
+        let value = match de::Deserialize::deserialize(&mut de) {
+            Ok(value) => Ok(value),
+            Err(err) => Err(de::Error::at_key(err, &key.key_name())),
+        };
+
+        // The value was ignored, so put the entry back.
+        if let Some(content) = de.content {
+            *entry = Some((key, content));
+        }
+
+        value
+    }
+
+    fn end(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn missing_field<V>(&mut self, field: &'static str) -> Result<V, E>
+        where V: de::Deserialize,
+    {
+        // Types like `Option` can still be created from a missing field.
+        let mut de = ContentDeserializer::<E>::new(Content::None);
+
+        match de::Deserialize::deserialize(&mut de) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(de::Error::missing_field_error(field)),
+        }
+    }
+}
+
+/// Deserializes the value of one flattened entry, leaving it in place if it is ignored.
+struct FlatValueDeserializer<E> {
+    content: Option<Content>,
+    marker: PhantomData<E>,
+}
+
+impl<E> FlatValueDeserializer<E> {
+    fn take(&mut self) -> ContentDeserializer<E> {
+        ContentDeserializer {
+            content: self.content.take(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> de::Deserializer for FlatValueDeserializer<E>
+    where E: de::Error,
+{
+    type Error = E;
+
+    fn visit<V>(&mut self, visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        de::Deserializer::visit(&mut self.take(), visitor)
+    }
+
+    fn visit_option<V>(&mut self, visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        de::Deserializer::visit_option(&mut self.take(), visitor)
+    }
+
+    fn visit_enum<V>(&mut self, name: &str, visitor: V) -> Result<V::Value, E>
+        where V: de::EnumVisitor,
+    {
+        de::Deserializer::visit_enum(&mut self.take(), name, visitor)
+    }
+
+    fn visit_ignored_any<V>(&mut self, mut visitor: V) -> Result<V::Value, E>
+        where V: de::Visitor,
+    {
+        visitor.visit_unit()
+    }
+}
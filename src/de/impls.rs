@@ -1,3 +1,4 @@
+use std::borrow::{Cow, ToOwned};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecMap};
 use std::hash::Hash;
 use std::marker::PhantomData;
@@ -7,7 +8,10 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 use de::{
+    BorrowedDeserializer,
+    BorrowedVisitor,
     Deserialize,
+    DeserializeBorrowed,
     Deserializer,
     Error,
     MapVisitor,
@@ -22,6 +26,10 @@ pub struct UnitVisitor;
 impl Visitor for UnitVisitor {
     type Value = ();
 
+    fn expecting(&self) -> &'static str {
+        "unit"
+    }
+
     fn visit_unit<E>(&mut self) -> Result<(), E>
         where E: Error,
     {
@@ -43,6 +51,131 @@ impl Deserialize for () {
     }
 }
 
+impl<T> Deserialize for PhantomData<T> {
+    fn deserialize<D>(deserializer: &mut D) -> Result<PhantomData<T>, D::Error>
+        where D: Deserializer,
+    {
+        try!(deserializer.visit(UnitVisitor));
+        Ok(PhantomData)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// A value that can be deserialized from anything, discarding it along with all the elements of
+/// any sequence or map. Derived `Deserialize` impls use it to skip over unknown fields.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IgnoredAny;
+
+struct IgnoredAnyVisitor;
+
+impl Visitor for IgnoredAnyVisitor {
+    type Value = IgnoredAny;
+
+    fn expecting(&self) -> &'static str {
+        "anything"
+    }
+
+    #[inline]
+    fn visit_bool<E>(&mut self, _: bool) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_i64<E>(&mut self, _: i64) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_u64<E>(&mut self, _: u64) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_f64<E>(&mut self, _: f64) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_str<E>(&mut self, _: &str) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_unit<E>(&mut self) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_none<E>(&mut self) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_some<D>(&mut self, deserializer: &mut D) -> Result<IgnoredAny, D::Error>
+        where D: Deserializer,
+    {
+        Deserialize::deserialize(deserializer)
+    }
+
+    #[inline]
+    fn visit_seq<V>(&mut self, mut visitor: V) -> Result<IgnoredAny, V::Error>
+        where V: SeqVisitor,
+    {
+        while let Some(IgnoredAny) = try!(visitor.visit()) { }
+
+        try!(visitor.end());
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_map<V>(&mut self, mut visitor: V) -> Result<IgnoredAny, V::Error>
+        where V: MapVisitor,
+    {
+        while let Some((IgnoredAny, IgnoredAny)) = try!(visitor.visit()) { }
+
+        try!(visitor.end());
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_bytes<E>(&mut self, _: &[u8]) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+
+    #[inline]
+    fn visit_byte_buf<E>(&mut self, _: Vec<u8>) -> Result<IgnoredAny, E>
+        where E: Error,
+    {
+        Ok(IgnoredAny)
+    }
+}
+
+impl Deserialize for IgnoredAny {
+    #[inline]
+    fn deserialize<D>(deserializer: &mut D) -> Result<IgnoredAny, D::Error>
+        where D: Deserializer,
+    {
+        deserializer.visit_ignored_any(IgnoredAnyVisitor)
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
 struct BoolVisitor;
@@ -50,6 +183,10 @@ struct BoolVisitor;
 impl Visitor for BoolVisitor {
     type Value = bool;
 
+    fn expecting(&self) -> &'static str {
+        "bool"
+    }
+
     fn visit_bool<E>(&mut self, v: bool) -> Result<bool, E>
         where E: Error,
     {
@@ -75,20 +212,30 @@ macro_rules! impl_deserialize_num_method {
         {
             match FromPrimitive::$from_method(v) {
                 Some(v) => Ok(v),
-                None => Err(Error::syntax_error()),
+                None => {
+                    Err(Error::invalid_value(&format!("{} is out of range for {}", v, self.name)))
+                }
             }
         }
     }
 }
 
 pub struct PrimitiveVisitor<T> {
+    name: &'static str,
     marker: PhantomData<T>,
 }
 
 impl<T> PrimitiveVisitor<T> {
     #[inline]
     pub fn new() -> Self {
+        PrimitiveVisitor::named("a number")
+    }
+
+    /// Create a visitor that describes the value it expects as `name` in error messages.
+    #[inline]
+    pub fn named(name: &'static str) -> Self {
         PrimitiveVisitor {
+            name: name,
             marker: PhantomData,
         }
     }
@@ -99,6 +246,10 @@ impl<
 > Visitor for PrimitiveVisitor<T> {
     type Value = T;
 
+    fn expecting(&self) -> &'static str {
+        self.name
+    }
+
     impl_deserialize_num_method!(isize, visit_isize, from_isize);
     impl_deserialize_num_method!(i8, visit_i8, from_i8);
     impl_deserialize_num_method!(i16, visit_i16, from_i16);
@@ -120,7 +271,7 @@ macro_rules! impl_deserialize_num {
             fn deserialize<D>(deserializer: &mut D) -> Result<$ty, D::Error>
                 where D: Deserializer,
             {
-                deserializer.visit(PrimitiveVisitor::new())
+                deserializer.visit(PrimitiveVisitor::named(stringify!($ty)))
             }
         }
     }
@@ -146,6 +297,10 @@ struct CharVisitor;
 impl Visitor for CharVisitor {
     type Value = char;
 
+    fn expecting(&self) -> &'static str {
+        "char"
+    }
+
     #[inline]
     fn visit_char<E>(&mut self, v: char) -> Result<char, E>
         where E: Error,
@@ -160,12 +315,12 @@ impl Visitor for CharVisitor {
         let mut iter = v.chars();
         if let Some(v) = iter.next() {
             if iter.next().is_some() {
-                Err(Error::syntax_error())
+                Err(Error::invalid_value("expected a single character, found a longer string"))
             } else {
                 Ok(v)
             }
         } else {
-            Err(Error::end_of_stream_error())
+            Err(Error::invalid_value("expected a single character, found an empty string"))
         }
     }
 }
@@ -186,6 +341,10 @@ struct StringVisitor;
 impl Visitor for StringVisitor {
     type Value = String;
 
+    fn expecting(&self) -> &'static str {
+        "string"
+    }
+
     fn visit_str<E>(&mut self, v: &str) -> Result<String, E>
         where E: Error,
     {
@@ -218,6 +377,10 @@ impl<
 > Visitor for OptionVisitor<T> {
     type Value = Option<T>;
 
+    fn expecting(&self) -> &'static str {
+        "option"
+    }
+
     #[inline]
     fn visit_none<E>(&mut self) -> Result<Option<T>, E>
         where E: Error,
@@ -260,6 +423,10 @@ impl<T> Visitor for BTreeSetVisitor<T>
 {
     type Value = BTreeSet<T>;
 
+    fn expecting(&self) -> &'static str {
+        "set"
+    }
+
     #[inline]
     fn visit_unit<E>(&mut self) -> Result<BTreeSet<T>, E>
         where E: Error,
@@ -312,6 +479,10 @@ impl<T> Visitor for HashSetVisitor<T>
 {
     type Value = HashSet<T>;
 
+    fn expecting(&self) -> &'static str {
+        "set"
+    }
+
     #[inline]
     fn visit_unit<E>(&mut self) -> Result<HashSet<T>, E>
         where E: Error,
@@ -363,6 +534,10 @@ impl<T> VecVisitor<T> {
 impl<T> Visitor for VecVisitor<T> where T: Deserialize {
     type Value = Vec<T>;
 
+    fn expecting(&self) -> &'static str {
+        "sequence"
+    }
+
     #[inline]
     fn visit_unit<E>(&mut self) -> Result<Vec<T>, E>
         where E: Error,
@@ -412,16 +587,27 @@ macro_rules! tuple_impls {
             > Visitor for $visitor<$($name,)+> {
                 type Value = ($($name,)+);
 
+                fn expecting(&self) -> &'static str {
+                    "tuple"
+                }
+
                 #[inline]
-                #[allow(non_snake_case)]
+                #[allow(non_snake_case, unused_assignments)]
                 fn visit_seq<V>(&mut self, mut visitor: V) -> Result<($($name,)+), V::Error>
                     where V: SeqVisitor,
                 {
+                    let mut len = 0;
+                    let expected_len = [$(stringify!($name)),+].len();
+
                     $(
                         let $name = match try!(visitor.visit()) {
                             Some(value) => value,
-                            None => { return Err(Error::end_of_stream_error()); }
+                            None => {
+                                let expected = format!("a tuple of length {}", expected_len);
+                                return Err(Error::invalid_length(len, &expected));
+                            }
                         };
+                        len += 1;
                      )+;
 
                     try!(visitor.end());
@@ -480,6 +666,10 @@ impl<K, V> Visitor for BTreeMapVisitor<K, V>
 {
     type Value = BTreeMap<K, V>;
 
+    fn expecting(&self) -> &'static str {
+        "map"
+    }
+
     #[inline]
     fn visit_unit<E>(&mut self) -> Result<BTreeMap<K, V>, E>
         where E: Error,
@@ -535,6 +725,10 @@ impl<K, V> Visitor for HashMapVisitor<K, V>
 {
     type Value = HashMap<K, V>;
 
+    fn expecting(&self) -> &'static str {
+        "map"
+    }
+
     #[inline]
     fn visit_unit<E>(&mut self) -> Result<HashMap<K, V>, E>
         where E: Error,
@@ -590,6 +784,10 @@ impl<V> Visitor for VecMapVisitor<V>
 {
     type Value = VecMap<V>;
 
+    fn expecting(&self) -> &'static str {
+        "map"
+    }
+
     #[inline]
     fn visit_unit<E>(&mut self) -> Result<VecMap<V>, E>
         where E: Error,
@@ -631,6 +829,10 @@ struct PathBufVisitor;
 impl Visitor for PathBufVisitor {
     type Value = path::PathBuf;
 
+    fn expecting(&self) -> &'static str {
+        "path"
+    }
+
     fn visit_str<E>(&mut self, v: &str) -> Result<path::PathBuf, E>
         where E: Error,
     {
@@ -654,6 +856,204 @@ impl Deserialize for path::PathBuf {
 
 ///////////////////////////////////////////////////////////////////////////////
 
+struct BorrowedStrVisitor<'a> {
+    marker: PhantomData<&'a str>,
+}
+
+/// Explains why a `&str` or `&[u8]` could not be deserialized from data that was not borrowed.
+const NOT_BORROWED: &'static str =
+    "data that is not a slice of the input, such as a string with escape sequences, cannot be \
+     borrowed; use an owned type or a #[serde(borrow)] Cow instead";
+
+impl<'a> Visitor for BorrowedStrVisitor<'a> {
+    type Value = &'a str;
+
+    fn expecting(&self) -> &'static str {
+        "borrowed string"
+    }
+
+    fn visit_str<E>(&mut self, _v: &str) -> Result<&'a str, E>
+        where E: Error,
+    {
+        Err(Error::invalid_value(NOT_BORROWED))
+    }
+}
+
+impl<'a> BorrowedVisitor<'a> for BorrowedStrVisitor<'a> {
+    #[inline]
+    fn visit_borrowed_str<E>(&mut self, v: &'a str) -> Result<&'a str, E>
+        where E: Error,
+    {
+        Ok(v)
+    }
+}
+
+impl<'a> DeserializeBorrowed<'a> for &'a str {
+    fn deserialize_borrowed<D>(deserializer: &mut D) -> Result<&'a str, D::Error>
+        where D: BorrowedDeserializer<'a>,
+    {
+        deserializer.visit_borrowed(BorrowedStrVisitor { marker: PhantomData })
+    }
+}
+
+struct BorrowedBytesVisitor<'a> {
+    marker: PhantomData<&'a [u8]>,
+}
+
+impl<'a> Visitor for BorrowedBytesVisitor<'a> {
+    type Value = &'a [u8];
+
+    fn expecting(&self) -> &'static str {
+        "borrowed bytes"
+    }
+
+    fn visit_str<E>(&mut self, _v: &str) -> Result<&'a [u8], E>
+        where E: Error,
+    {
+        Err(Error::invalid_value(NOT_BORROWED))
+    }
+
+    fn visit_bytes<E>(&mut self, _v: &[u8]) -> Result<&'a [u8], E>
+        where E: Error,
+    {
+        Err(Error::invalid_value(NOT_BORROWED))
+    }
+}
+
+impl<'a> BorrowedVisitor<'a> for BorrowedBytesVisitor<'a> {
+    #[inline]
+    fn visit_borrowed_str<E>(&mut self, v: &'a str) -> Result<&'a [u8], E>
+        where E: Error,
+    {
+        Ok(v.as_bytes())
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E>(&mut self, v: &'a [u8]) -> Result<&'a [u8], E>
+        where E: Error,
+    {
+        Ok(v)
+    }
+}
+
+impl<'a> DeserializeBorrowed<'a> for &'a [u8] {
+    fn deserialize_borrowed<D>(deserializer: &mut D) -> Result<&'a [u8], D::Error>
+        where D: BorrowedDeserializer<'a>,
+    {
+        deserializer.visit_borrowed(BorrowedBytesVisitor { marker: PhantomData })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+impl<'a, T: ?Sized> Deserialize for Cow<'a, T>
+    where T: ToOwned,
+          T::Owned: Deserialize,
+{
+    fn deserialize<D>(deserializer: &mut D) -> Result<Cow<'a, T>, D::Error>
+        where D: Deserializer,
+    {
+        let value = try!(Deserialize::deserialize(deserializer));
+        Ok(Cow::Owned(value))
+    }
+}
+
+/// A `Cow` that borrows from the input when it can and otherwise owns a copy, such as when a
+/// string contains escape sequences. Derived impls use it for `#[serde(borrow)]` fields, since a
+/// plain `Cow` is always deserialized as owned.
+pub struct BorrowedCow<'a, T: ?Sized + ToOwned + 'a>(pub Cow<'a, T>);
+
+struct BorrowedCowStrVisitor<'a> {
+    marker: PhantomData<&'a str>,
+}
+
+impl<'a> Visitor for BorrowedCowStrVisitor<'a> {
+    type Value = BorrowedCow<'a, str>;
+
+    fn expecting(&self) -> &'static str {
+        "string"
+    }
+
+    fn visit_str<E>(&mut self, v: &str) -> Result<BorrowedCow<'a, str>, E>
+        where E: Error,
+    {
+        Ok(BorrowedCow(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E>(&mut self, v: String) -> Result<BorrowedCow<'a, str>, E>
+        where E: Error,
+    {
+        Ok(BorrowedCow(Cow::Owned(v)))
+    }
+}
+
+impl<'a> BorrowedVisitor<'a> for BorrowedCowStrVisitor<'a> {
+    #[inline]
+    fn visit_borrowed_str<E>(&mut self, v: &'a str) -> Result<BorrowedCow<'a, str>, E>
+        where E: Error,
+    {
+        Ok(BorrowedCow(Cow::Borrowed(v)))
+    }
+}
+
+impl<'a> DeserializeBorrowed<'a> for BorrowedCow<'a, str> {
+    fn deserialize_borrowed<D>(deserializer: &mut D) -> Result<BorrowedCow<'a, str>, D::Error>
+        where D: BorrowedDeserializer<'a>,
+    {
+        deserializer.visit_borrowed(BorrowedCowStrVisitor { marker: PhantomData })
+    }
+}
+
+struct BorrowedCowBytesVisitor<'a> {
+    marker: PhantomData<&'a [u8]>,
+}
+
+impl<'a> Visitor for BorrowedCowBytesVisitor<'a> {
+    type Value = BorrowedCow<'a, [u8]>;
+
+    fn expecting(&self) -> &'static str {
+        "bytes"
+    }
+
+    fn visit_str<E>(&mut self, v: &str) -> Result<BorrowedCow<'a, [u8]>, E>
+        where E: Error,
+    {
+        Ok(BorrowedCow(Cow::Owned(v.as_bytes().to_vec())))
+    }
+
+    fn visit_bytes<E>(&mut self, v: &[u8]) -> Result<BorrowedCow<'a, [u8]>, E>
+        where E: Error,
+    {
+        Ok(BorrowedCow(Cow::Owned(v.to_vec())))
+    }
+}
+
+impl<'a> BorrowedVisitor<'a> for BorrowedCowBytesVisitor<'a> {
+    #[inline]
+    fn visit_borrowed_str<E>(&mut self, v: &'a str) -> Result<BorrowedCow<'a, [u8]>, E>
+        where E: Error,
+    {
+        Ok(BorrowedCow(Cow::Borrowed(v.as_bytes())))
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E>(&mut self, v: &'a [u8]) -> Result<BorrowedCow<'a, [u8]>, E>
+        where E: Error,
+    {
+        Ok(BorrowedCow(Cow::Borrowed(v)))
+    }
+}
+
+impl<'a> DeserializeBorrowed<'a> for BorrowedCow<'a, [u8]> {
+    fn deserialize_borrowed<D>(deserializer: &mut D) -> Result<BorrowedCow<'a, [u8]>, D::Error>
+        where D: BorrowedDeserializer<'a>,
+    {
+        deserializer.visit_borrowed(BorrowedCowBytesVisitor { marker: PhantomData })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
 impl<T: Deserialize> Deserialize for Box<T> {
     fn deserialize<D>(deserializer: &mut D) -> Result<Box<T>, D::Error>
         where D: Deserializer,
@@ -1,18 +1,146 @@
 //! Generic deserialization framework.
 
+use std::fmt;
+use std::marker::PhantomData;
 use std::str;
 
+pub mod content;
 pub mod impls;
 pub mod value;
 
 ///////////////////////////////////////////////////////////////////////////////
 
-pub trait Error {
+/// `Error` is a trait that allows a `Deserialize` to generically create a `Deserializer` error.
+///
+/// Only the first three methods are required. The rest default to a `syntax_error`, but formats
+/// should override them in order to produce more helpful error messages.
+pub trait Error: Sized {
     fn syntax_error() -> Self;
 
     fn end_of_stream_error() -> Self;
 
     fn missing_field_error(&'static str) -> Self;
+
+    /// Raised when a `Deserialize` fails for a reason that is specific to the type.
+    #[inline]
+    fn custom<T: Into<String>>(_msg: T) -> Self {
+        Error::syntax_error()
+    }
+
+    /// Raised when a `Visitor` expected one kind of value but the `Deserializer` produced another.
+    #[inline]
+    fn invalid_type(_expected: &'static str, _found: Type) -> Self {
+        Error::syntax_error()
+    }
+
+    /// Raised when a value has the right type but is not acceptable, such as an integer that is
+    /// out of range.
+    #[inline]
+    fn invalid_value(_msg: &str) -> Self {
+        Error::syntax_error()
+    }
+
+    /// Raised when a sequence or map has `len` elements, which is not the length the `Visitor`
+    /// expected. `expected` describes the length it wanted, such as "a tuple of length 2".
+    #[inline]
+    fn invalid_length(_len: usize, _expected: &str) -> Self {
+        Error::syntax_error()
+    }
+
+    /// Raised when a map contains a key that is not one of the `expected` field names.
+    #[inline]
+    fn unknown_field(_field: &str, _expected: &'static [&'static str]) -> Self {
+        Error::syntax_error()
+    }
+
+    /// Raised when an enum variant name is not one of the `expected` variant names.
+    #[inline]
+    fn unknown_variant(_variant: &str, _expected: &'static [&'static str]) -> Self {
+        Error::syntax_error()
+    }
+
+    /// Raised when a map contains the same field more than once.
+    #[inline]
+    fn duplicate_field(_field: &'static str) -> Self {
+        Error::syntax_error()
+    }
+
+    /// Records that this error was raised while deserializing the value of the map key or field
+    /// `key`. `MapVisitor`s call this as errors leave a value, so formats that report where an
+    /// error happened can build up its path. By default the key is ignored.
+    #[inline]
+    fn at_key(self, _key: &str) -> Self {
+        self
+    }
+
+    /// Records that this error was raised while deserializing element `index` of a sequence.
+    /// `SeqVisitor`s call this as errors leave an element. By default the index is ignored.
+    #[inline]
+    fn at_index(self, _index: usize) -> Self {
+        self
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// `Type` describes the kind of value a `Deserializer` handed to a `Visitor`. It is used to
+/// report type mismatches.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Type {
+    Bool,
+    Isize,
+    I8,
+    I16,
+    I32,
+    I64,
+    Usize,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Char,
+    Str,
+    String,
+    Unit,
+    Option,
+    Seq,
+    Map,
+    Bytes,
+    NewtypeStruct,
+    Enum,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Type::Bool => "bool",
+            Type::Isize => "isize",
+            Type::I8 => "i8",
+            Type::I16 => "i16",
+            Type::I32 => "i32",
+            Type::I64 => "i64",
+            Type::Usize => "usize",
+            Type::U8 => "u8",
+            Type::U16 => "u16",
+            Type::U32 => "u32",
+            Type::U64 => "u64",
+            Type::F32 => "f32",
+            Type::F64 => "f64",
+            Type::Char => "char",
+            Type::Str | Type::String => "string",
+            Type::Unit => "unit",
+            Type::Option => "option",
+            Type::Seq => "sequence",
+            Type::Map => "map",
+            Type::Bytes => "bytes",
+            Type::NewtypeStruct => "newtype struct",
+            Type::Enum => "enum",
+        };
+
+        f.write_str(name)
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -25,6 +153,19 @@ pub trait Deserialize {
 
 ///////////////////////////////////////////////////////////////////////////////
 
+/// `TryFrom` is a conversion that may fail. A container with a `#[serde(try_from = "T")]`
+/// attribute is deserialized as a `T` and then converted with it, and a failed conversion is
+/// reported through `Error::custom`.
+pub trait TryFrom<T>: Sized {
+    /// The reason a value could not be converted.
+    type Err: fmt::Display;
+
+    /// Converts the value, or explains why it is not acceptable.
+    fn try_from(value: T) -> Result<Self, Self::Err>;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
 /// `Deserializer` is an abstract trait that can deserialize values into a `Visitor`.
 pub trait Deserializer {
     type Error: Error;
@@ -94,6 +235,19 @@ pub trait Deserializer {
         self.visit_map(visitor)
     }
 
+    /// The `visit_newtype_struct` method allows a `Deserialize` type to inform the `Deserializer`
+    /// that it's expecting a struct that wraps a single value, such as `struct Meters(f64)`. By
+    /// default the wrapper is transparent, and the visitor reads the value from this deserializer.
+    #[inline]
+    fn visit_newtype_struct<V>(&mut self,
+                               _name: &str,
+                               mut visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor,
+              Self: Sized,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
     /// The `visit_enum` method allows a `Deserialize` type to inform the `Deserializer` that it's
     /// expecting an enum value. This allows deserializers that provide a custom enumeration
     /// serialization to properly deserialize the type.
@@ -113,6 +267,16 @@ pub trait Deserializer {
     {
         self.visit(visitor)
     }
+
+    /// The `visit_ignored_any` method allows a `Deserialize` type to inform the `Deserializer`
+    /// that it is going to discard the value. This allows deserializers to skip over the value
+    /// without interpreting it, or to keep it for someone else.
+    #[inline]
+    fn visit_ignored_any<V>(&mut self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor,
+    {
+        self.visit(visitor)
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -120,10 +284,17 @@ pub trait Deserializer {
 pub trait Visitor {
     type Value;
 
+    /// `expecting` describes the kind of value this visitor accepts. It is used as the expected
+    /// half of the errors the default methods produce, as in "expected u32, found string".
+    #[inline]
+    fn expecting(&self) -> &'static str {
+        "a value"
+    }
+
     fn visit_bool<E>(&mut self, _v: bool) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Bool))
     }
 
     fn visit_isize<E>(&mut self, v: isize) -> Result<Self::Value, E>
@@ -153,7 +324,7 @@ pub trait Visitor {
     fn visit_i64<E>(&mut self, _v: i64) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::I64))
     }
 
     fn visit_usize<E>(&mut self, v: usize) -> Result<Self::Value, E>
@@ -183,7 +354,7 @@ pub trait Visitor {
     fn visit_u64<E>(&mut self, _v: u64) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::U64))
     }
 
     fn visit_f32<E>(&mut self, v: f32) -> Result<Self::Value, E>
@@ -195,7 +366,7 @@ pub trait Visitor {
     fn visit_f64<E>(&mut self, _v: f64) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::F64))
     }
 
     #[inline]
@@ -211,7 +382,7 @@ pub trait Visitor {
     fn visit_str<E>(&mut self, _v: &str) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Str))
     }
 
     #[inline]
@@ -224,7 +395,7 @@ pub trait Visitor {
     fn visit_unit<E>(&mut self) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Unit))
     }
 
     #[inline]
@@ -237,37 +408,43 @@ pub trait Visitor {
     fn visit_none<E>(&mut self) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Option))
     }
 
     fn visit_some<D>(&mut self, _deserializer: &mut D) -> Result<Self::Value, D::Error>
         where D: Deserializer,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Option))
     }
 
     fn visit_seq<V>(&mut self, _visitor: V) -> Result<Self::Value, V::Error>
         where V: SeqVisitor,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Seq))
     }
 
     fn visit_map<V>(&mut self, _visitor: V) -> Result<Self::Value, V::Error>
         where V: MapVisitor,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Map))
+    }
+
+    fn visit_newtype_struct<D>(&mut self, _deserializer: &mut D) -> Result<Self::Value, D::Error>
+        where D: Deserializer,
+    {
+        Err(Error::invalid_type(self.expecting(), Type::NewtypeStruct))
     }
 
     fn visit_bytes<E>(&mut self, _v: &[u8]) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Bytes))
     }
 
     fn visit_byte_buf<E>(&mut self, _v: Vec<u8>) -> Result<Self::Value, E>
         where E: Error,
     {
-        Err(Error::syntax_error())
+        Err(Error::invalid_type(self.expecting(), Type::Bytes))
     }
 }
 
@@ -402,6 +579,15 @@ pub trait VariantVisitor {
 
     fn visit_value<V>(&mut self, _visitor: V) -> Result<V::Value, Self::Error>
         where V: Visitor;
+
+    /// `visit_newtype` deserializes the value of a variant that wraps a single value. By default
+    /// the variant is read like a tuple variant with one field.
+    #[inline]
+    fn visit_newtype<T>(&mut self) -> Result<T, Self::Error>
+        where T: Deserialize,
+    {
+        self.visit_value(NewtypeVariantVisitor { marker: PhantomData })
+    }
 }
 
 impl<'a, T> VariantVisitor for &'a mut T where T: VariantVisitor {
@@ -418,6 +604,37 @@ impl<'a, T> VariantVisitor for &'a mut T where T: VariantVisitor {
     {
         (**self).visit_value(visitor)
     }
+
+    fn visit_newtype<V>(&mut self) -> Result<V, T::Error>
+        where V: Deserialize,
+    {
+        (**self).visit_newtype()
+    }
+}
+
+/// Reads a newtype variant's value from a sequence of one element.
+struct NewtypeVariantVisitor<T> {
+    marker: PhantomData<T>,
+}
+
+impl<T> Visitor for NewtypeVariantVisitor<T> where T: Deserialize {
+    type Value = T;
+
+    fn expecting(&self) -> &'static str {
+        "newtype variant"
+    }
+
+    fn visit_seq<V>(&mut self, mut visitor: V) -> Result<T, V::Error>
+        where V: SeqVisitor,
+    {
+        let value = match try!(visitor.visit()) {
+            Some(value) => value,
+            None => { return Err(Error::invalid_length(0, "a sequence of length 1")); }
+        };
+
+        try!(visitor.end());
+        Ok(value)
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -437,3 +654,104 @@ pub trait EnumMapVisitor {
     fn visit<V>(&mut self, visitor: V) -> Result<Self::Value, V::Error>
         where V: MapVisitor;
 }
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// `DeserializeBorrowed` is a `Deserialize` that may borrow data, such as a `&'a str`, from the
+/// input of a `BorrowedDeserializer<'a>`. Every `Deserialize` type is also a
+/// `DeserializeBorrowed`.
+pub trait DeserializeBorrowed<'a>: Sized {
+    /// Deserialize this value given this `BorrowedDeserializer`.
+    fn deserialize_borrowed<D>(deserializer: &mut D) -> Result<Self, D::Error>
+        where D: BorrowedDeserializer<'a>;
+}
+
+impl<'a, T> DeserializeBorrowed<'a> for T where T: Deserialize {
+    #[inline]
+    fn deserialize_borrowed<D>(deserializer: &mut D) -> Result<T, D::Error>
+        where D: BorrowedDeserializer<'a>,
+    {
+        Deserialize::deserialize(deserializer)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// `BorrowedDeserializer` is a `Deserializer` reading from input that lives for `'a`, which lets
+/// it hand out slices of the input instead of copying them.
+pub trait BorrowedDeserializer<'a>: Deserializer {
+    /// The `visit_borrowed` method walks a visitor through a value, calling its `visit_borrowed_*`
+    /// methods for any data that could be borrowed from the input.
+    fn visit_borrowed<V>(&mut self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: BorrowedVisitor<'a>;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// `BorrowedVisitor` is a `Visitor` that can accept data borrowed from the input. By default the
+/// borrowed data is passed on to the matching `Visitor` method.
+pub trait BorrowedVisitor<'a>: Visitor {
+    /// `visit_borrowed_str` deserializes a `&str` that lives as long as the input.
+    #[inline]
+    fn visit_borrowed_str<E>(&mut self, v: &'a str) -> Result<Self::Value, E>
+        where E: Error,
+    {
+        self.visit_str(v)
+    }
+
+    /// `visit_borrowed_bytes` deserializes a `&[u8]` that lives as long as the input.
+    #[inline]
+    fn visit_borrowed_bytes<E>(&mut self, v: &'a [u8]) -> Result<Self::Value, E>
+        where E: Error,
+    {
+        self.visit_bytes(v)
+    }
+
+    /// `visit_borrowed_seq` deserializes a sequence whose elements may borrow from the input.
+    #[inline]
+    fn visit_borrowed_seq<V>(&mut self, visitor: V) -> Result<Self::Value, V::Error>
+        where V: BorrowedSeqVisitor<'a>,
+    {
+        self.visit_seq(visitor)
+    }
+
+    /// `visit_borrowed_map` deserializes a map whose values may borrow from the input.
+    #[inline]
+    fn visit_borrowed_map<V>(&mut self, visitor: V) -> Result<Self::Value, V::Error>
+        where V: BorrowedMapVisitor<'a>,
+    {
+        self.visit_map(visitor)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub trait BorrowedSeqVisitor<'a>: SeqVisitor {
+    fn visit_borrowed<T>(&mut self) -> Result<Option<T>, Self::Error>
+        where T: DeserializeBorrowed<'a>;
+}
+
+impl<'a, 'b, V> BorrowedSeqVisitor<'a> for &'b mut V where V: BorrowedSeqVisitor<'a> {
+    #[inline]
+    fn visit_borrowed<T>(&mut self) -> Result<Option<T>, V::Error>
+        where T: DeserializeBorrowed<'a>
+    {
+        (**self).visit_borrowed()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub trait BorrowedMapVisitor<'a>: MapVisitor {
+    fn visit_borrowed_value<V>(&mut self) -> Result<V, Self::Error>
+        where V: DeserializeBorrowed<'a>;
+}
+
+impl<'a, 'b, V_> BorrowedMapVisitor<'a> for &'b mut V_ where V_: BorrowedMapVisitor<'a> {
+    #[inline]
+    fn visit_borrowed_value<V>(&mut self) -> Result<V, V_::Error>
+        where V: DeserializeBorrowed<'a>
+    {
+        (**self).visit_borrowed_value()
+    }
+}
@@ -15,16 +15,39 @@ use de;
 
 ///////////////////////////////////////////////////////////////////////////////
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum Error {
     SyntaxError,
     EndOfStreamError,
     MissingFieldError(&'static str),
+    CustomError(String),
+    InvalidTypeError(&'static str, de::Type),
+    InvalidValueError(String),
+    InvalidLengthError(usize, String),
+    UnknownFieldError(String, &'static [&'static str]),
+    UnknownVariantError(String, &'static [&'static str]),
+    DuplicateFieldError(&'static str),
 }
 
 impl de::Error for Error {
     fn syntax_error() -> Self { Error::SyntaxError }
     fn end_of_stream_error() -> Self { Error::EndOfStreamError }
     fn missing_field_error(field: &'static str) -> Self { Error::MissingFieldError(field) }
+    fn custom<T: Into<String>>(msg: T) -> Self { Error::CustomError(msg.into()) }
+    fn invalid_type(expected: &'static str, found: de::Type) -> Self {
+        Error::InvalidTypeError(expected, found)
+    }
+    fn invalid_value(msg: &str) -> Self { Error::InvalidValueError(msg.to_string()) }
+    fn invalid_length(len: usize, expected: &str) -> Self {
+        Error::InvalidLengthError(len, expected.to_string())
+    }
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Error::UnknownFieldError(field.to_string(), expected)
+    }
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        Error::UnknownVariantError(variant.to_string(), expected)
+    }
+    fn duplicate_field(field: &'static str) -> Self { Error::DuplicateFieldError(field) }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -8,9 +8,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::collections::BTreeMap;
-
 use ser::{self, Serialize};
+use json::map::Map;
 use json::value::{self, Value};
 
 pub struct ArrayBuilder {
@@ -27,7 +26,7 @@ impl ArrayBuilder {
     }
 
     pub fn push<T: ser::Serialize>(mut self, v: T) -> ArrayBuilder {
-        self.array.push(value::to_value(&v));
+        self.array.push(value::to_value(&v).unwrap());
         self
     }
 
@@ -49,12 +48,12 @@ impl ArrayBuilder {
 }
 
 pub struct ObjectBuilder {
-    object: BTreeMap<String, Value>,
+    object: Map,
 }
 
 impl ObjectBuilder {
     pub fn new() -> ObjectBuilder {
-        ObjectBuilder { object: BTreeMap::new() }
+        ObjectBuilder { object: Map::new() }
     }
 
     pub fn unwrap(self) -> Value {
@@ -62,7 +61,7 @@ impl ObjectBuilder {
     }
 
     pub fn insert<V: ser::Serialize>(mut self, k: String, v: V) -> ObjectBuilder {
-        self.object.insert(k, value::to_value(&v));
+        self.object.insert(k, value::to_value(&v).unwrap());
         self
     }
 
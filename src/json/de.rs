@@ -1,102 +1,155 @@
 use std::char;
 use std::io;
+use std::marker::PhantomData;
+use std::mem;
 use std::str;
 
 use unicode::str::Utf16Item;
 
 use de;
-use super::error::{Error, ErrorCode};
+use super::error::{Error, ErrorCode, PathSegment};
+use super::read::{IoRead, Read, SliceRead};
 
-pub struct Deserializer<Iter> {
-    rdr: Iter,
+pub struct Deserializer<R> {
+    rdr: R,
+
+    /// The next byte of input, once it has been peeked at but not yet consumed. Bytes are only
+    /// read when the parser needs to look at them, so a complete value never waits on the input
+    /// that follows it.
     ch: Option<u8>,
     line: usize,
     col: usize,
+
+    /// The number of bytes consumed so far.
+    offset: usize,
     str_buf: Vec<u8>,
 }
 
-impl<Iter> Deserializer<Iter>
-    where Iter: Iterator<Item=io::Result<u8>>,
+impl<'a, R> Deserializer<R>
+    where R: Read<'a>,
 {
-    /// Creates the JSON parser from an `std::iter::Iterator`.
+    /// Creates the JSON parser from a `Read`, such as an `std::iter::Iterator` of bytes or a
+    /// `SliceRead`. Nothing is read from the input until a value is deserialized.
     #[inline]
-    pub fn new(rdr: Iter) -> Result<Deserializer<Iter>, Error> {
-        let mut deserializer = Deserializer {
+    pub fn new(rdr: R) -> Result<Deserializer<R>, Error> {
+        Ok(Deserializer {
             rdr: rdr,
             ch: None,
             line: 1,
             col: 0,
+            offset: 0,
             str_buf: Vec::with_capacity(128),
-        };
-
-        try!(deserializer.bump());
-
-        Ok(deserializer)
+        })
     }
 
     #[inline]
     pub fn end(&mut self) -> Result<(), Error> {
         try!(self.parse_whitespace());
-        if self.eof() {
+        if try!(self.eof()) {
             Ok(())
         } else {
             Err(self.error(ErrorCode::TrailingCharacters))
         }
     }
 
-    fn eof(&self) -> bool { self.ch.is_none() }
+    /// Returns the byte offset of the next character to be parsed, or the length of the input
+    /// once it has all been parsed.
+    #[inline]
+    pub fn byte_offset(&self) -> usize {
+        self.offset
+    }
 
-    fn ch_or_null(&self) -> u8 { self.ch.unwrap_or(b'\x00') }
+    /// Returns the next byte of input without consuming it, or `None` at the end of the input.
+    fn peek(&mut self) -> Result<Option<u8>, Error> {
+        if self.ch.is_none() {
+            self.ch = match self.rdr.next() {
+                Some(Err(err)) => { return Err(Error::IoError(err)); }
+                Some(Ok(ch)) => Some(ch),
+                None => None,
+            };
+        }
 
-    fn bump(&mut self) -> Result<(), Error> {
-        self.ch = match self.rdr.next() {
-            Some(Err(err)) => { return Err(Error::IoError(err)); }
-            Some(Ok(ch)) => Some(ch),
-            None => None,
-        };
+        Ok(self.ch)
+    }
 
-        if self.ch_is(b'\n') {
-            self.line += 1;
-            self.col = 1;
-        } else {
-            self.col += 1;
-        }
+    fn peek_or_null(&mut self) -> Result<u8, Error> {
+        Ok(try!(self.peek()).unwrap_or(b'\x00'))
+    }
 
-        Ok(())
+    fn peek_is(&mut self, c: u8) -> Result<bool, Error> {
+        Ok(try!(self.peek()) == Some(c))
+    }
+
+    fn eof(&mut self) -> Result<bool, Error> {
+        Ok(try!(self.peek()).is_none())
+    }
+
+    /// Consumes the byte returned by the last `peek`.
+    fn eat_char(&mut self) {
+        match self.ch.take() {
+            Some(b'\n') => {
+                self.line += 1;
+                self.col = 1;
+                self.offset += 1;
+            }
+            Some(_) => {
+                self.col += 1;
+                self.offset += 1;
+            }
+            None => { }
+        }
     }
 
+    /// Consumes and returns the next byte of input, or `None` at the end of the input.
     fn next_char(&mut self) -> Result<Option<u8>, Error> {
-        try!(self.bump());
-        Ok(self.ch)
+        let ch = try!(self.peek());
+        self.eat_char();
+        Ok(ch)
     }
 
-    fn ch_is(&self, c: u8) -> bool {
-        self.ch == Some(c)
+    /// Accounts for `len` bytes the reader consumed without returning them from `next`. They
+    /// must not contain any newlines.
+    #[inline]
+    fn skipped(&mut self, len: usize) {
+        self.col += len;
+        self.offset += len;
     }
 
+    /// Reports an error at the next byte of input, which is where parsing stopped.
     fn error(&mut self, reason: ErrorCode) -> Error {
-        Error::SyntaxError(reason, self.line, self.col)
+        if self.ch == Some(b'\n') {
+            Error::SyntaxError(reason, self.line + 1, 1)
+        } else {
+            Error::SyntaxError(reason, self.line, self.col + 1)
+        }
     }
 
     fn parse_whitespace(&mut self) -> Result<(), Error> {
-        while self.ch_is(b' ') ||
-              self.ch_is(b'\n') ||
-              self.ch_is(b'\t') ||
-              self.ch_is(b'\r') { try!(self.bump()); }
+        loop {
+            match try!(self.peek_or_null()) {
+                b' ' | b'\t' | b'\r' => {
+                    self.eat_char();
 
-        Ok(())
+                    // Let the reader skip the rest of the run at once if it can.
+                    let len = self.rdr.skip_spaces();
+                    self.skipped(len);
+                }
+                b'\n' => { self.eat_char(); }
+                _ => { return Ok(()); }
+            }
+        }
     }
 
     fn parse_value<V>(&mut self, mut visitor: V) -> Result<V::Value, Error>
-        where V: de::Visitor,
+        where V: de::BorrowedVisitor<'a>,
     {
         try!(self.parse_whitespace());
 
-        if self.eof() {
+        if try!(self.eof()) {
             return Err(self.error(ErrorCode::EOFWhileParsingValue));
         }
 
-        let value = match self.ch_or_null() {
+        let value = match try!(self.peek_or_null()) {
             b'n' => {
                 try!(self.parse_ident(b"ull"));
                 visitor.visit_unit()
@@ -111,38 +164,70 @@ impl<Iter> Deserializer<Iter>
             }
             b'0' ... b'9' | b'-' => self.parse_number(visitor),
             b'"' => {
-                try!(self.parse_string());
-                let s = str::from_utf8(&self.str_buf).unwrap();
-                visitor.visit_str(s)
+                match try!(self.parse_string()) {
+                    Some(s) => {
+                        match str::from_utf8(s) {
+                            Ok(s) => visitor.visit_borrowed_str(s),
+                            Err(_) => Err(self.error(ErrorCode::NotUtf8)),
+                        }
+                    }
+                    None => {
+                        match str::from_utf8(&self.str_buf) {
+                            Ok(s) => visitor.visit_str(s),
+                            // `str_buf` is still borrowed here, so `locate` fills in the
+                            // position below.
+                            Err(_) => Err(Error::SyntaxError(ErrorCode::NotUtf8, 0, 0)),
+                        }
+                    }
+                }
             }
             b'[' => {
-                try!(self.bump());
-                visitor.visit_seq(SeqVisitor::new(self))
+                self.eat_char();
+                visitor.visit_borrowed_seq(SeqVisitor::new(self))
             }
             b'{' => {
-                try!(self.bump());
-                visitor.visit_map(MapVisitor::new(self))
+                self.eat_char();
+                visitor.visit_borrowed_map(MapVisitor::new(self))
             }
             _ => {
                 Err(self.error(ErrorCode::ExpectedSomeValue))
             }
         };
 
-        match value {
+        self.locate(value)
+    }
+
+    /// Attach the current position to errors raised by a visitor.
+    fn locate<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        match result {
             Ok(value) => Ok(value),
             Err(Error::SyntaxError(code, _, _)) => Err(self.error(code)),
+            Err(Error::PathError(path, err)) => {
+                // Errors from buffered content are given a path before they reach us, but no
+                // position.
+                let err = match *err {
+                    Error::SyntaxError(code, 0, _) => self.error(code),
+                    err => err,
+                };
+                Err(Error::PathError(path, Box::new(err)))
+            }
             Err(err) => Err(err),
         }
     }
 
+    /// Consumes the first character of an identifier, which has already been peeked at, and
+    /// then the rest of it.
     fn parse_ident(&mut self, ident: &[u8]) -> Result<(), Error> {
+        self.eat_char();
+
         for c in ident {
-            if Some(*c) != try!(self.next_char()) {
+            if try!(self.peek()) != Some(*c) {
                 return Err(self.error(ErrorCode::ExpectedSomeIdent));
             }
+
+            self.eat_char();
         }
 
-        try!(self.bump());
         Ok(())
     }
 
@@ -151,21 +236,23 @@ impl<Iter> Deserializer<Iter>
     {
         let mut neg = false;
 
-        if self.ch_is(b'-') {
-            try!(self.bump());
+        if try!(self.peek_is(b'-')) {
+            self.eat_char();
             neg = true;
         }
 
         let res = try!(self.parse_integer());
 
-        if self.ch_is(b'.') || self.ch_is(b'e') || self.ch_is(b'E') {
+        let ch = try!(self.peek_or_null());
+        if ch == b'.' || ch == b'e' || ch == b'E' {
             let mut res = res as f64;
 
-            if self.ch_is(b'.') {
+            if try!(self.peek_is(b'.')) {
                 res = try!(self.parse_decimal(res));
             }
 
-            if self.ch_is(b'e') || self.ch_is(b'E') {
+            let ch = try!(self.peek_or_null());
+            if ch == b'e' || ch == b'E' {
                 res = try!(self.parse_exponent(res));
             }
 
@@ -193,12 +280,12 @@ impl<Iter> Deserializer<Iter>
     fn parse_integer(&mut self) -> Result<u64, Error> {
         let mut res = 0;
 
-        match self.ch_or_null() {
+        match try!(self.peek_or_null()) {
             b'0' => {
-                try!(self.bump());
+                self.eat_char();
 
                 // There can be only one leading '0'.
-                match self.ch_or_null() {
+                match try!(self.peek_or_null()) {
                     b'0' ... b'9' => {
                         return Err(self.error(ErrorCode::InvalidNumber));
                     }
@@ -206,12 +293,12 @@ impl<Iter> Deserializer<Iter>
                 }
             },
             b'1' ... b'9' => {
-                while !self.eof() {
-                    match self.ch_or_null() {
-                        c @ b'0' ... b'9' => {
+                loop {
+                    match try!(self.peek()) {
+                        Some(c @ b'0' ... b'9') => {
                             res *= 10;
                             res += (c as u64) - (b'0' as u64);
-                            try!(self.bump());
+                            self.eat_char();
                         }
                         _ => break,
                     }
@@ -224,22 +311,22 @@ impl<Iter> Deserializer<Iter>
     }
 
     fn parse_decimal(&mut self, res: f64) -> Result<f64, Error> {
-        try!(self.bump());
+        self.eat_char();
 
         // Make sure a digit follows the decimal place.
-        match self.ch_or_null() {
+        match try!(self.peek_or_null()) {
             b'0' ... b'9' => (),
              _ => { return Err(self.error(ErrorCode::InvalidNumber)); }
         }
 
         let mut res = res;
         let mut dec = 1.0;
-        while !self.eof() {
-            match self.ch_or_null() {
-                c @ b'0' ... b'9' => {
+        loop {
+            match try!(self.peek()) {
+                Some(c @ b'0' ... b'9') => {
                     dec /= 10.0;
                     res += (((c as u64) - (b'0' as u64)) as f64) * dec;
-                    try!(self.bump());
+                    self.eat_char();
                 }
                 _ => break,
             }
@@ -249,30 +336,30 @@ impl<Iter> Deserializer<Iter>
     }
 
     fn parse_exponent(&mut self, mut res: f64) -> Result<f64, Error> {
-        try!(self.bump());
+        self.eat_char();
 
         let mut exp = 0;
         let mut neg_exp = false;
 
-        if self.ch_is(b'+') {
-            try!(self.bump());
-        } else if self.ch_is(b'-') {
-            try!(self.bump());
+        if try!(self.peek_is(b'+')) {
+            self.eat_char();
+        } else if try!(self.peek_is(b'-')) {
+            self.eat_char();
             neg_exp = true;
         }
 
         // Make sure a digit follows the exponent place.
-        match self.ch_or_null() {
+        match try!(self.peek_or_null()) {
             b'0' ... b'9' => (),
             _ => { return Err(self.error(ErrorCode::InvalidNumber)); }
         }
-        while !self.eof() {
-            match self.ch_or_null() {
-                c @ b'0' ... b'9' => {
+        loop {
+            match try!(self.peek()) {
+                Some(c @ b'0' ... b'9') => {
                     exp *= 10;
                     exp += (c as i32) - (b'0' as i32);
 
-                    try!(self.bump());
+                    self.eat_char();
                 }
                 _ => break
             }
@@ -288,122 +375,143 @@ impl<Iter> Deserializer<Iter>
         Ok(res)
     }
 
+    /// Parses the four hex digits of a `\u` escape, whose `u` has been consumed.
     fn decode_hex_escape(&mut self) -> Result<u16, Error> {
-        let mut i = 0;
         let mut n = 0u16;
-        while i < 4 && !self.eof() {
-            try!(self.bump());
-            n = match self.ch_or_null() {
-                c @ b'0' ... b'9' => n * 16_u16 + ((c as u16) - (b'0' as u16)),
-                b'a' | b'A' => n * 16_u16 + 10_u16,
-                b'b' | b'B' => n * 16_u16 + 11_u16,
-                b'c' | b'C' => n * 16_u16 + 12_u16,
-                b'd' | b'D' => n * 16_u16 + 13_u16,
-                b'e' | b'E' => n * 16_u16 + 14_u16,
-                b'f' | b'F' => n * 16_u16 + 15_u16,
+
+        for _ in 0..4 {
+            n = match try!(self.peek()) {
+                Some(c @ b'0' ... b'9') => n * 16_u16 + ((c as u16) - (b'0' as u16)),
+                Some(b'a') | Some(b'A') => n * 16_u16 + 10_u16,
+                Some(b'b') | Some(b'B') => n * 16_u16 + 11_u16,
+                Some(b'c') | Some(b'C') => n * 16_u16 + 12_u16,
+                Some(b'd') | Some(b'D') => n * 16_u16 + 13_u16,
+                Some(b'e') | Some(b'E') => n * 16_u16 + 14_u16,
+                Some(b'f') | Some(b'F') => n * 16_u16 + 15_u16,
                 _ => { return Err(self.error(ErrorCode::InvalidEscape)); }
             };
 
-            i += 1;
-        }
-
-        // Error out if we didn't parse 4 digits.
-        if i != 4 {
-            return Err(self.error(ErrorCode::InvalidEscape));
+            self.eat_char();
         }
 
         Ok(n)
     }
 
-    fn parse_string(&mut self) -> Result<(), Error> {
+    /// Parses a string whose opening quote is the next character. The string is returned if it
+    /// could be borrowed from the input, and is otherwise left in `str_buf`, which is empty
+    /// after a borrowed string. Nothing after the closing quote is read.
+    fn parse_string(&mut self) -> Result<Option<&'a [u8]>, Error> {
+        self.eat_char();
         self.str_buf.clear();
 
-        let mut escape = false;
+        if let Some(s) = self.rdr.borrow_str() {
+            // Account for the string and its closing quote. Borrowed strings never contain
+            // newlines.
+            self.skipped(s.len() + 1);
+            return Ok(Some(s));
+        }
 
         loop {
-            let ch = match try!(self.next_char()) {
+            // Copy any plain bytes in bulk.
+            let len = self.rdr.read_str_chunk(&mut self.str_buf);
+            self.skipped(len);
+
+            let ch = match try!(self.peek()) {
                 Some(ch) => ch,
                 None => { return Err(self.error(ErrorCode::EOFWhileParsingString)); }
             };
 
-            if escape {
-                match ch {
-                    b'"' => self.str_buf.push(b'"'),
-                    b'\\' => self.str_buf.push(b'\\'),
-                    b'/' => self.str_buf.push(b'/'),
-                    b'b' => self.str_buf.push(b'\x08'),
-                    b'f' => self.str_buf.push(b'\x0c'),
-                    b'n' => self.str_buf.push(b'\n'),
-                    b'r' => self.str_buf.push(b'\r'),
-                    b't' => self.str_buf.push(b'\t'),
-                    b'u' => {
-                        let c = match try!(self.decode_hex_escape()) {
-                            0xDC00 ... 0xDFFF => {
-                                return Err(self.error(ErrorCode::LoneLeadingSurrogateInHexEscape));
-                            }
+            match ch {
+                b'"' => {
+                    self.eat_char();
+                    return Ok(None);
+                }
+                b'\\' => {
+                    self.eat_char();
+                    try!(self.parse_escape());
+                }
+                ch => {
+                    self.eat_char();
+                    self.str_buf.push(ch);
+                }
+            }
+        }
+    }
 
-                            // Non-BMP characters are encoded as a sequence of
-                            // two hex escapes, representing UTF-16 surrogates.
-                            n1 @ 0xD800 ... 0xDBFF => {
-                                let c1 = try!(self.next_char());
-                                let c2 = try!(self.next_char());
-                                match (c1, c2) {
-                                    (Some(b'\\'), Some(b'u')) => (),
-                                    _ => {
-                                        return Err(self.error(ErrorCode::UnexpectedEndOfHexEscape));
-                                    }
-                                }
-
-                                let buf = &[n1, try!(self.decode_hex_escape())];
-                                match ::unicode::str::utf16_items(buf).next() {
-                                    Some(Utf16Item::ScalarValue(c)) => c,
-                                    _ => {
-                                        return Err(self.error(ErrorCode::LoneLeadingSurrogateInHexEscape));
-                                    }
-                                }
-                            }
+    /// Parses the escape sequence after a backslash into `str_buf`.
+    fn parse_escape(&mut self) -> Result<(), Error> {
+        let ch = match try!(self.peek()) {
+            Some(ch) => ch,
+            None => { return Err(self.error(ErrorCode::EOFWhileParsingString)); }
+        };
 
-                            n => match char::from_u32(n as u32) {
-                                Some(c) => c,
-                                None => {
-                                    return Err(self.error(ErrorCode::InvalidUnicodeCodePoint));
-                                }
+        match ch {
+            b'"' => self.str_buf.push(b'"'),
+            b'\\' => self.str_buf.push(b'\\'),
+            b'/' => self.str_buf.push(b'/'),
+            b'b' => self.str_buf.push(b'\x08'),
+            b'f' => self.str_buf.push(b'\x0c'),
+            b'n' => self.str_buf.push(b'\n'),
+            b'r' => self.str_buf.push(b'\r'),
+            b't' => self.str_buf.push(b'\t'),
+            b'u' => {
+                self.eat_char();
+
+                let c = match try!(self.decode_hex_escape()) {
+                    0xDC00 ... 0xDFFF => {
+                        return Err(self.error(ErrorCode::LoneLeadingSurrogateInHexEscape));
+                    }
+
+                    // Non-BMP characters are encoded as a sequence of
+                    // two hex escapes, representing UTF-16 surrogates.
+                    n1 @ 0xD800 ... 0xDBFF => {
+                        let c1 = try!(self.next_char());
+                        let c2 = try!(self.next_char());
+                        match (c1, c2) {
+                            (Some(b'\\'), Some(b'u')) => (),
+                            _ => {
+                                return Err(self.error(ErrorCode::UnexpectedEndOfHexEscape));
                             }
-                        };
+                        }
 
-                        let buf = &mut [0; 4];
-                        let len = c.encode_utf8(buf).unwrap_or(0);
-                        self.str_buf.extend(buf[..len].iter().map(|b| *b));
-                    }
-                    _ => {
-                        return Err(self.error(ErrorCode::InvalidEscape));
-                    }
-                }
-                escape = false;
-            } else {
-                match ch {
-                    b'"' => {
-                        try!(self.bump());
-                        return Ok(());
-                    }
-                    b'\\' => {
-                        escape = true;
+                        let buf = &[n1, try!(self.decode_hex_escape())];
+                        match ::unicode::str::utf16_items(buf).next() {
+                            Some(Utf16Item::ScalarValue(c)) => c,
+                            _ => {
+                                return Err(self.error(ErrorCode::LoneLeadingSurrogateInHexEscape));
+                            }
+                        }
                     }
-                    ch => {
-                        self.str_buf.push(ch);
+
+                    n => match char::from_u32(n as u32) {
+                        Some(c) => c,
+                        None => {
+                            return Err(self.error(ErrorCode::InvalidUnicodeCodePoint));
+                        }
                     }
-                }
+                };
+
+                let buf = &mut [0; 4];
+                let len = c.encode_utf8(buf).unwrap_or(0);
+                self.str_buf.extend(buf[..len].iter().map(|b| *b));
+                return Ok(());
+            }
+            _ => {
+                return Err(self.error(ErrorCode::InvalidEscape));
             }
         }
+
+        self.eat_char();
+        Ok(())
     }
 
     fn parse_object_colon(&mut self) -> Result<(), Error> {
         try!(self.parse_whitespace());
 
-        if self.ch_is(b':') {
-            try!(self.bump());
+        if try!(self.peek_is(b':')) {
+            self.eat_char();
             Ok(())
-        } else if self.eof() {
+        } else if try!(self.eof()) {
             Err(self.error(ErrorCode::EOFWhileParsingObject))
         } else {
             Err(self.error(ErrorCode::ExpectedColon))
@@ -411,8 +519,8 @@ impl<Iter> Deserializer<Iter>
     }
 }
 
-impl<Iter> de::Deserializer for Deserializer<Iter>
-    where Iter: Iterator<Item=io::Result<u8>>,
+impl<'a, R> de::Deserializer for Deserializer<R>
+    where R: Read<'a>,
 {
     type Error = Error;
 
@@ -420,7 +528,7 @@ impl<Iter> de::Deserializer for Deserializer<Iter>
     fn visit<V>(&mut self, visitor: V) -> Result<V::Value, Error>
         where V: de::Visitor,
     {
-        self.parse_value(visitor)
+        self.parse_value(OwnedVisitor(visitor))
     }
 
     #[inline]
@@ -429,11 +537,11 @@ impl<Iter> de::Deserializer for Deserializer<Iter>
     {
         try!(self.parse_whitespace());
 
-        if self.eof() {
+        if try!(self.eof()) {
             return Err(self.error(ErrorCode::EOFWhileParsingValue));
         }
 
-        if self.ch_is(b'n') {
+        if try!(self.peek_is(b'n')) {
             try!(self.parse_ident(b"ull"));
             visitor.visit_none()
         } else {
@@ -447,18 +555,19 @@ impl<Iter> de::Deserializer for Deserializer<Iter>
     {
         try!(self.parse_whitespace());
 
-        if self.ch_is(b'{') {
-            try!(self.bump());
+        if try!(self.peek_is(b'{')) {
+            self.eat_char();
             try!(self.parse_whitespace());
 
             let value = {
-                try!(visitor.visit(&mut *self))
+                let value = visitor.visit(&mut *self);
+                try!(self.locate(value))
             };
 
             try!(self.parse_whitespace());
 
-            if self.ch_is(b'}') {
-                try!(self.bump());
+            if try!(self.peek_is(b'}')) {
+                self.eat_char();
                 Ok(value)
             } else {
                 Err(self.error(ErrorCode::ExpectedSomeValue))
@@ -469,56 +578,165 @@ impl<Iter> de::Deserializer for Deserializer<Iter>
     }
 }
 
-struct SeqVisitor<'a, Iter: 'a> {
-    de: &'a mut Deserializer<Iter>,
+impl<'a, R> de::BorrowedDeserializer<'a> for Deserializer<R>
+    where R: Read<'a>,
+{
+    #[inline]
+    fn visit_borrowed<V>(&mut self, visitor: V) -> Result<V::Value, Error>
+        where V: de::BorrowedVisitor<'a>,
+    {
+        self.parse_value(visitor)
+    }
+}
+
+/// `OwnedVisitor` lets a plain `Visitor` be driven by `parse_value`, receiving borrowed strings
+/// through its `visit_str` method. Only the methods `parse_value` calls need forwarding.
+struct OwnedVisitor<V>(V);
+
+impl<V> de::Visitor for OwnedVisitor<V>
+    where V: de::Visitor,
+{
+    type Value = V::Value;
+
+    #[inline]
+    fn expecting(&self) -> &'static str {
+        self.0.expecting()
+    }
+
+    #[inline]
+    fn visit_bool<E>(&mut self, v: bool) -> Result<V::Value, E>
+        where E: de::Error,
+    {
+        self.0.visit_bool(v)
+    }
+
+    #[inline]
+    fn visit_i64<E>(&mut self, v: i64) -> Result<V::Value, E>
+        where E: de::Error,
+    {
+        self.0.visit_i64(v)
+    }
+
+    #[inline]
+    fn visit_u64<E>(&mut self, v: u64) -> Result<V::Value, E>
+        where E: de::Error,
+    {
+        self.0.visit_u64(v)
+    }
+
+    #[inline]
+    fn visit_f64<E>(&mut self, v: f64) -> Result<V::Value, E>
+        where E: de::Error,
+    {
+        self.0.visit_f64(v)
+    }
+
+    #[inline]
+    fn visit_str<E>(&mut self, v: &str) -> Result<V::Value, E>
+        where E: de::Error,
+    {
+        self.0.visit_str(v)
+    }
+
+    #[inline]
+    fn visit_unit<E>(&mut self) -> Result<V::Value, E>
+        where E: de::Error,
+    {
+        self.0.visit_unit()
+    }
+
+    #[inline]
+    fn visit_seq<V_>(&mut self, visitor: V_) -> Result<V::Value, V_::Error>
+        where V_: de::SeqVisitor,
+    {
+        self.0.visit_seq(visitor)
+    }
+
+    #[inline]
+    fn visit_map<V_>(&mut self, visitor: V_) -> Result<V::Value, V_::Error>
+        where V_: de::MapVisitor,
+    {
+        self.0.visit_map(visitor)
+    }
+}
+
+impl<'a, V> de::BorrowedVisitor<'a> for OwnedVisitor<V> where V: de::Visitor { }
+
+struct SeqVisitor<'b, R: 'b> {
+    de: &'b mut Deserializer<R>,
     first: bool,
+    index: usize,
 }
 
-impl<'a, Iter> SeqVisitor<'a, Iter> {
-    fn new(de: &'a mut Deserializer<Iter>) -> Self {
+impl<'a, 'b, R> SeqVisitor<'b, R>
+    where R: Read<'a>,
+{
+    fn new(de: &'b mut Deserializer<R>) -> Self {
         SeqVisitor {
             de: de,
             first: true,
+            index: 0,
         }
     }
-}
-
-impl<'a, Iter> de::SeqVisitor for SeqVisitor<'a, Iter>
-    where Iter: Iterator<Item=io::Result<u8>>,
-{
-    type Error = Error;
 
-    fn visit<T>(&mut self) -> Result<Option<T>, Error>
-        where T: de::Deserialize,
-    {
+    /// Moves past the separator before the next element, returning `false` at the end of the
+    /// array.
+    fn has_next(&mut self) -> Result<bool, Error> {
         try!(self.de.parse_whitespace());
 
-        if self.de.ch_is(b']') {
-            return Ok(None);
+        if try!(self.de.peek_is(b']')) {
+            return Ok(false);
         }
 
         if self.first {
             self.first = false;
         } else {
-            if self.de.ch_is(b',') {
-                try!(self.de.bump());
-            } else if self.de.eof() {
+            if try!(self.de.peek_is(b',')) {
+                self.de.eat_char();
+            } else if try!(self.de.eof()) {
                 return Err(self.de.error(ErrorCode::EOFWhileParsingList));
             } else {
                 return Err(self.de.error(ErrorCode::ExpectedListCommaOrEnd));
             }
         }
 
-        let value = try!(de::Deserialize::deserialize(self.de));
-        Ok(Some(value))
+        Ok(true)
+    }
+
+    fn element<T>(&mut self, value: Result<T, Error>) -> Result<Option<T>, Error> {
+        match value {
+            Ok(value) => {
+                self.index += 1;
+                Ok(Some(value))
+            }
+            Err(err) => Err(err.at(PathSegment::Index(self.index))),
+        }
+    }
+}
+
+impl<'a, 'b, R> de::SeqVisitor for SeqVisitor<'b, R>
+    where R: Read<'a>,
+{
+    type Error = Error;
+
+    fn visit<T>(&mut self) -> Result<Option<T>, Error>
+        where T: de::Deserialize,
+    {
+        if !try!(self.has_next()) {
+            return Ok(None);
+        }
+
+        let value = de::Deserialize::deserialize(self.de);
+        self.element(value)
     }
 
     fn end(&mut self) -> Result<(), Error> {
         try!(self.de.parse_whitespace());
 
-        if self.de.ch_is(b']') {
-            self.de.bump()
-        } else if self.de.eof() {
+        if try!(self.de.peek_is(b']')) {
+            self.de.eat_char();
+            Ok(())
+        } else if try!(self.de.eof()) {
             Err(self.de.error(ErrorCode::EOFWhileParsingList))
         } else {
             Err(self.de.error(ErrorCode::TrailingCharacters))
@@ -526,22 +744,71 @@ impl<'a, Iter> de::SeqVisitor for SeqVisitor<'a, Iter>
     }
 }
 
-struct MapVisitor<'a, Iter: 'a> {
-    de: &'a mut Deserializer<Iter>,
+impl<'a, 'b, R> de::BorrowedSeqVisitor<'a> for SeqVisitor<'b, R>
+    where R: Read<'a>,
+{
+    fn visit_borrowed<T>(&mut self) -> Result<Option<T>, Error>
+        where T: de::DeserializeBorrowed<'a>,
+    {
+        if !try!(self.has_next()) {
+            return Ok(None);
+        }
+
+        let value = de::DeserializeBorrowed::deserialize_borrowed(self.de);
+        self.element(value)
+    }
+}
+
+struct MapVisitor<'b, R: 'b> {
+    de: &'b mut Deserializer<R>,
     first: bool,
+
+    /// Where the last key read lies in the input, and the key itself if it had to be unescaped.
+    /// These are only turned into a string when its value fails, so the error can report where
+    /// it happened.
+    key_start: usize,
+    key_end: usize,
+    key_buf: Vec<u8>,
 }
 
-impl<'a, Iter> MapVisitor<'a, Iter> {
-    fn new(de: &'a mut Deserializer<Iter>) -> Self {
+impl<'a, 'b, R> MapVisitor<'b, R>
+    where R: Read<'a>,
+{
+    fn new(de: &'b mut Deserializer<R>) -> Self {
         MapVisitor {
             de: de,
             first: true,
+            key_start: 0,
+            key_end: 0,
+            key_buf: Vec::new(),
+        }
+    }
+
+    /// Returns the last key read. Keys that were borrowed rather than unescaped are sliced back
+    /// out of the input.
+    fn key(&self) -> String {
+        let key: &[u8] = if self.key_buf.is_empty() {
+            match self.de.rdr.slice(self.key_start, self.key_end) {
+                Some(key) => key,
+                None => &[],
+            }
+        } else {
+            &self.key_buf[..]
+        };
+
+        String::from_utf8_lossy(key).into_owned()
+    }
+
+    fn field<V>(&mut self, value: Result<V, Error>) -> Result<V, Error> {
+        match value {
+            Ok(value) => Ok(value),
+            Err(err) => Err(err.at(PathSegment::Key(self.key()))),
         }
     }
 }
 
-impl<'a, Iter> de::MapVisitor for MapVisitor<'a, Iter>
-    where Iter: Iterator<Item=io::Result<u8>>
+impl<'a, 'b, R> de::MapVisitor for MapVisitor<'b, R>
+    where R: Read<'a>,
 {
     type Error = Error;
 
@@ -550,32 +817,42 @@ impl<'a, Iter> de::MapVisitor for MapVisitor<'a, Iter>
     {
         try!(self.de.parse_whitespace());
 
-        if self.de.ch_is(b'}') {
+        if try!(self.de.peek_is(b'}')) {
             return Ok(None);
         }
 
         if self.first {
             self.first = false;
         } else {
-            if self.de.ch_is(b',') {
-                try!(self.de.bump());
+            if try!(self.de.peek_is(b',')) {
+                self.de.eat_char();
                 try!(self.de.parse_whitespace());
-            } else if self.de.eof() {
+            } else if try!(self.de.eof()) {
                 return Err(self.de.error(ErrorCode::EOFWhileParsingObject));
             } else {
                 return Err(self.de.error(ErrorCode::ExpectedObjectCommaOrEnd));
             }
         }
 
-        if self.de.eof() {
+        if try!(self.de.eof()) {
             return Err(self.de.error(ErrorCode::EOFWhileParsingValue));
         }
 
-        if !self.de.ch_is(b'"') {
+        if !try!(self.de.peek_is(b'"')) {
             return Err(self.de.error(ErrorCode::KeyMustBeAString));
         }
 
-        Ok(Some(try!(de::Deserialize::deserialize(self.de))))
+        let start = self.de.byte_offset();
+        let key = try!(de::Deserialize::deserialize(self.de));
+
+        // Remember where the key was, without its quotes. If it had to be unescaped, move it out
+        // of the string buffer before its value overwrites it.
+        self.key_start = start + 1;
+        self.key_end = self.de.byte_offset() - 1;
+        self.key_buf.clear();
+        mem::swap(&mut self.key_buf, &mut self.de.str_buf);
+
+        Ok(Some(key))
     }
 
     fn visit_value<V>(&mut self) -> Result<V, Error>
@@ -583,32 +860,50 @@ impl<'a, Iter> de::MapVisitor for MapVisitor<'a, Iter>
     {
         try!(self.de.parse_object_colon());
 
-        Ok(try!(de::Deserialize::deserialize(self.de)))
+        let value = de::Deserialize::deserialize(self.de);
+        self.field(value)
     }
 
     fn end(&mut self) -> Result<(), Error> {
         try!(self.de.parse_whitespace());
 
-        if self.de.ch_is(b'}') {
-            try!(self.de.bump());
+        if try!(self.de.peek_is(b'}')) {
+            self.de.eat_char();
             Ok(())
-        } else if self.de.eof() {
+        } else if try!(self.de.eof()) {
             Err(self.de.error(ErrorCode::EOFWhileParsingObject))
         } else {
             Err(self.de.error(ErrorCode::TrailingCharacters))
         }
     }
 
-    fn missing_field<V>(&mut self, _field: &'static str) -> Result<V, Error>
+    fn missing_field<V>(&mut self, field: &'static str) -> Result<V, Error>
         where V: de::Deserialize,
     {
+        // Types like `Option` can still be created from a missing field.
         let mut de = de::value::ValueDeserializer::into_deserializer(());
-        Ok(try!(de::Deserialize::deserialize(&mut de)))
+        match de::Deserialize::deserialize(&mut de) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(self.de.error(ErrorCode::MissingField(field))),
+        }
     }
 }
 
-impl<Iter> de::VariantVisitor for Deserializer<Iter>
-    where Iter: Iterator<Item=io::Result<u8>>,
+impl<'a, 'b, R> de::BorrowedMapVisitor<'a> for MapVisitor<'b, R>
+    where R: Read<'a>,
+{
+    fn visit_borrowed_value<V>(&mut self) -> Result<V, Error>
+        where V: de::DeserializeBorrowed<'a>,
+    {
+        try!(self.de.parse_object_colon());
+
+        let value = de::DeserializeBorrowed::deserialize_borrowed(self.de);
+        self.field(value)
+    }
+}
+
+impl<'a, R> de::VariantVisitor for Deserializer<R>
+    where R: Read<'a>,
 {
     type Error = Error;
 
@@ -625,39 +920,124 @@ impl<Iter> de::VariantVisitor for Deserializer<Iter>
 
         de::Deserializer::visit(self, visitor)
     }
+
+    fn visit_newtype<T>(&mut self) -> Result<T, Error>
+        where T: de::Deserialize,
+    {
+        try!(self.parse_object_colon());
+
+        de::Deserialize::deserialize(self)
+    }
 }
 
-/// Decodes a json value from a `std::io::Read`.
-pub fn from_iter<I, T>(iter: I) -> Result<T, Error>
-    where I: Iterator<Item=io::Result<u8>>,
-          T: de::Deserialize,
+//////////////////////////////////////////////////////////////////////////////
+
+/// `StreamDeserializer` iterates over a stream of JSON values that follow one another, optionally
+/// separated by whitespace, such as newline-delimited JSON.
+///
+/// Each value is returned as soon as its last byte has been read, without waiting for the input
+/// that follows, so it can be used on a socket. The exception is a bare number, whose end can
+/// only be told from the byte after it.
+///
+/// Iteration stops after the first error, since the rest of the stream can no longer be trusted
+/// to start at a value.
+pub struct StreamDeserializer<T, R> {
+    de: Deserializer<R>,
+    offset: usize,
+    failed: bool,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T, R> StreamDeserializer<T, R>
+    where R: Read<'a>,
+          T: de::DeserializeBorrowed<'a>,
 {
-    let mut de = try!(Deserializer::new(iter));
-    let value = try!(de::Deserialize::deserialize(&mut de));
+    /// Creates a JSON stream parser from a `Read`.
+    #[inline]
+    pub fn new(rdr: R) -> Result<StreamDeserializer<T, R>, Error> {
+        Ok(StreamDeserializer {
+            de: try!(Deserializer::new(rdr)),
+            offset: 0,
+            failed: false,
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the byte offset where the value last returned by `next` started.
+    #[inline]
+    pub fn value_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a, T, R> Iterator for StreamDeserializer<T, R>
+    where R: Read<'a>,
+          T: de::DeserializeBorrowed<'a>,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        if self.failed {
+            return None;
+        }
+
+        let value = match self.de.parse_whitespace().and_then(|()| self.de.eof()) {
+            Ok(true) => { return None; }
+            Ok(false) => {
+                self.offset = self.de.byte_offset();
+                de::DeserializeBorrowed::deserialize_borrowed(&mut self.de)
+            }
+            Err(err) => Err(err),
+        };
+
+        if value.is_err() {
+            self.failed = true;
+        }
+
+        Some(value)
+    }
+}
+
+fn from_read<'a, R, T>(rdr: R) -> Result<T, Error>
+    where R: Read<'a>,
+          T: de::DeserializeBorrowed<'a>,
+{
+    let mut de = try!(Deserializer::new(rdr));
+    let value = try!(de::DeserializeBorrowed::deserialize_borrowed(&mut de));
 
     // Make sure the whole stream has been consumed.
     try!(de.end());
     Ok(value)
 }
 
+/// Decodes a json value from a `std::io::Read`.
+pub fn from_iter<I, T>(iter: I) -> Result<T, Error>
+    where I: Iterator<Item=io::Result<u8>>,
+          T: de::Deserialize,
+{
+    from_read(iter)
+}
+
 /// Decodes a json value from a `std::io::Read`.
 pub fn from_reader<R, T>(rdr: R) -> Result<T, Error>
     where R: io::Read,
           T: de::Deserialize,
 {
-    from_iter(rdr.bytes())
+    from_read(IoRead::new(rdr))
 }
 
-/// Decodes a json value from a `&str`.
-pub fn from_slice<T>(v: &[u8]) -> Result<T, Error>
-    where T: de::Deserialize
+/// Decodes a json value from a `&[u8]`. Strings without escapes are borrowed from the input
+/// when `T` asks for them, as with `&'a str` and `&'a [u8]`.
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T, Error>
+    where T: de::DeserializeBorrowed<'a>
 {
-    from_iter(v.iter().map(|byte| Ok(*byte)))
+    from_read(SliceRead::new(v))
 }
 
-/// Decodes a json value from a `&str`.
-pub fn from_str<T>(s: &str) -> Result<T, Error>
-    where T: de::Deserialize
+/// Decodes a json value from a `&str`. Strings without escapes are borrowed from the input
+/// when `T` asks for them, as with `&'a str` and `&'a [u8]`.
+pub fn from_str<'a, T>(s: &'a str) -> Result<T, Error>
+    where T: de::DeserializeBorrowed<'a>
 {
     from_slice(s.as_bytes())
 }
@@ -3,10 +3,33 @@ use std::fmt;
 use std::io;
 
 use de;
+use ser;
 
 /// The errors that can arise while parsing a JSON stream.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum ErrorCode {
+    /// A type-specific error raised through `de::Error::custom` or `ser::Error::custom`.
+    Custom(String),
+
+    /// The visitor expected the first type, but the JSON contained the second.
+    InvalidType(&'static str, de::Type),
+
+    /// The value had the right type but could not be accepted.
+    InvalidValue(String),
+
+    /// A sequence had a length the visitor did not expect. The second field describes the
+    /// length it expected.
+    InvalidLength(usize, String),
+
+    /// An object contained a field that is not one of the expected fields.
+    UnknownField(String, &'static [&'static str]),
+
+    /// An enum variant name is not one of the expected variants.
+    UnknownVariant(String, &'static [&'static str]),
+
+    /// An object contained the same field more than once.
+    DuplicateField(&'static str),
+
     EOFWhileParsingList,
     EOFWhileParsingObject,
     EOFWhileParsingString,
@@ -33,7 +56,6 @@ pub enum ErrorCode {
     NotUtf8,
     TrailingCharacters,
     UnexpectedEndOfHexEscape,
-    UnknownVariant,
     UnrecognizedHex,
 }
 
@@ -43,6 +65,33 @@ impl fmt::Debug for ErrorCode {
 
         match *self {
             //ErrorCode::ConversionError(ref token) => write!(f, "failed to convert {}", token),
+            ErrorCode::Custom(ref msg) => f.write_str(msg),
+            ErrorCode::InvalidType(expected, found) => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ErrorCode::InvalidValue(ref msg) => write!(f, "invalid value: {}", msg),
+            ErrorCode::InvalidLength(len, ref expected) => {
+                write!(f, "invalid length {}, expected {}", len, expected)
+            }
+            ErrorCode::UnknownField(ref field, expected) => {
+                if expected.is_empty() {
+                    write!(f, "unknown field \"{}\", there are no fields", field)
+                } else {
+                    write!(f, "unknown field \"{}\", expected one of \"{}\"",
+                           field,
+                           expected.connect("\", \""))
+                }
+            }
+            ErrorCode::UnknownVariant(ref variant, expected) => {
+                if expected.is_empty() {
+                    write!(f, "unknown variant \"{}\", there are no variants", variant)
+                } else {
+                    write!(f, "unknown variant \"{}\", expected one of \"{}\"",
+                           variant,
+                           expected.connect("\", \""))
+                }
+            }
+            ErrorCode::DuplicateField(field) => write!(f, "duplicate field \"{}\"", field),
             ErrorCode::EOFWhileParsingList => "EOF While parsing list".fmt(f),
             ErrorCode::EOFWhileParsingObject => "EOF While parsing object".fmt(f),
             ErrorCode::EOFWhileParsingString => "EOF While parsing string".fmt(f),
@@ -71,7 +120,6 @@ impl fmt::Debug for ErrorCode {
             ErrorCode::TrailingCharacters => "trailing characters".fmt(f),
             ErrorCode::UnexpectedEndOfHexEscape => "unexpected end of hex escape".fmt(f),
             //ErrorCode::UnexpectedName(ref name) => write!(f, "unexpected name {}", name),
-            ErrorCode::UnknownVariant => "unknown variant".fmt(f),
             ErrorCode::UnrecognizedHex => "invalid \\u escape (unrecognized hex)".fmt(f),
         }
     }
@@ -82,10 +130,13 @@ pub enum Error {
     /// msg, line, col
     SyntaxError(ErrorCode, usize, usize),
     IoError(io::Error),
+    /// Raised when a value cannot be represented as JSON.
+    SerializeError(ErrorCode),
+    /// An error raised while deserializing the value found at `path`.
+    PathError(Path, Box<Error>),
     /*
     ExpectedError(String, String),
     */
-    MissingFieldError(&'static str),
     /*
     UnknownVariantError(String),
     */
@@ -96,10 +147,11 @@ impl error::Error for Error {
         match *self {
             Error::SyntaxError(..) => "syntax error",
             Error::IoError(ref error) => error::Error::description(error),
+            Error::SerializeError(..) => "serialize error",
+            Error::PathError(_, ref error) => error::Error::description(&**error),
             /*
             Error::ExpectedError(ref expected, _) => &expected,
             */
-            Error::MissingFieldError(_) => "missing field",
             /*
             Error::UnknownVariantError(_) => "unknown variant",
             */
@@ -109,6 +161,7 @@ impl error::Error for Error {
     fn cause(&self) -> Option<&error::Error> {
         match *self {
             Error::IoError(ref error) => Some(error),
+            Error::PathError(_, ref error) => error::Error::cause(&**error),
             _ => None,
         }
     }
@@ -122,14 +175,13 @@ impl fmt::Display for Error {
                 write!(fmt, "{:?} at line {} column {}", code, line, col)
             }
             Error::IoError(ref error) => fmt::Display::fmt(error, fmt),
+            Error::SerializeError(ref code) => write!(fmt, "{:?}", code),
+            Error::PathError(ref path, ref error) => write!(fmt, "{}: {}", path, error),
             /*
             Error::ExpectedError(ref expected, ref found) => {
                 Some(format!("expected {}, found {}", expected, found))
             }
             */
-            Error::MissingFieldError(ref field) => {
-                write!(fmt, "missing field {}", field)
-            }
             /*
             Error::UnknownVariantError(ref variant) => {
                 Some(format!("unknown variant {}", variant))
@@ -139,6 +191,104 @@ impl fmt::Display for Error {
     }
 }
 
+impl Error {
+    /// Returns the path to the value that failed to deserialize, if it is nested inside an array
+    /// or object. Paths are recorded through `de::Error::at_key` and `de::Error::at_index`, so
+    /// they also cover values replayed from buffered content, such as the fields of an
+    /// internally tagged enum.
+    pub fn path(&self) -> Option<&Path> {
+        match *self {
+            Error::PathError(ref path, _) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Records that this error was raised while deserializing the element or field `segment`.
+    /// Segments are added from the innermost value outwards. Malformed JSON and I/O errors are
+    /// returned unchanged, since the line and column already pinpoint them.
+    pub fn at(self, segment: PathSegment) -> Error {
+        match self {
+            Error::PathError(mut path, error) => {
+                path.segments.insert(0, segment);
+                Error::PathError(path, error)
+            }
+            Error::SyntaxError(code, line, col) => {
+                if is_data_error(&code) {
+                    let error = Error::SyntaxError(code, line, col);
+                    Error::PathError(Path { segments: vec![segment] }, Box::new(error))
+                } else {
+                    Error::SyntaxError(code, line, col)
+                }
+            }
+            error => error,
+        }
+    }
+}
+
+/// Whether `code` describes well-formed JSON that did not match the type being deserialized.
+fn is_data_error(code: &ErrorCode) -> bool {
+    match *code {
+        ErrorCode::Custom(_) |
+        ErrorCode::InvalidType(..) |
+        ErrorCode::InvalidValue(_) |
+        ErrorCode::InvalidLength(..) |
+        ErrorCode::UnknownField(..) |
+        ErrorCode::UnknownVariant(..) |
+        ErrorCode::DuplicateField(_) |
+        ErrorCode::ExpectedConversion |
+        ErrorCode::MissingField(_) => true,
+        _ => false,
+    }
+}
+
+/// The location of a value inside a JSON document, such as `.servers[3].tls.cert_path`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Creates a path from its segments, outermost first.
+    pub fn new(segments: Vec<PathSegment>) -> Path {
+        Path { segments: segments }
+    }
+
+    /// The segments of the path, outermost first.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for segment in self.segments.iter() {
+            match *segment {
+                PathSegment::Index(index) => try!(write!(fmt, "[{}]", index)),
+                PathSegment::Key(ref key) => {
+                    // Quote keys that would otherwise be read as several segments.
+                    if key.is_empty() || key.chars().any(|c| c == '.' || c == '[' || c == ']') {
+                        try!(write!(fmt, "[{:?}]", key))
+                    } else {
+                        try!(write!(fmt, ".{}", key))
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// One step of a `Path`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+    /// An element of an array.
+    Index(usize),
+
+    /// A field of an object.
+    Key(String),
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Error {
         Error::IoError(error)
@@ -157,6 +307,27 @@ impl From<de::value::Error> for Error {
             de::value::Error::MissingFieldError(field) => {
                 de::Error::missing_field_error(field)
             }
+            de::value::Error::CustomError(msg) => {
+                de::Error::custom(msg)
+            }
+            de::value::Error::InvalidTypeError(expected, found) => {
+                de::Error::invalid_type(expected, found)
+            }
+            de::value::Error::InvalidValueError(msg) => {
+                de::Error::invalid_value(&msg)
+            }
+            de::value::Error::InvalidLengthError(len, expected) => {
+                de::Error::invalid_length(len, &expected)
+            }
+            de::value::Error::UnknownFieldError(field, expected) => {
+                de::Error::unknown_field(&field, expected)
+            }
+            de::value::Error::UnknownVariantError(variant, expected) => {
+                de::Error::unknown_variant(&variant, expected)
+            }
+            de::value::Error::DuplicateFieldError(field) => {
+                de::Error::duplicate_field(field)
+            }
         }
     }
 }
@@ -171,6 +342,48 @@ impl de::Error for Error {
     }
 
     fn missing_field_error(field: &'static str) -> Error {
-        Error::MissingFieldError(field)
+        Error::SyntaxError(ErrorCode::MissingField(field), 0, 0)
+    }
+
+    fn custom<T: Into<String>>(msg: T) -> Error {
+        Error::SyntaxError(ErrorCode::Custom(msg.into()), 0, 0)
+    }
+
+    fn invalid_type(expected: &'static str, found: de::Type) -> Error {
+        Error::SyntaxError(ErrorCode::InvalidType(expected, found), 0, 0)
+    }
+
+    fn invalid_value(msg: &str) -> Error {
+        Error::SyntaxError(ErrorCode::InvalidValue(msg.to_string()), 0, 0)
+    }
+
+    fn invalid_length(len: usize, expected: &str) -> Error {
+        Error::SyntaxError(ErrorCode::InvalidLength(len, expected.to_string()), 0, 0)
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Error {
+        Error::SyntaxError(ErrorCode::UnknownField(field.to_string(), expected), 0, 0)
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Error {
+        Error::SyntaxError(ErrorCode::UnknownVariant(variant.to_string(), expected), 0, 0)
+    }
+
+    fn duplicate_field(field: &'static str) -> Error {
+        Error::SyntaxError(ErrorCode::DuplicateField(field), 0, 0)
+    }
+
+    fn at_key(self, key: &str) -> Error {
+        self.at(PathSegment::Key(key.to_string()))
+    }
+
+    fn at_index(self, index: usize) -> Error {
+        self.at(PathSegment::Index(index))
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Into<String>>(msg: T) -> Error {
+        Error::SerializeError(ErrorCode::Custom(msg.into()))
     }
 }
@@ -0,0 +1,134 @@
+/// Builds a `serde::json::Value` from JSON-like syntax.
+///
+/// ```ignore
+/// #[macro_use]
+/// extern crate serde;
+///
+/// let code = 200;
+/// let features = vec!["serde", "json"];
+///
+/// let value = json!({
+///     "code": code,
+///     "success": code == 200,
+///     "payload": {
+///         features[0]: features,
+///         "nothing": null,
+///     },
+/// });
+/// ```
+///
+/// Values can be `null`, nested arrays and objects, or any expression whose type implements
+/// `Serialize`, which is converted with `to_value`. Object keys can be any expression that
+/// converts `Into<String>`. Trailing commas are allowed.
+///
+/// Panics if serializing one of the values fails, such as a map with non-string keys. Use
+/// `to_value` directly to handle the error.
+#[macro_export]
+macro_rules! json {
+    //////////////////////////////////////////////////////////////////////////
+    // The array muncher. Each element is parsed into a `Value` expression and
+    // added to the list in square brackets.
+
+    // Done, with or without a trailing comma.
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        vec![$($elems),*]
+    };
+
+    // The next element is `null`, an array or an object.
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        json!(@array [$($elems,)* json!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        json!(@array [$($elems,)* json!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($map:tt)*} $($rest:tt)*) => {
+        json!(@array [$($elems,)* json!({$($map)*})] $($rest)*)
+    };
+
+    // The next element is an expression, followed by a comma or the end.
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        json!(@array [$($elems,)* json!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        json!(@array [$($elems,)* json!($last)])
+    };
+
+    // The comma after the most recent element.
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        json!(@array [$($elems,)*] $($rest)*)
+    };
+
+    //////////////////////////////////////////////////////////////////////////
+    // The object muncher. Key tokens are collected in parentheses until the
+    // colon, then the value is parsed and the entry inserted into `$object`.
+
+    // Done.
+    (@object $object:ident () ()) => {
+        ()
+    };
+
+    // Insert an entry, followed by a comma or the end.
+    (@object $object:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {{
+        $object.insert(($($key)+).into(), $value);
+        json!(@object $object () ($($rest)*));
+    }};
+    (@object $object:ident [$($key:tt)+] ($value:expr)) => {{
+        $object.insert(($($key)+).into(), $value);
+    }};
+
+    // The value is `null`, an array or an object.
+    (@object $object:ident ($($key:tt)+) (: null $($rest:tt)*)) => {
+        json!(@object $object [$($key)+] (json!(null)) $($rest)*)
+    };
+    (@object $object:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*)) => {
+        json!(@object $object [$($key)+] (json!([$($array)*])) $($rest)*)
+    };
+    (@object $object:ident ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*)) => {
+        json!(@object $object [$($key)+] (json!({$($map)*})) $($rest)*)
+    };
+
+    // The value is an expression, followed by a comma or the end.
+    (@object $object:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*)) => {
+        json!(@object $object [$($key)+] (json!($value)) , $($rest)*)
+    };
+    (@object $object:ident ($($key:tt)+) (: $value:expr)) => {
+        json!(@object $object [$($key)+] (json!($value)))
+    };
+
+    // Move the next token of the key into the parentheses.
+    (@object $object:ident ($($key:tt)*) ($tt:tt $($rest:tt)*)) => {
+        json!(@object $object ($($key)* $tt) ($($rest)*))
+    };
+
+    //////////////////////////////////////////////////////////////////////////
+    // The entry points.
+
+    (null) => {
+        $crate::json::Value::Null
+    };
+
+    ([]) => {
+        $crate::json::Value::Array(vec![])
+    };
+
+    ([ $($tt:tt)+ ]) => {
+        $crate::json::Value::Array(json!(@array [] $($tt)+))
+    };
+
+    ({}) => {
+        $crate::json::Value::Object($crate::json::Map::new())
+    };
+
+    ({ $($tt:tt)+ }) => {{
+        let mut object = $crate::json::Map::new();
+        json!(@object object () ($($tt)+));
+        $crate::json::Value::Object(object)
+    }};
+
+    ($other:expr) => {
+        $crate::json::to_value(&$other).unwrap()
+    };
+}
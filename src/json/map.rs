@@ -0,0 +1,475 @@
+//! The map of keys to values that makes up a JSON object.
+//!
+//! By default a `Map` keeps its keys sorted, like a `BTreeMap`. With the `preserve_order`
+//! feature enabled it keeps them in the order they were first inserted instead, so that parsing
+//! and serializing a document leaves its keys where they were.
+
+#[cfg(not(feature = "preserve_order"))]
+use std::collections::{BTreeMap, btree_map};
+use std::fmt;
+use std::iter::FromIterator;
+
+use de;
+use ser;
+use super::value::Value;
+
+#[cfg(not(feature = "preserve_order"))]
+type MapImpl = BTreeMap<String, Value>;
+#[cfg(not(feature = "preserve_order"))]
+type IterImpl<'a> = btree_map::Iter<'a, String, Value>;
+#[cfg(not(feature = "preserve_order"))]
+type IterMutImpl<'a> = btree_map::IterMut<'a, String, Value>;
+#[cfg(not(feature = "preserve_order"))]
+type IntoIterImpl = btree_map::IntoIter<String, Value>;
+
+#[cfg(feature = "preserve_order")]
+type MapImpl = ordered::OrderedMap;
+#[cfg(feature = "preserve_order")]
+type IterImpl<'a> = ordered::Iter<'a>;
+#[cfg(feature = "preserve_order")]
+type IterMutImpl<'a> = ordered::IterMut<'a>;
+#[cfg(feature = "preserve_order")]
+type IntoIterImpl = ordered::IntoIter;
+
+/// A map of `String` keys to `Value`s, which represents a JSON object.
+#[derive(Clone, PartialEq)]
+pub struct Map {
+    map: MapImpl,
+}
+
+impl Map {
+    /// Creates an empty map.
+    #[inline]
+    pub fn new() -> Map {
+        Map { map: MapImpl::new() }
+    }
+
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.len() == 0
+    }
+
+    /// Removes all the entries from the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// Returns the value associated with `key`, if any.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.map.get(key)
+    }
+
+    /// Returns the value associated with `key` mutably, if any.
+    #[inline]
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if the map has a value for `key`.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Associates `value` with `key`, returning the value it replaces, if any. A replaced entry
+    /// keeps its position in the map.
+    #[inline]
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        self.map.insert(key, value)
+    }
+
+    /// Removes the value associated with `key` and returns it, if any.
+    #[inline]
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.map.remove(key)
+    }
+
+    /// Returns an iterator over the entries of the map.
+    #[inline]
+    pub fn iter(&self) -> Iter {
+        Iter { iter: self.map.iter() }
+    }
+
+    /// Returns an iterator over the entries of the map, with mutable values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut {
+        IterMut { iter: self.map.iter_mut() }
+    }
+
+    /// Returns an iterator over the keys of the map.
+    #[inline]
+    pub fn keys(&self) -> Keys {
+        Keys { iter: self.iter() }
+    }
+
+    /// Returns an iterator over the values of the map.
+    #[inline]
+    pub fn values(&self) -> Values {
+        Values { iter: self.iter() }
+    }
+}
+
+impl Default for Map {
+    #[inline]
+    fn default() -> Map {
+        Map::new()
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{{"));
+
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i != 0 {
+                try!(write!(f, ", "));
+            }
+
+            try!(write!(f, "{:?}: {:?}", key, value));
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl FromIterator<(String, Value)> for Map {
+    fn from_iter<T>(iter: T) -> Map
+        where T: IntoIterator<Item=(String, Value)>,
+    {
+        let mut map = Map::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl Extend<(String, Value)> for Map {
+    fn extend<T>(&mut self, iter: T)
+        where T: IntoIterator<Item=(String, Value)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl ser::Serialize for Map {
+    #[inline]
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: ser::Serializer,
+    {
+        serializer.visit_map(ser::impls::MapIteratorVisitor::new(self.iter(), Some(self.len())))
+    }
+}
+
+impl de::Deserialize for Map {
+    fn deserialize<D>(deserializer: &mut D) -> Result<Map, D::Error>
+        where D: de::Deserializer,
+    {
+        struct MapVisitor;
+
+        impl de::Visitor for MapVisitor {
+            type Value = Map;
+
+            fn expecting(&self) -> &'static str {
+                "map"
+            }
+
+            #[inline]
+            fn visit_unit<E>(&mut self) -> Result<Map, E>
+                where E: de::Error,
+            {
+                Ok(Map::new())
+            }
+
+            #[inline]
+            fn visit_map<V>(&mut self, mut visitor: V) -> Result<Map, V::Error>
+                where V: de::MapVisitor,
+            {
+                let mut values = Map::new();
+
+                while let Some((key, value)) = try!(visitor.visit()) {
+                    values.insert(key, value);
+                }
+
+                try!(visitor.end());
+
+                Ok(values)
+            }
+        }
+
+        deserializer.visit(MapVisitor)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// An iterator over the entries of a `Map`.
+pub struct Iter<'a> {
+    iter: IterImpl<'a>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Value);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a String, &'a Value)> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// An iterator over the entries of a `Map`, with mutable values.
+pub struct IterMut<'a> {
+    iter: IterMutImpl<'a>,
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a String, &'a mut Value);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a String, &'a mut Value)> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// An owning iterator over the entries of a `Map`.
+pub struct IntoIter {
+    iter: IntoIterImpl,
+}
+
+impl Iterator for IntoIter {
+    type Item = (String, Value);
+
+    #[inline]
+    fn next(&mut self) -> Option<(String, Value)> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// An iterator over the keys of a `Map`.
+pub struct Keys<'a> {
+    iter: Iter<'a>,
+}
+
+impl<'a> Iterator for Keys<'a> {
+    type Item = &'a String;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a String> {
+        self.iter.next().map(|(key, _)| key)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// An iterator over the values of a `Map`.
+pub struct Values<'a> {
+    iter: Iter<'a>,
+}
+
+impl<'a> Iterator for Values<'a> {
+    type Item = &'a Value;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a Value> {
+        self.iter.next().map(|(_, value)| value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, Value);
+    type IntoIter = IntoIter;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter {
+        IntoIter { iter: self.map.into_iter() }
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map {
+    type Item = (&'a String, &'a mut Value);
+    type IntoIter = IterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "preserve_order")]
+mod ordered {
+    use std::collections::HashMap;
+    use std::mem;
+    use std::slice;
+    use std::vec;
+
+    use super::super::value::Value;
+
+    /// `OrderedMap` keeps its entries in insertion order, with an index to look keys up by.
+    #[derive(Clone)]
+    pub struct OrderedMap {
+        entries: Vec<(String, Value)>,
+        indices: HashMap<String, usize>,
+    }
+
+    impl OrderedMap {
+        pub fn new() -> OrderedMap {
+            OrderedMap {
+                entries: Vec::new(),
+                indices: HashMap::new(),
+            }
+        }
+
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        pub fn clear(&mut self) {
+            self.entries.clear();
+            self.indices.clear();
+        }
+
+        pub fn get(&self, key: &str) -> Option<&Value> {
+            match self.indices.get(key) {
+                Some(&index) => Some(&self.entries[index].1),
+                None => None,
+            }
+        }
+
+        pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+            match self.indices.get(key) {
+                Some(&index) => Some(&mut self.entries[index].1),
+                None => None,
+            }
+        }
+
+        pub fn contains_key(&self, key: &str) -> bool {
+            self.indices.contains_key(key)
+        }
+
+        pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+            if let Some(&index) = self.indices.get(&key) {
+                return Some(mem::replace(&mut self.entries[index].1, value));
+            }
+
+            self.indices.insert(key.clone(), self.entries.len());
+            self.entries.push((key, value));
+            None
+        }
+
+        pub fn remove(&mut self, key: &str) -> Option<Value> {
+            let index = match self.indices.remove(key) {
+                Some(index) => index,
+                None => { return None; }
+            };
+
+            let (_, value) = self.entries.remove(index);
+
+            // Every later entry has moved down by one.
+            for i in self.indices.values_mut() {
+                if *i > index {
+                    *i -= 1;
+                }
+            }
+
+            Some(value)
+        }
+
+        pub fn iter(&self) -> Iter {
+            Iter { iter: self.entries.iter() }
+        }
+
+        pub fn iter_mut(&mut self) -> IterMut {
+            IterMut { iter: self.entries.iter_mut() }
+        }
+
+        pub fn into_iter(self) -> IntoIter {
+            self.entries.into_iter()
+        }
+    }
+
+    impl PartialEq for OrderedMap {
+        /// Maps are equal when they have the same entries, whatever their order.
+        fn eq(&self, other: &OrderedMap) -> bool {
+            self.len() == other.len() &&
+                self.entries.iter().all(|&(ref key, ref value)| other.get(key) == Some(value))
+        }
+    }
+
+    pub struct Iter<'a> {
+        iter: slice::Iter<'a, (String, Value)>,
+    }
+
+    impl<'a> Iterator for Iter<'a> {
+        type Item = (&'a String, &'a Value);
+
+        fn next(&mut self) -> Option<(&'a String, &'a Value)> {
+            self.iter.next().map(|&(ref key, ref value)| (key, value))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.iter.size_hint()
+        }
+    }
+
+    pub struct IterMut<'a> {
+        iter: slice::IterMut<'a, (String, Value)>,
+    }
+
+    impl<'a> Iterator for IterMut<'a> {
+        type Item = (&'a String, &'a mut Value);
+
+        fn next(&mut self) -> Option<(&'a String, &'a mut Value)> {
+            self.iter.next().map(|&mut (ref key, ref mut value)| (key, value))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.iter.size_hint()
+        }
+    }
+
+    pub type IntoIter = vec::IntoIter<(String, Value)>;
+}
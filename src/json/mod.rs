@@ -16,7 +16,8 @@
 //! * `String`: equivalent to rust's `String`
 //! * `Array`: equivalent to rust's `Vec<T>`, but also allowing objects of different types in the
 //!    same array
-//! * `Object`: equivalent to rust's `BTreeMap<String, serde::json::Value>`
+//! * `Object`: equivalent to rust's `BTreeMap<String, serde::json::Value>`, or an insertion
+//!    ordered map with the `preserve_order` feature (see `serde::json::Map`)
 //! * `Null`
 //!
 //! An object is a series of string keys mapping to values, in `"key": value` format.  Arrays are
@@ -92,8 +93,10 @@
 //! }
 //! ```
 
-pub use self::de::{Deserializer, from_str};
-pub use self::error::{Error, ErrorCode};
+pub use self::de::{Deserializer, StreamDeserializer, from_str};
+pub use self::error::{Error, ErrorCode, Path, PathSegment};
+pub use self::map::Map;
+pub use self::read::{IoRead, SliceRead};
 pub use self::ser::{
     Serializer,
     to_writer,
@@ -106,8 +109,14 @@ pub use self::ser::{
 };
 pub use self::value::{Value, to_value, from_value};
 
+#[macro_use]
+mod macros;
+
 pub mod builder;
 pub mod de;
 pub mod error;
+pub mod map;
+pub mod patch;
+pub mod read;
 pub mod ser;
 pub mod value;
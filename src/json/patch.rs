@@ -0,0 +1,370 @@
+//! JSON Patch (RFC 6902) and JSON Merge Patch (RFC 7396) support for `Value`s.
+//!
+//! A JSON Patch is a list of `PatchOperation`s, which `patch` applies to a document all at once
+//! or not at all. `diff` computes a JSON Patch that turns one document into another. A merge
+//! patch is a document that mirrors the shape of the one it changes, and is applied with
+//! `merge`.
+
+use std::error;
+use std::fmt;
+
+use de;
+use ser;
+use super::map::Map;
+use super::value::Value;
+
+/// A single operation of a JSON Patch. Paths are JSON Pointers, as taken by `Value::pointer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchOperation {
+    /// Inserts `value` at `path`, replacing any object member already there.
+    Add { path: String, value: Value },
+    /// Removes the value at `path`.
+    Remove { path: String },
+    /// Replaces the value at `path`, which has to exist, with `value`.
+    Replace { path: String, value: Value },
+    /// Removes the value at `from` and adds it at `path`.
+    Move { from: String, path: String },
+    /// Adds a copy of the value at `from` at `path`.
+    Copy { from: String, path: String },
+    /// Checks that the value at `path` is equal to `value`.
+    Test { path: String, value: Value },
+}
+
+/// The reason a `PatchOperation` could not be applied.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchErrorKind {
+    /// There is no value at the path, or for an add, no parent to add it to.
+    PathNotFound(String),
+    /// A move tried to move a value into one of its own children.
+    MoveIntoChild(String),
+    /// A test found a different value at the path.
+    TestFailed(String),
+}
+
+/// The error returned when a JSON Patch fails, naming the index of the operation that failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatchError {
+    pub index: usize,
+    pub kind: PatchErrorKind,
+}
+
+impl error::Error for PatchError {
+    fn description(&self) -> &str {
+        match self.kind {
+            PatchErrorKind::PathNotFound(_) => "path not found",
+            PatchErrorKind::MoveIntoChild(_) => "cannot move a value into one of its children",
+            PatchErrorKind::TestFailed(_) => "test failed",
+        }
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let path = match self.kind {
+            PatchErrorKind::PathNotFound(ref path) => path,
+            PatchErrorKind::MoveIntoChild(ref path) => path,
+            PatchErrorKind::TestFailed(ref path) => path,
+        };
+
+        write!(fmt, "operation {}: {} at \"{}\"", self.index, error::Error::description(self), path)
+    }
+}
+
+/// Applies the operations of a JSON Patch to `doc` in order. If any of them fails, `doc` is
+/// left unchanged and the error names the operation that failed.
+pub fn patch(doc: &mut Value, operations: &[PatchOperation]) -> Result<(), PatchError> {
+    let mut patched = doc.clone();
+
+    for (index, operation) in operations.iter().enumerate() {
+        match apply(&mut patched, operation) {
+            Ok(()) => { }
+            Err(kind) => {
+                return Err(PatchError { index: index, kind: kind });
+            }
+        }
+    }
+
+    *doc = patched;
+    Ok(())
+}
+
+fn apply(doc: &mut Value, operation: &PatchOperation) -> Result<(), PatchErrorKind> {
+    match *operation {
+        PatchOperation::Add { ref path, ref value } => add(doc, path, value.clone()),
+        PatchOperation::Remove { ref path } => {
+            match doc.remove_at_pointer(path) {
+                Some(_) => Ok(()),
+                None => Err(PatchErrorKind::PathNotFound(path.clone())),
+            }
+        }
+        PatchOperation::Replace { ref path, ref value } => {
+            match doc.pointer_mut(path) {
+                Some(target) => {
+                    *target = value.clone();
+                    Ok(())
+                }
+                None => Err(PatchErrorKind::PathNotFound(path.clone())),
+            }
+        }
+        PatchOperation::Move { ref from, ref path } => {
+            if path.starts_with(&format!("{}/", from)[..]) {
+                return Err(PatchErrorKind::MoveIntoChild(path.clone()));
+            }
+
+            match doc.remove_at_pointer(from) {
+                Some(value) => add(doc, path, value),
+                None => Err(PatchErrorKind::PathNotFound(from.clone())),
+            }
+        }
+        PatchOperation::Copy { ref from, ref path } => {
+            let value = match doc.pointer(from) {
+                Some(value) => value.clone(),
+                None => { return Err(PatchErrorKind::PathNotFound(from.clone())); }
+            };
+
+            add(doc, path, value)
+        }
+        PatchOperation::Test { ref path, ref value } => {
+            match doc.pointer(path) {
+                Some(target) if values_equal(target, value) => Ok(()),
+                Some(_) => Err(PatchErrorKind::TestFailed(path.clone())),
+                None => Err(PatchErrorKind::PathNotFound(path.clone())),
+            }
+        }
+    }
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), PatchErrorKind> {
+    match doc.insert_at_pointer(path, value) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(PatchErrorKind::PathNotFound(path.to_string())),
+    }
+}
+
+/// Compares two values the way the `test` operation does (RFC 6902, section 4.6). Unlike `==`,
+/// numbers are equal when their values are, whether they are stored as `U64`, `I64` or `F64`.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (&Value::I64(i), &Value::U64(u)) | (&Value::U64(u), &Value::I64(i)) => {
+            i >= 0 && i as u64 == u
+        }
+        (&Value::F64(f), &Value::U64(u)) | (&Value::U64(u), &Value::F64(f)) => {
+            f >= 0.0 && f < 18446744073709551616.0 && f as u64 == u && u as f64 == f
+        }
+        (&Value::F64(f), &Value::I64(i)) | (&Value::I64(i), &Value::F64(f)) => {
+            f >= -9223372036854775808.0 && f < 9223372036854775808.0 && f as i64 == i &&
+                i as f64 == f
+        }
+        (&Value::Array(ref a), &Value::Array(ref b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| values_equal(a, b))
+        }
+        (&Value::Object(ref a), &Value::Object(ref b)) => {
+            a.len() == b.len() && a.iter().all(|(key, a)| {
+                b.get(key).map_or(false, |b| values_equal(a, b))
+            })
+        }
+        _ => a == b,
+    }
+}
+
+/// Applies a JSON Merge Patch to `doc`. Members of an object patch are merged into `doc`
+/// recursively, with `null` members removing the corresponding member of `doc`. Any other patch
+/// replaces `doc` entirely.
+pub fn merge(doc: &mut Value, patch: &Value) {
+    let patch = match *patch {
+        Value::Object(ref patch) => patch,
+        _ => {
+            *doc = patch.clone();
+            return;
+        }
+    };
+
+    if !doc.is_object() {
+        *doc = Value::Object(Map::new());
+    }
+
+    let map = doc.as_object_mut().unwrap();
+
+    for (key, value) in patch {
+        if value.is_null() {
+            map.remove(key);
+        } else {
+            if !map.contains_key(key) {
+                map.insert(key.clone(), Value::Null);
+            }
+
+            merge(map.get_mut(key).unwrap(), value);
+        }
+    }
+}
+
+/// Computes a JSON Patch that turns `from` into `to`.
+pub fn diff(from: &Value, to: &Value) -> Vec<PatchOperation> {
+    let mut operations = vec![];
+    diff_at(&mut String::new(), from, to, &mut operations);
+    operations
+}
+
+fn diff_at(path: &mut String, from: &Value, to: &Value, operations: &mut Vec<PatchOperation>) {
+    if from == to {
+        return;
+    }
+
+    match (from, to) {
+        (&Value::Object(ref from), &Value::Object(ref to)) => {
+            for key in from.keys() {
+                if !to.contains_key(key) {
+                    operations.push(PatchOperation::Remove { path: child_path(path, key) });
+                }
+            }
+
+            for (key, value) in to {
+                match from.get(key) {
+                    Some(from_value) => {
+                        let len = path.len();
+                        path.push('/');
+                        path.push_str(&escape_pointer_token(key));
+                        diff_at(path, from_value, value, operations);
+                        path.truncate(len);
+                    }
+                    None => {
+                        operations.push(PatchOperation::Add {
+                            path: child_path(path, key),
+                            value: value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        (&Value::Array(ref from), &Value::Array(ref to)) => {
+            let common = if from.len() < to.len() { from.len() } else { to.len() };
+
+            for (index, (from_value, value)) in from.iter().zip(to.iter()).enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{}", index));
+                diff_at(path, from_value, value, operations);
+                path.truncate(len);
+            }
+
+            // Remove from the end, so the indices of the elements left don't shift.
+            for index in (common..from.len()).rev() {
+                operations.push(PatchOperation::Remove { path: format!("{}/{}", path, index) });
+            }
+
+            for value in &to[common..] {
+                operations.push(PatchOperation::Add {
+                    path: format!("{}/-", path),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ => {
+            operations.push(PatchOperation::Replace { path: path.clone(), value: to.clone() });
+        }
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    format!("{}/{}", path, escape_pointer_token(key))
+}
+
+fn escape_pointer_token(key: &str) -> String {
+    key.replace("~", "~0").replace("/", "~1")
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+impl PatchOperation {
+    fn to_value(&self) -> Value {
+        let (op, path, from, value) = match *self {
+            PatchOperation::Add { ref path, ref value } => ("add", path, None, Some(value)),
+            PatchOperation::Remove { ref path } => ("remove", path, None, None),
+            PatchOperation::Replace { ref path, ref value } => ("replace", path, None, Some(value)),
+            PatchOperation::Move { ref from, ref path } => ("move", path, Some(from), None),
+            PatchOperation::Copy { ref from, ref path } => ("copy", path, Some(from), None),
+            PatchOperation::Test { ref path, ref value } => ("test", path, None, Some(value)),
+        };
+
+        let mut map = Map::new();
+        map.insert("op".to_string(), Value::String(op.to_string()));
+
+        if let Some(from) = from {
+            map.insert("from".to_string(), Value::String(from.clone()));
+        }
+
+        map.insert("path".to_string(), Value::String(path.clone()));
+
+        if let Some(value) = value {
+            map.insert("value".to_string(), value.clone());
+        }
+
+        Value::Object(map)
+    }
+}
+
+impl ser::Serialize for PatchOperation {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: ser::Serializer,
+    {
+        ser::Serialize::serialize(&self.to_value(), serializer)
+    }
+}
+
+impl de::Deserialize for PatchOperation {
+    fn deserialize<D>(deserializer: &mut D) -> Result<PatchOperation, D::Error>
+        where D: de::Deserializer,
+    {
+        let value: Value = try!(de::Deserialize::deserialize(deserializer));
+
+        let mut map = match value {
+            Value::Object(map) => map,
+            _ => { return Err(de::Error::invalid_value("patch operation must be an object")); }
+        };
+
+        let op = try!(take_string::<D::Error>(&mut map, "op"));
+        let path = try!(take_string::<D::Error>(&mut map, "path"));
+
+        match &op[..] {
+            "add" => {
+                let value = try!(take_value::<D::Error>(&mut map, "value"));
+                Ok(PatchOperation::Add { path: path, value: value })
+            }
+            "remove" => Ok(PatchOperation::Remove { path: path }),
+            "replace" => {
+                let value = try!(take_value::<D::Error>(&mut map, "value"));
+                Ok(PatchOperation::Replace { path: path, value: value })
+            }
+            "move" => {
+                let from = try!(take_string::<D::Error>(&mut map, "from"));
+                Ok(PatchOperation::Move { from: from, path: path })
+            }
+            "copy" => {
+                let from = try!(take_string::<D::Error>(&mut map, "from"));
+                Ok(PatchOperation::Copy { from: from, path: path })
+            }
+            "test" => {
+                let value = try!(take_value::<D::Error>(&mut map, "value"));
+                Ok(PatchOperation::Test { path: path, value: value })
+            }
+            _ => Err(de::Error::invalid_value(&format!("unknown patch operation \"{}\"", op))),
+        }
+    }
+}
+
+fn take_value<E>(map: &mut Map, field: &'static str) -> Result<Value, E>
+    where E: de::Error,
+{
+    match map.remove(field) {
+        Some(value) => Ok(value),
+        None => Err(de::Error::missing_field_error(field)),
+    }
+}
+
+fn take_string<E>(map: &mut Map, field: &'static str) -> Result<String, E>
+    where E: de::Error,
+{
+    match try!(take_value(map, field)) {
+        Value::String(value) => Ok(value),
+        _ => Err(de::Error::invalid_value(&format!("patch field \"{}\" must be a string", field))),
+    }
+}
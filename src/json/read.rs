@@ -0,0 +1,248 @@
+//! Sources of input for the JSON `Deserializer`.
+
+use std::io;
+
+/// `Read` is the input a `Deserializer` parses. Input that lives for `'a` can let the
+/// `Deserializer` borrow strings from it rather than copying them.
+///
+/// Only `next` is required. The other methods let inputs that can look ahead skip over runs of
+/// bytes at once, and their defaults leave the work to `next`.
+pub trait Read<'a> {
+    /// Returns the next byte of input, or `None` at the end of the input.
+    fn next(&mut self) -> Option<io::Result<u8>>;
+
+    /// Called just after the opening quote of a string has been read. If the rest of the string
+    /// can be borrowed from the input as is, because it contains no escapes or control
+    /// characters, this consumes it along with its closing quote and returns it without the
+    /// quote. Otherwise nothing is consumed.
+    #[inline]
+    fn borrow_str(&mut self) -> Option<&'a [u8]> {
+        None
+    }
+
+    /// Called inside a string. Copies the bytes up to the next quote, backslash or control
+    /// character into `buf`, and returns how many were copied. Copying fewer, or none at all, is
+    /// allowed.
+    #[inline]
+    fn read_str_chunk(&mut self, _buf: &mut Vec<u8>) -> usize {
+        0
+    }
+
+    /// Called after a space, tab or carriage return has been read. Skips the spaces, tabs and
+    /// carriage returns that immediately follow, and returns how many were skipped. Skipping
+    /// fewer, or none at all, is allowed.
+    #[inline]
+    fn skip_spaces(&mut self) -> usize {
+        0
+    }
+
+    /// Returns the input between the byte offsets `start` and `end`, if it is still available.
+    /// Used to recover borrowed object keys when reporting the path of an error.
+    #[inline]
+    fn slice(&self, _start: usize, _end: usize) -> Option<&'a [u8]> {
+        None
+    }
+}
+
+impl<'a, Iter> Read<'a> for Iter
+    where Iter: Iterator<Item=io::Result<u8>>,
+{
+    #[inline]
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        Iterator::next(self)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// `SliceRead` reads JSON from an in-memory byte slice, and can lend out strings from it.
+pub struct SliceRead<'a> {
+    slice: &'a [u8],
+    index: usize,
+}
+
+impl<'a> SliceRead<'a> {
+    /// Creates a JSON input from a `&[u8]`.
+    #[inline]
+    pub fn new(slice: &'a [u8]) -> SliceRead<'a> {
+        SliceRead {
+            slice: slice,
+            index: 0,
+        }
+    }
+
+    /// Returns the length of the run of plain string bytes at the current position.
+    #[inline]
+    fn str_chunk_len(&self) -> usize {
+        let rest = &self.slice[self.index..];
+        rest.iter().position(|ch| ESCAPE[*ch as usize]).unwrap_or(rest.len())
+    }
+}
+
+impl<'a> Read<'a> for SliceRead<'a> {
+    #[inline]
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        if self.index < self.slice.len() {
+            let ch = self.slice[self.index];
+            self.index += 1;
+            Some(Ok(ch))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn borrow_str(&mut self) -> Option<&'a [u8]> {
+        let start = self.index;
+        let end = start + self.str_chunk_len();
+
+        if end < self.slice.len() && self.slice[end] == b'"' {
+            self.index = end + 1;
+            Some(&self.slice[start..end])
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn read_str_chunk(&mut self, buf: &mut Vec<u8>) -> usize {
+        let start = self.index;
+        let end = start + self.str_chunk_len();
+
+        buf.extend(self.slice[start..end].iter().map(|b| *b));
+        self.index = end;
+        end - start
+    }
+
+    #[inline]
+    fn skip_spaces(&mut self) -> usize {
+        let start = self.index;
+        self.index += count_spaces(&self.slice[start..]);
+        self.index - start
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> Option<&'a [u8]> {
+        if start <= end && end <= self.slice.len() {
+            Some(&self.slice[start..end])
+        } else {
+            None
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// `IoRead` reads JSON from an `std::io::Read`, buffering it so that the underlying reader is
+/// not called for every byte.
+pub struct IoRead<R> {
+    rdr: R,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+}
+
+impl<R> IoRead<R>
+    where R: io::Read,
+{
+    /// Creates a JSON input from an `std::io::Read`.
+    #[inline]
+    pub fn new(rdr: R) -> IoRead<R> {
+        IoRead {
+            rdr: rdr,
+            buf: vec![0; 8 * 1024],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    fn fill_buf(&mut self) -> io::Result<usize> {
+        loop {
+            match self.rdr.read(&mut self.buf) {
+                Ok(len) => {
+                    self.pos = 0;
+                    self.len = len;
+                    return Ok(len);
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => { }
+                Err(err) => { return Err(err); }
+            }
+        }
+    }
+
+    /// Returns the length of the run of plain string bytes in the buffer.
+    #[inline]
+    fn str_chunk_len(&self) -> usize {
+        let rest = &self.buf[self.pos..self.len];
+        rest.iter().position(|ch| ESCAPE[*ch as usize]).unwrap_or(rest.len())
+    }
+}
+
+impl<'a, R> Read<'a> for IoRead<R>
+    where R: io::Read,
+{
+    #[inline]
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        if self.pos == self.len {
+            match self.fill_buf() {
+                Ok(0) => { return None; }
+                Ok(_) => { }
+                Err(err) => { return Some(Err(err)); }
+            }
+        }
+
+        let ch = self.buf[self.pos];
+        self.pos += 1;
+        Some(Ok(ch))
+    }
+
+    #[inline]
+    fn read_str_chunk(&mut self, buf: &mut Vec<u8>) -> usize {
+        let start = self.pos;
+        let end = start + self.str_chunk_len();
+
+        buf.extend(self.buf[start..end].iter().map(|b| *b));
+        self.pos = end;
+        end - start
+    }
+
+    #[inline]
+    fn skip_spaces(&mut self) -> usize {
+        let start = self.pos;
+        self.pos += count_spaces(&self.buf[start..self.len]);
+        self.pos - start
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[inline]
+fn count_spaces(v: &[u8]) -> usize {
+    v.iter().position(|ch| !(*ch == b' ' || *ch == b'\t' || *ch == b'\r')).unwrap_or(v.len())
+}
+
+const CT: bool = true;  // control character \x00 ... \x1F
+const QU: bool = true;  // quote \x22
+const BS: bool = true;  // backslash \x5C
+const __: bool = false; // allowed unescaped
+
+/// Lookup table of the bytes that end a run of plain string bytes.
+static ESCAPE: [bool; 256] = [
+    //  1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
+    CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, // 0
+    CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, // 1
+    __, __, QU, __, __, __, __, __, __, __, __, __, __, __, __, __, // 2
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 3
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 4
+    __, __, __, __, __, __, __, __, __, __, __, __, BS, __, __, __, // 5
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 6
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 7
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 8
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 9
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // A
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // B
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // C
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // D
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // E
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // F
+];
@@ -1,9 +1,9 @@
 use std::{f32, f64};
 use std::io;
 use std::num::{Float, FpCategory};
-use std::string::FromUtf8Error;
 
 use ser;
+use super::error::{Error, ErrorCode};
 
 /// A structure for implementing serialization to JSON.
 pub struct Serializer<W, F=CompactFormatter> {
@@ -52,121 +52,154 @@ impl<W, F> ser::Serializer for Serializer<W, F>
     where W: io::Write,
           F: Formatter,
 {
-    type Error = io::Error;
+    type Error = Error;
 
     #[inline]
-    fn visit_bool(&mut self, value: bool) -> io::Result<()> {
+    fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
         if value {
-            self.writer.write_all(b"true")
+            try!(self.writer.write_all(b"true"));
         } else {
-            self.writer.write_all(b"false")
+            try!(self.writer.write_all(b"false"));
         }
+        Ok(())
     }
 
     #[inline]
-    fn visit_isize(&mut self, value: isize) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_isize(&mut self, value: isize) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_i8(&mut self, value: i8) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_i8(&mut self, value: i8) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_i16(&mut self, value: i16) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_i16(&mut self, value: i16) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_i32(&mut self, value: i32) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_i32(&mut self, value: i32) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_i64(&mut self, value: i64) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_usize(&mut self, value: usize) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_usize(&mut self, value: usize) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_u8(&mut self, value: u8) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_u8(&mut self, value: u8) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_u16(&mut self, value: u16) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_u16(&mut self, value: u16) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_u32(&mut self, value: u32) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_u32(&mut self, value: u32) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_u64(&mut self, value: u64) -> io::Result<()> {
-        write!(&mut self.writer, "{}", value)
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        try!(write!(&mut self.writer, "{}", value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_f32(&mut self, value: f32) -> io::Result<()> {
-        fmt_f32_or_null(&mut self.writer, value)
+    fn visit_f32(&mut self, value: f32) -> Result<(), Error> {
+        try!(fmt_f32_or_null(&mut self.writer, value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_f64(&mut self, value: f64) -> io::Result<()> {
-        fmt_f64_or_null(&mut self.writer, value)
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        try!(fmt_f64_or_null(&mut self.writer, value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_char(&mut self, value: char) -> io::Result<()> {
-        escape_char(&mut self.writer, value)
+    fn visit_char(&mut self, value: char) -> Result<(), Error> {
+        try!(escape_char(&mut self.writer, value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_str(&mut self, value: &str) -> io::Result<()> {
-        escape_str(&mut self.writer, value)
+    fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+        try!(escape_str(&mut self.writer, value));
+        Ok(())
     }
 
     #[inline]
-    fn visit_none(&mut self) -> io::Result<()> {
+    fn visit_none(&mut self) -> Result<(), Error> {
         self.visit_unit()
     }
 
     #[inline]
-    fn visit_some<V>(&mut self, value: V) -> io::Result<()>
+    fn visit_some<V>(&mut self, value: V) -> Result<(), Error>
         where V: ser::Serialize
     {
         value.serialize(self)
     }
 
     #[inline]
-    fn visit_unit(&mut self) -> io::Result<()> {
-        self.writer.write_all(b"null")
+    fn visit_unit(&mut self) -> Result<(), Error> {
+        try!(self.writer.write_all(b"null"));
+        Ok(())
     }
 
     #[inline]
-    fn visit_enum_unit(&mut self, _name: &str, variant: &str) -> io::Result<()> {
+    fn visit_enum_unit(&mut self, _name: &str, variant: &str) -> Result<(), Error> {
         try!(self.formatter.open(&mut self.writer, b'{'));
         try!(self.formatter.comma(&mut self.writer, true));
         try!(self.visit_str(variant));
         try!(self.formatter.colon(&mut self.writer));
         try!(self.writer.write_all(b"[]"));
-        self.formatter.close(&mut self.writer, b'}')
+        try!(self.formatter.close(&mut self.writer, b'}'));
+        Ok(())
     }
 
     #[inline]
-    fn visit_seq<V>(&mut self, mut visitor: V) -> io::Result<()>
+    fn visit_newtype_variant<T>(&mut self,
+                                _name: &str,
+                                variant: &str,
+                                value: T) -> Result<(), Error>
+        where T: ser::Serialize,
+    {
+        try!(self.formatter.open(&mut self.writer, b'{'));
+        try!(self.formatter.comma(&mut self.writer, true));
+        try!(self.visit_str(variant));
+        try!(self.formatter.colon(&mut self.writer));
+        try!(value.serialize(self));
+        try!(self.formatter.close(&mut self.writer, b'}'));
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_seq<V>(&mut self, mut visitor: V) -> Result<(), Error>
         where V: ser::SeqVisitor,
     {
         match visitor.len() {
             Some(len) if len == 0 => {
-                self.writer.write_all(b"[]")
+                try!(self.writer.write_all(b"[]"));
             }
             _ => {
                 try!(self.formatter.open(&mut self.writer, b'['));
@@ -175,14 +208,15 @@ impl<W, F> ser::Serializer for Serializer<W, F>
 
                 while let Some(()) = try!(visitor.visit(self)) { }
 
-                self.formatter.close(&mut self.writer, b']')
+                try!(self.formatter.close(&mut self.writer, b']'));
             }
         }
 
+        Ok(())
     }
 
     #[inline]
-    fn visit_enum_seq<V>(&mut self, _name: &str, variant: &str, visitor: V) -> io::Result<()>
+    fn visit_enum_seq<V>(&mut self, _name: &str, variant: &str, visitor: V) -> Result<(), Error>
         where V: ser::SeqVisitor,
     {
         try!(self.formatter.open(&mut self.writer, b'{'));
@@ -190,11 +224,12 @@ impl<W, F> ser::Serializer for Serializer<W, F>
         try!(self.visit_str(variant));
         try!(self.formatter.colon(&mut self.writer));
         try!(self.visit_seq(visitor));
-        self.formatter.close(&mut self.writer, b'}')
+        try!(self.formatter.close(&mut self.writer, b'}'));
+        Ok(())
     }
 
     #[inline]
-    fn visit_seq_elt<T>(&mut self, value: T) -> io::Result<()>
+    fn visit_seq_elt<T>(&mut self, value: T) -> Result<(), Error>
         where T: ser::Serialize,
     {
         try!(self.formatter.comma(&mut self.writer, self.first));
@@ -204,12 +239,12 @@ impl<W, F> ser::Serializer for Serializer<W, F>
     }
 
     #[inline]
-    fn visit_map<V>(&mut self, mut visitor: V) -> io::Result<()>
+    fn visit_map<V>(&mut self, mut visitor: V) -> Result<(), Error>
         where V: ser::MapVisitor,
     {
         match visitor.len() {
             Some(len) if len == 0 => {
-                self.writer.write_all(b"{}")
+                try!(self.writer.write_all(b"{}"));
             }
             _ => {
                 try!(self.formatter.open(&mut self.writer, b'{'));
@@ -218,13 +253,15 @@ impl<W, F> ser::Serializer for Serializer<W, F>
 
                 while let Some(()) = try!(visitor.visit(self)) { }
 
-                self.formatter.close(&mut self.writer, b'}')
+                try!(self.formatter.close(&mut self.writer, b'}'));
             }
         }
+
+        Ok(())
     }
 
     #[inline]
-    fn visit_enum_map<V>(&mut self, _name: &str, variant: &str, visitor: V) -> io::Result<()>
+    fn visit_enum_map<V>(&mut self, _name: &str, variant: &str, visitor: V) -> Result<(), Error>
         where V: ser::MapVisitor,
     {
         try!(self.formatter.open(&mut self.writer, b'{'));
@@ -232,24 +269,97 @@ impl<W, F> ser::Serializer for Serializer<W, F>
         try!(self.visit_str(variant));
         try!(self.formatter.colon(&mut self.writer));
         try!(self.visit_map(visitor));
-
-        self.formatter.close(&mut self.writer, b'}')
+        try!(self.formatter.close(&mut self.writer, b'}'));
+        Ok(())
     }
 
     #[inline]
-    fn visit_map_elt<K, V>(&mut self, key: K, value: V) -> io::Result<()>
+    fn visit_map_elt<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
         where K: ser::Serialize,
               V: ser::Serialize,
     {
         try!(self.formatter.comma(&mut self.writer, self.first));
         self.first = false;
 
-        try!(key.serialize(self));
+        try!(key.serialize(&mut MapKeySerializer { ser: self }));
         try!(self.formatter.colon(&mut self.writer));
         value.serialize(self)
     }
 }
 
+/// JSON object keys must be strings, so map keys are serialized through this wrapper, which only
+/// lets strings through to the underlying serializer.
+struct MapKeySerializer<'a, W: 'a, F: 'a> {
+    ser: &'a mut Serializer<W, F>,
+}
+
+impl<'a, W, F> ser::Serializer for MapKeySerializer<'a, W, F>
+    where W: io::Write,
+          F: Formatter,
+{
+    type Error = Error;
+
+    #[inline]
+    fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+        ser::Serializer::visit_str(self.ser, value)
+    }
+
+    fn visit_bool(&mut self, _value: bool) -> Result<(), Error> {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_i64(&mut self, _value: i64) -> Result<(), Error> {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_u64(&mut self, _value: u64) -> Result<(), Error> {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_f64(&mut self, _value: f64) -> Result<(), Error> {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_unit(&mut self) -> Result<(), Error> {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_none(&mut self) -> Result<(), Error> {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_some<V>(&mut self, _value: V) -> Result<(), Error>
+        where V: ser::Serialize,
+    {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_seq<V>(&mut self, _visitor: V) -> Result<(), Error>
+        where V: ser::SeqVisitor,
+    {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_seq_elt<T>(&mut self, _value: T) -> Result<(), Error>
+        where T: ser::Serialize,
+    {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_map<V>(&mut self, _visitor: V) -> Result<(), Error>
+        where V: ser::MapVisitor,
+    {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+
+    fn visit_map_elt<K, V>(&mut self, _key: K, _value: V) -> Result<(), Error>
+        where K: ser::Serialize,
+              V: ser::Serialize,
+    {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString))
+    }
+}
+
 pub trait Formatter {
     fn open<W>(&mut self, writer: &mut W, ch: u8) -> io::Result<()>
         where W: io::Write;
@@ -411,7 +521,7 @@ fn fmt_f64_or_null<W>(wr: &mut W, value: f64) -> io::Result<()>
 
 /// Encode the specified struct into a json `[u8]` writer.
 #[inline]
-pub fn to_writer<W, T>(writer: &mut W, value: &T) -> io::Result<()>
+pub fn to_writer<W, T>(writer: &mut W, value: &T) -> Result<(), Error>
     where W: io::Write,
           T: ser::Serialize,
 {
@@ -422,7 +532,7 @@ pub fn to_writer<W, T>(writer: &mut W, value: &T) -> io::Result<()>
 
 /// Encode the specified struct into a json `[u8]` writer.
 #[inline]
-pub fn to_writer_pretty<W, T>(writer: &mut W, value: &T) -> io::Result<()>
+pub fn to_writer_pretty<W, T>(writer: &mut W, value: &T) -> Result<(), Error>
     where W: io::Write,
           T: ser::Serialize,
 {
@@ -436,44 +546,48 @@ pub fn to_writer_pretty<W, T>(writer: &mut W, value: &T) -> io::Result<()>
 
 /// Encode the specified struct into a json `[u8]` buffer.
 #[inline]
-pub fn to_vec<T>(value: &T) -> Vec<u8>
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, Error>
     where T: ser::Serialize,
 {
-    // We are writing to a Vec, which doesn't fail. So we can ignore
-    // the error.
+    // We are writing to a Vec, which doesn't fail, but the value itself may still fail to
+    // serialize.
     let mut writer = Vec::with_capacity(128);
-    to_writer(&mut writer, value).unwrap();
-    writer
+    try!(to_writer(&mut writer, value));
+    Ok(writer)
 }
 
 /// Encode the specified struct into a json `[u8]` buffer.
 #[inline]
-pub fn to_vec_pretty<T>(value: &T) -> Vec<u8>
+pub fn to_vec_pretty<T>(value: &T) -> Result<Vec<u8>, Error>
     where T: ser::Serialize,
 {
-    // We are writing to a Vec, which doesn't fail. So we can ignore
-    // the error.
+    // We are writing to a Vec, which doesn't fail, but the value itself may still fail to
+    // serialize.
     let mut writer = Vec::with_capacity(128);
-    to_writer_pretty(&mut writer, value).unwrap();
-    writer
+    try!(to_writer_pretty(&mut writer, value));
+    Ok(writer)
 }
 
 /// Encode the specified struct into a json `String` buffer.
 #[inline]
-pub fn to_string<T>(value: &T) -> Result<String, FromUtf8Error>
+pub fn to_string<T>(value: &T) -> Result<String, Error>
     where T: ser::Serialize
 {
-    let vec = to_vec(value);
-    String::from_utf8(vec)
+    let vec = try!(to_vec(value));
+
+    // The serializer only ever writes valid UTF-8.
+    Ok(String::from_utf8(vec).unwrap())
 }
 
 /// Encode the specified struct into a json `String` buffer.
 #[inline]
-pub fn to_string_pretty<T>(value: &T) -> Result<String, FromUtf8Error>
+pub fn to_string_pretty<T>(value: &T) -> Result<String, Error>
     where T: ser::Serialize
 {
-    let vec = to_vec_pretty(value);
-    String::from_utf8(vec)
+    let vec = try!(to_vec_pretty(value));
+
+    // The serializer only ever writes valid UTF-8.
+    Ok(String::from_utf8(vec).unwrap())
 }
 
 fn spaces<W>(wr: &mut W, mut n: usize) -> io::Result<()>
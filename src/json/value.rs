@@ -1,13 +1,14 @@
-use std::collections::{BTreeMap, btree_map};
 use std::fmt;
 use std::io;
+use std::mem;
 use std::num;
 use std::str;
 use std::vec;
 
 use de;
 use ser;
-use super::error::Error;
+use super::error::{Error, ErrorCode, PathSegment};
+use super::map::{self, Map};
 
 #[derive(Clone, PartialEq)]
 pub enum Value {
@@ -18,7 +19,7 @@ pub enum Value {
     F64(f64),
     String(String),
     Array(Vec<Value>),
-    Object(BTreeMap<String, Value>),
+    Object(Map),
 }
 
 impl Value {
@@ -45,6 +46,128 @@ impl Value {
         Some(target)
     }
 
+    /// Looks up a value by a JSON Pointer, as defined in RFC 6901, such as `/a/b/0/c`. Object
+    /// keys are unescaped, so `~1` stands for `/` and `~0` for `~`, and array elements are
+    /// addressed by their index. The empty pointer refers to the whole `Value`.
+    /// Returns None if there is no value at the pointer.
+    pub fn pointer<'a>(&'a self, pointer: &str) -> Option<&'a Value> {
+        let tokens = match pointer_tokens(pointer) {
+            Some(tokens) => tokens,
+            None => { return None; }
+        };
+
+        let mut target = self;
+
+        for token in tokens {
+            target = match *target {
+                Value::Object(ref map) => {
+                    match map.get(&token) {
+                        Some(value) => value,
+                        None => { return None; }
+                    }
+                }
+                Value::Array(ref list) => {
+                    match pointer_index(&token) {
+                        Some(index) if index < list.len() => &list[index],
+                        _ => { return None; }
+                    }
+                }
+                _ => { return None; }
+            };
+        }
+
+        Some(target)
+    }
+
+    /// Looks up a value by a JSON Pointer like `pointer`, returning a mutable reference.
+    pub fn pointer_mut<'a>(&'a mut self, pointer: &str) -> Option<&'a mut Value> {
+        let tokens = match pointer_tokens(pointer) {
+            Some(tokens) => tokens,
+            None => { return None; }
+        };
+
+        let mut target = self;
+
+        for token in tokens {
+            let current = target;
+            target = match *current {
+                Value::Object(ref mut map) => {
+                    match map.get_mut(&token) {
+                        Some(value) => value,
+                        None => { return None; }
+                    }
+                }
+                Value::Array(ref mut list) => {
+                    match pointer_index(&token) {
+                        Some(index) if index < list.len() => &mut list[index],
+                        _ => { return None; }
+                    }
+                }
+                _ => { return None; }
+            };
+        }
+
+        Some(target)
+    }
+
+    /// Inserts `value` at a JSON Pointer. The pointer's parent has to exist already. If it is an
+    /// Object, `value` is added under the last key, replacing any value already there. If it is
+    /// an Array, `value` is inserted before the element at the last index, or appended when the
+    /// index is the array's length or `-`. The empty pointer replaces the whole `Value`.
+    ///
+    /// Returns the value that was replaced, if any, or hands `value` back if there is nowhere to
+    /// insert it.
+    pub fn insert_at_pointer(&mut self, pointer: &str, value: Value)
+                             -> Result<Option<Value>, Value> {
+        if pointer.is_empty() {
+            return Ok(Some(mem::replace(self, value)));
+        }
+
+        let (parent, token) = match split_pointer(pointer) {
+            Some(split) => split,
+            None => { return Err(value); }
+        };
+
+        match self.pointer_mut(parent) {
+            Some(&mut Value::Object(ref mut map)) => Ok(map.insert(token, value)),
+            Some(&mut Value::Array(ref mut list)) => {
+                let index = if token == "-" {
+                    list.len()
+                } else {
+                    match pointer_index(&token) {
+                        Some(index) if index <= list.len() => index,
+                        _ => { return Err(value); }
+                    }
+                };
+
+                list.insert(index, value);
+                Ok(None)
+            }
+            _ => Err(value),
+        }
+    }
+
+    /// Removes the value at a JSON Pointer and returns it. Removing an Array element shifts the
+    /// elements after it down. Returns None if there is no value at the pointer, or if the
+    /// pointer is empty, since the whole `Value` can't be removed from itself.
+    pub fn remove_at_pointer(&mut self, pointer: &str) -> Option<Value> {
+        let (parent, token) = match split_pointer(pointer) {
+            Some(split) => split,
+            None => { return None; }
+        };
+
+        match self.pointer_mut(parent) {
+            Some(&mut Value::Object(ref mut map)) => map.remove(&token),
+            Some(&mut Value::Array(ref mut list)) => {
+                match pointer_index(&token) {
+                    Some(index) if index < list.len() => Some(list.remove(index)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
     /// If the `Value` is an Object, performs a depth-first search until
     /// a value associated with the provided key is found. If no value is found
     /// or the `Value` is not an Object, returns None.
@@ -73,18 +196,18 @@ impl Value {
         self.as_object().is_some()
     }
 
-    /// If the `Value` is an Object, returns the associated BTreeMap.
+    /// If the `Value` is an Object, returns the associated Map.
     /// Returns None otherwise.
-    pub fn as_object<'a>(&'a self) -> Option<&'a BTreeMap<String, Value>> {
+    pub fn as_object<'a>(&'a self) -> Option<&'a Map> {
         match self {
             &Value::Object(ref map) => Some(map),
             _ => None
         }
     }
 
-    /// If the `Value` is an Object, returns the associated mutable BTreeMap.
+    /// If the `Value` is an Object, returns the associated mutable Map.
     /// Returns None otherwise.
-    pub fn as_object_mut<'a>(&'a mut self) -> Option<&'a mut BTreeMap<String, Value>> {
+    pub fn as_object_mut<'a>(&'a mut self) -> Option<&'a mut Map> {
         match self {
             &mut Value::Object(ref mut map) => Some(map),
             _ => None
@@ -220,6 +343,43 @@ impl Value {
     }
 }
 
+/// Splits a JSON Pointer into its unescaped reference tokens, or returns None if it is not a
+/// valid pointer.
+fn pointer_tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        Some(vec![])
+    } else if pointer.starts_with("/") {
+        Some(pointer[1..].split('/').map(unescape_pointer_token).collect())
+    } else {
+        None
+    }
+}
+
+/// Splits a non-empty JSON Pointer into the pointer to its parent and its unescaped last
+/// reference token.
+fn split_pointer(pointer: &str) -> Option<(&str, String)> {
+    if !pointer.starts_with("/") {
+        return None;
+    }
+
+    let index = pointer.rfind('/').unwrap();
+    Some((&pointer[..index], unescape_pointer_token(&pointer[index + 1..])))
+}
+
+fn unescape_pointer_token(token: &str) -> String {
+    // `~1` has to be replaced first, so that `~01` becomes `~1` rather than `/`.
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Parses an array index, which may not have a sign or leading zeros.
+fn pointer_index(token: &str) -> Option<usize> {
+    if token.starts_with("+") || (token.starts_with("0") && token.len() != 1) {
+        None
+    } else {
+        token.parse().ok()
+    }
+}
+
 impl ser::Serialize for Value {
     #[inline]
     fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
@@ -313,7 +473,14 @@ impl de::Deserialize for Value {
             fn visit_map<V>(&mut self, visitor: V) -> Result<Value, V::Error>
                 where V: de::MapVisitor,
             {
-                let values = try!(de::impls::BTreeMapVisitor::new().visit_map(visitor));
+                let mut values = Map::new();
+
+                while let Some((key, value)) = try!(visitor.visit()) {
+                    values.insert(key, value);
+                }
+
+                try!(visitor.end());
+
                 Ok(Value::Object(values))
             }
         }
@@ -351,7 +518,7 @@ impl fmt::Debug for Value {
 enum State {
     Value(Value),
     Array(Vec<Value>),
-    Object(BTreeMap<String, Value>),
+    Object(Map),
 }
 
 pub struct Serializer {
@@ -374,16 +541,16 @@ impl Serializer {
 }
 
 impl ser::Serializer for Serializer {
-    type Error = ();
+    type Error = Error;
 
     #[inline]
-    fn visit_bool(&mut self, value: bool) -> Result<(), ()> {
+    fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
         self.state.push(State::Value(Value::Bool(value)));
         Ok(())
     }
 
     #[inline]
-    fn visit_i64(&mut self, value: i64) -> Result<(), ()> {
+    fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
         if value < 0 {
             self.state.push(State::Value(Value::I64(value)));
         } else {
@@ -393,50 +560,50 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn visit_u64(&mut self, value: u64) -> Result<(), ()> {
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
         self.state.push(State::Value(Value::U64(value)));
         Ok(())
     }
 
     #[inline]
-    fn visit_f64(&mut self, value: f64) -> Result<(), ()> {
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
         self.state.push(State::Value(Value::F64(value as f64)));
         Ok(())
     }
 
     #[inline]
-    fn visit_char(&mut self, value: char) -> Result<(), ()> {
+    fn visit_char(&mut self, value: char) -> Result<(), Error> {
         self.state.push(State::Value(Value::String(value.to_string())));
         Ok(())
     }
 
     #[inline]
-    fn visit_str(&mut self, value: &str) -> Result<(), ()> {
+    fn visit_str(&mut self, value: &str) -> Result<(), Error> {
         self.state.push(State::Value(Value::String(value.to_string())));
         Ok(())
     }
 
     #[inline]
-    fn visit_none(&mut self) -> Result<(), ()> {
+    fn visit_none(&mut self) -> Result<(), Error> {
         self.visit_unit()
     }
 
     #[inline]
-    fn visit_some<V>(&mut self, value: V) -> Result<(), ()>
+    fn visit_some<V>(&mut self, value: V) -> Result<(), Error>
         where V: ser::Serialize,
     {
         value.serialize(self)
     }
 
     #[inline]
-    fn visit_unit(&mut self) -> Result<(), ()> {
+    fn visit_unit(&mut self) -> Result<(), Error> {
         self.state.push(State::Value(Value::Null));
         Ok(())
     }
 
     #[inline]
-    fn visit_enum_unit(&mut self, _name: &str, variant: &str) -> Result<(), ()> {
-        let mut values = BTreeMap::new();
+    fn visit_enum_unit(&mut self, _name: &str, variant: &str) -> Result<(), Error> {
+        let mut values = Map::new();
         values.insert(variant.to_string(), Value::Array(vec![]));
 
         self.state.push(State::Value(Value::Object(values)));
@@ -445,7 +612,7 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn visit_seq<V>(&mut self, mut visitor: V) -> Result<(), ()>
+    fn visit_seq<V>(&mut self, mut visitor: V) -> Result<(), Error>
         where V: ser::SeqVisitor,
     {
         let len = visitor.len().unwrap_or(0);
@@ -466,7 +633,7 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn visit_enum_seq<V>(&mut self, _name: &str, variant: &str, visitor: V) -> Result<(), ()>
+    fn visit_enum_seq<V>(&mut self, _name: &str, variant: &str, visitor: V) -> Result<(), Error>
         where V: ser::SeqVisitor,
     {
         try!(self.visit_seq(visitor));
@@ -476,7 +643,30 @@ impl ser::Serializer for Serializer {
             state => panic!("expected value, found {:?}", state),
         };
 
-        let mut object = BTreeMap::new();
+        let mut object = Map::new();
+
+        object.insert(variant.to_string(), value);
+
+        self.state.push(State::Value(Value::Object(object)));
+
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_newtype_variant<T>(&mut self,
+                                _name: &str,
+                                variant: &str,
+                                value: T) -> Result<(), Error>
+        where T: ser::Serialize,
+    {
+        try!(value.serialize(self));
+
+        let value = match self.state.pop().unwrap() {
+            State::Value(value) => value,
+            state => panic!("expected value, found {:?}", state),
+        };
+
+        let mut object = Map::new();
 
         object.insert(variant.to_string(), value);
 
@@ -486,7 +676,7 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn visit_seq_elt<T>(&mut self, value: T) -> Result<(), ()>
+    fn visit_seq_elt<T>(&mut self, value: T) -> Result<(), Error>
         where T: ser::Serialize,
     {
         try!(value.serialize(self));
@@ -505,10 +695,10 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn visit_map<V>(&mut self, mut visitor: V) -> Result<(), ()>
+    fn visit_map<V>(&mut self, mut visitor: V) -> Result<(), Error>
         where V: ser::MapVisitor,
     {
-        let values = BTreeMap::new();
+        let values = Map::new();
 
         self.state.push(State::Object(values));
 
@@ -525,7 +715,7 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn visit_enum_map<V>(&mut self, _name: &str, variant: &str, visitor: V) -> Result<(), ()>
+    fn visit_enum_map<V>(&mut self, _name: &str, variant: &str, visitor: V) -> Result<(), Error>
         where V: ser::MapVisitor,
     {
         try!(self.visit_map(visitor));
@@ -535,7 +725,7 @@ impl ser::Serializer for Serializer {
             state => panic!("expected value, found {:?}", state),
         };
 
-        let mut object = BTreeMap::new();
+        let mut object = Map::new();
 
         object.insert(variant.to_string(), value);
 
@@ -545,7 +735,7 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn visit_map_elt<K, V>(&mut self, key: K, value: V) -> Result<(), ()>
+    fn visit_map_elt<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
         where K: ser::Serialize,
               V: ser::Serialize,
     {
@@ -553,6 +743,9 @@ impl ser::Serializer for Serializer {
 
         let key = match self.state.pop().unwrap() {
             State::Value(Value::String(value)) => value,
+            State::Value(_) => {
+                return Err(Error::SerializeError(ErrorCode::KeyMustBeAString));
+            }
             state => panic!("expected key, found {:?}", state),
         };
 
@@ -610,6 +803,7 @@ impl de::Deserializer for Deserializer {
                     de: self,
                     iter: v.into_iter(),
                     len: len,
+                    index: 0,
                 })
             }
             Value::Object(v) => {
@@ -618,6 +812,7 @@ impl de::Deserializer for Deserializer {
                     de: self,
                     iter: v.into_iter(),
                     value: None,
+                    key: None,
                     len: len,
                 })
             }
@@ -656,6 +851,7 @@ impl de::Deserializer for Deserializer {
                     de: self,
                     iter: fields.into_iter(),
                     len: len,
+                    index: 0,
                 }))
             }
             Some((variant, Value::Object(fields))) => {
@@ -664,10 +860,17 @@ impl de::Deserializer for Deserializer {
                     de: self,
                     iter: fields.into_iter(),
                     value: Some(Value::String(variant)),
+                    key: None,
                     len: len,
                 }))
             }
-            Some(_) => { return Err(de::Error::syntax_error()); }
+            Some((variant, value)) => {
+                try!(visitor.visit(VariantDeserializer {
+                    de: self,
+                    variant: Some(Value::String(variant)),
+                    value: Some(value),
+                }))
+            }
             None => { return Err(de::Error::syntax_error()); }
         };
 
@@ -682,6 +885,7 @@ struct SeqDeserializer<'a> {
     de: &'a mut Deserializer,
     iter: vec::IntoIter<Value>,
     len: usize,
+    index: usize,
 }
 
 impl<'a> de::Deserializer for SeqDeserializer<'a> {
@@ -709,7 +913,13 @@ impl<'a> de::SeqVisitor for SeqDeserializer<'a> {
             Some(value) => {
                 self.len -= 1;
                 self.de.value = Some(value);
-                Ok(Some(try!(de::Deserialize::deserialize(self.de))))
+                match de::Deserialize::deserialize(self.de) {
+                    Ok(value) => {
+                        self.index += 1;
+                        Ok(Some(value))
+                    }
+                    Err(err) => Err(err.at(PathSegment::Index(self.index))),
+                }
             }
             None => Ok(None),
         }
@@ -742,12 +952,55 @@ impl<'a> de::VariantVisitor for SeqDeserializer<'a> {
     {
         de::Deserializer::visit(self, visitor)
     }
+
+    fn visit_newtype<T>(&mut self) -> Result<T, Error>
+        where T: de::Deserialize,
+    {
+        let values = self.iter.by_ref().collect();
+        self.len = 0;
+        self.de.value = Some(Value::Array(values));
+        de::Deserialize::deserialize(self.de)
+    }
+}
+
+/// The `VariantVisitor` of an enum whose value is neither an array nor an object, which can only
+/// be a newtype variant.
+struct VariantDeserializer<'a> {
+    de: &'a mut Deserializer,
+    variant: Option<Value>,
+    value: Option<Value>,
+}
+
+impl<'a> de::VariantVisitor for VariantDeserializer<'a> {
+    type Error = Error;
+
+    fn visit_variant<V>(&mut self) -> Result<V, Error>
+        where V: de::Deserialize,
+    {
+        self.de.value = self.variant.take();
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn visit_value<V>(&mut self, visitor: V) -> Result<V::Value, Error>
+        where V: de::Visitor,
+    {
+        self.de.value = self.value.take();
+        de::Deserializer::visit(self.de, visitor)
+    }
+
+    fn visit_newtype<T>(&mut self) -> Result<T, Error>
+        where T: de::Deserialize,
+    {
+        self.de.value = self.value.take();
+        de::Deserialize::deserialize(self.de)
+    }
 }
 
 struct MapDeserializer<'a> {
     de: &'a mut Deserializer,
-    iter: btree_map::IntoIter<String, Value>,
+    iter: map::IntoIter,
     value: Option<Value>,
+    key: Option<String>,
     len: usize,
 }
 
@@ -761,6 +1014,7 @@ impl<'a> de::MapVisitor for MapDeserializer<'a> {
             Some((key, value)) => {
                 self.len -= 1;
                 self.value = Some(value);
+                self.key = Some(key.clone());
                 self.de.value = Some(Value::String(key));
                 Ok(Some(try!(de::Deserialize::deserialize(self.de))))
             }
@@ -773,7 +1027,15 @@ impl<'a> de::MapVisitor for MapDeserializer<'a> {
     {
         let value = self.value.take().unwrap();
         self.de.value = Some(value);
-        Ok(try!(de::Deserialize::deserialize(self.de)))
+        match de::Deserialize::deserialize(self.de) {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                match self.key.take() {
+                    Some(key) => Err(err.at(PathSegment::Key(key))),
+                    None => Err(err),
+                }
+            }
+        }
     }
 
     fn end(&mut self) -> Result<(), Error> {
@@ -784,7 +1046,7 @@ impl<'a> de::MapVisitor for MapDeserializer<'a> {
         }
     }
 
-    fn missing_field<V>(&mut self, _field: &'static str) -> Result<V, Error>
+    fn missing_field<V>(&mut self, field: &'static str) -> Result<V, Error>
         where V: de::Deserialize,
     {
         // See if the type can deserialize from a unit.
@@ -806,7 +1068,10 @@ impl<'a> de::MapVisitor for MapDeserializer<'a> {
             }
         }
 
-        Ok(try!(de::Deserialize::deserialize(&mut UnitDeserializer)))
+        match de::Deserialize::deserialize(&mut UnitDeserializer) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(de::Error::missing_field_error(field)),
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -841,15 +1106,29 @@ impl<'a> de::VariantVisitor for MapDeserializer<'a> {
     {
         de::Deserializer::visit(self, visitor)
     }
+
+    fn visit_newtype<T>(&mut self) -> Result<T, Error>
+        where T: de::Deserialize,
+    {
+        let mut object = Map::new();
+
+        for (key, value) in self.iter.by_ref() {
+            object.insert(key, value);
+        }
+
+        self.len = 0;
+        self.de.value = Some(Value::Object(object));
+        de::Deserialize::deserialize(self.de)
+    }
 }
 
 /// Shortcut function to encode a `T` into a JSON `Value`
-pub fn to_value<T>(value: &T) -> Value
+pub fn to_value<T>(value: &T) -> Result<Value, Error>
     where T: ser::Serialize
 {
     let mut ser = Serializer::new();
-    value.serialize(&mut ser).ok().unwrap();
-    ser.unwrap()
+    try!(value.serialize(&mut ser));
+    Ok(ser.unwrap())
 }
 
 /// Shortcut function to decode a JSON `Value` into a `T`
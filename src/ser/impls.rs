@@ -1,11 +1,14 @@
+use std::borrow::{Cow, ToOwned};
 use std::collections::hash_state::HashState;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecMap};
 use std::hash::Hash;
+use std::marker::PhantomData;
 use std::path;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use super::{
+    Error,
     Serialize,
     Serializer,
     SeqVisitor,
@@ -174,6 +177,15 @@ impl Serialize for () {
     }
 }
 
+impl<T> Serialize for PhantomData<T> {
+    #[inline]
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer,
+    {
+        serializer.visit_unit()
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
 // FIXME(rust #19630) Remove this work-around
@@ -388,6 +400,114 @@ impl<K, V, I> MapVisitor for MapIteratorVisitor<I>
 
 ///////////////////////////////////////////////////////////////////////////////
 
+/// A `Serializer` that writes the entries of a map or struct into the map its inner serializer
+/// is in the middle of. Derived `Serialize` impls use it for `#[serde(flatten)]` fields.
+///
+/// `None` and `()` flatten to no entries at all, and every other kind of value is an error.
+pub struct FlatMapSerializer<'a, S: 'a> {
+    serializer: &'a mut S,
+}
+
+impl<'a, S> FlatMapSerializer<'a, S>
+    where S: Serializer,
+{
+    #[inline]
+    pub fn new(serializer: &'a mut S) -> FlatMapSerializer<'a, S> {
+        FlatMapSerializer {
+            serializer: serializer,
+        }
+    }
+}
+
+impl<'a, S> FlatMapSerializer<'a, S>
+    where S: Serializer,
+{
+    fn unsupported(&self) -> Result<(), S::Error> {
+        Err(Error::custom("can only flatten structs and maps"))
+    }
+}
+
+impl<'a, S> Serializer for FlatMapSerializer<'a, S>
+    where S: Serializer,
+{
+    type Error = S::Error;
+
+    fn visit_bool(&mut self, _v: bool) -> Result<(), S::Error> {
+        self.unsupported()
+    }
+
+    fn visit_i64(&mut self, _v: i64) -> Result<(), S::Error> {
+        self.unsupported()
+    }
+
+    fn visit_u64(&mut self, _v: u64) -> Result<(), S::Error> {
+        self.unsupported()
+    }
+
+    fn visit_f64(&mut self, _v: f64) -> Result<(), S::Error> {
+        self.unsupported()
+    }
+
+    fn visit_str(&mut self, _value: &str) -> Result<(), S::Error> {
+        self.unsupported()
+    }
+
+    fn visit_unit(&mut self) -> Result<(), S::Error> {
+        Ok(())
+    }
+
+    fn visit_enum_unit(&mut self, _name: &str, _variant: &str) -> Result<(), S::Error> {
+        self.unsupported()
+    }
+
+    fn visit_none(&mut self) -> Result<(), S::Error> {
+        Ok(())
+    }
+
+    fn visit_some<V>(&mut self, value: V) -> Result<(), S::Error>
+        where V: Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn visit_seq<V>(&mut self, _visitor: V) -> Result<(), S::Error>
+        where V: SeqVisitor,
+    {
+        self.unsupported()
+    }
+
+    fn visit_seq_elt<T>(&mut self, _value: T) -> Result<(), S::Error>
+        where T: Serialize,
+    {
+        self.unsupported()
+    }
+
+    fn visit_map<V>(&mut self, mut visitor: V) -> Result<(), S::Error>
+        where V: MapVisitor,
+    {
+        while let Some(()) = try!(visitor.visit(self)) { }
+        Ok(())
+    }
+
+    fn visit_enum_map<V>(&mut self,
+                         _name: &'static str,
+                         _variant: &'static str,
+                         _visitor: V) -> Result<(), S::Error>
+        where V: MapVisitor,
+    {
+        self.unsupported()
+    }
+
+    fn visit_map_elt<K, V>(&mut self, key: K, value: V) -> Result<(), S::Error>
+        where K: Serialize,
+              V: Serialize,
+    {
+        self.serializer.visit_map_elt(key, value)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
 impl<K, V> Serialize for BTreeMap<K, V>
     where K: Serialize + Ord,
           V: Serialize,
@@ -471,6 +591,15 @@ impl<T> Serialize for Arc<T> where T: Serialize, {
     }
 }
 
+impl<'a, T: ?Sized> Serialize for Cow<'a, T> where T: Serialize + ToOwned, {
+    #[inline]
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
 impl Serialize for path::Path {
@@ -6,6 +6,15 @@ pub mod impls;
 
 ///////////////////////////////////////////////////////////////////////////////
 
+/// `Error` is a trait that allows a `Serialize` to generically create a `Serializer` error.
+pub trait Error: Sized {
+    /// Raised when a `Serialize` was unable to serialize a value, such as a map key the format
+    /// cannot represent.
+    fn custom<T: Into<String>>(msg: T) -> Self;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
 pub trait Serialize {
     fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
         where S: Serializer;
@@ -14,7 +23,7 @@ pub trait Serialize {
 ///////////////////////////////////////////////////////////////////////////////
 
 pub trait Serializer {
-    type Error;
+    type Error: Error;
 
     /// `visit_bool` serializes a `bool` value.
     fn visit_bool(&mut self, v: bool) -> Result<(), Self::Error>;
@@ -127,6 +136,34 @@ pub trait Serializer {
         self.visit_unit()
     }
 
+    /// `visit_newtype_struct` serializes a struct that wraps a single value, such as
+    /// `struct Meters(f64)`. By default the wrapper is transparent and only the value is
+    /// serialized.
+    #[inline]
+    fn visit_newtype_struct<T>(&mut self,
+                               _name: &'static str,
+                               value: T) -> Result<(), Self::Error>
+        where T: Serialize,
+              Self: Sized,
+    {
+        value.serialize(self)
+    }
+
+    /// `visit_newtype_variant` serializes an enum variant that wraps a single value. By default
+    /// it is serialized as a tuple variant with one field.
+    #[inline]
+    fn visit_newtype_variant<T>(&mut self,
+                                name: &'static str,
+                                variant: &'static str,
+                                value: T) -> Result<(), Self::Error>
+        where T: Serialize,
+    {
+        self.visit_enum_seq(
+            name,
+            variant,
+            impls::SeqIteratorVisitor::new(Some(value).into_iter(), Some(1)))
+    }
+
     fn visit_none(&mut self) -> Result<(), Self::Error>;
 
     fn visit_some<V>(&mut self, value: V) -> Result<(), Self::Error>
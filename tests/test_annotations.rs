@@ -4,6 +4,11 @@
 extern crate test;
 extern crate serde;
 
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use serde::de::TryFrom;
+use serde::de::impls::IgnoredAny;
 use serde::json;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -38,3 +43,666 @@ fn test_rename() {
     let deserialized_value: Rename = json::from_str(&serialized_value).unwrap();
     assert_eq!(value, deserialized_value);
 }
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Skip {
+    a1: i32,
+    #[serde(skip_serializing)]
+    a2: i32,
+    #[serde(skip_deserializing)]
+    a3: i32,
+    #[serde(skip_serializing_if="Option::is_none")]
+    a4: Option<i32>,
+    #[serde(skip_serializing_if="Vec::is_empty", default)]
+    a5: Vec<i32>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum SkipEnum {
+    Variant {
+        a1: i32,
+        #[serde(skip_serializing_if="Option::is_none")]
+        a2: Option<i32>,
+    },
+}
+
+#[test]
+fn test_skip_serializing() {
+    let value = Skip { a1: 1, a2: 2, a3: 3, a4: None, a5: vec![] };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"a1\":1,\"a3\":3}");
+
+    let value = Skip { a1: 1, a2: 2, a3: 3, a4: Some(4), a5: vec![5] };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"a1\":1,\"a3\":3,\"a4\":4,\"a5\":[5]}");
+
+    // The reported length has to match the number of fields written.
+    let value = Skip { a1: 1, a2: 2, a3: 3, a4: None, a5: vec![5] };
+    assert_eq!(
+        json::to_value(&value).unwrap(),
+        json::from_str("{\"a1\":1,\"a3\":3,\"a5\":[5]}").unwrap());
+
+    let value = SkipEnum::Variant { a1: 1, a2: None };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"Variant\":{\"a1\":1}}");
+
+    let value = SkipEnum::Variant { a1: 1, a2: Some(2) };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"Variant\":{\"a1\":1,\"a2\":2}}");
+}
+
+#[test]
+fn test_skip_deserializing() {
+    let deserialized_value: Skip = json::from_str(&"{\"a1\":1,\"a2\":2,\"a4\":4}").unwrap();
+    assert_eq!(deserialized_value, Skip { a1: 1, a2: 2, a3: 0, a4: Some(4), a5: vec![] });
+
+    // Skipped fields are ignored like any other unknown key.
+    let deserialized_value: Skip = json::from_str(&"{\"a1\":1,\"a2\":2,\"a3\":3}").unwrap();
+    assert_eq!(deserialized_value, Skip { a1: 1, a2: 2, a3: 0, a4: None, a5: vec![] });
+}
+
+fn serialize_as_string<S>(value: &i32, serializer: &mut S) -> Result<(), S::Error>
+    where S: serde::Serializer,
+{
+    serde::Serialize::serialize(&value.to_string(), serializer)
+}
+
+fn deserialize_from_string<D>(deserializer: &mut D) -> Result<i32, D::Error>
+    where D: serde::Deserializer,
+{
+    let value: String = try!(serde::Deserialize::deserialize(deserializer));
+    value.parse().map_err(|_| serde::de::Error::invalid_value("expected an integer string"))
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct With<T> {
+    a1: T,
+    #[serde(serialize_with="serialize_as_string", deserialize_with="deserialize_from_string")]
+    a2: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum WithEnum {
+    Variant {
+        #[serde(serialize_with="serialize_as_string", deserialize_with="deserialize_from_string")]
+        a1: i32,
+    },
+}
+
+#[test]
+fn test_serialize_with() {
+    let value = With { a1: true, a2: 2 };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"a1\":true,\"a2\":\"2\"}");
+
+    let value = WithEnum::Variant { a1: 1 };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"Variant\":{\"a1\":\"1\"}}");
+}
+
+#[test]
+fn test_deserialize_with() {
+    let deserialized_value: With<bool> = json::from_str(&"{\"a1\":true,\"a2\":\"2\"}").unwrap();
+    assert_eq!(deserialized_value, With { a1: true, a2: 2 });
+
+    assert!(json::from_str::<With<bool>>(&"{\"a1\":true,\"a2\":2}").is_err());
+
+    let deserialized_value: WithEnum = json::from_str(&"{\"Variant\":{\"a1\":\"1\"}}").unwrap();
+    assert_eq!(deserialized_value, WithEnum::Variant { a1: 1 });
+}
+
+fn default_a2() -> i32 {
+    5
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct DefaultPath {
+    a1: i32,
+    #[serde(default="default_a2")]
+    a2: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct ContainerDefault {
+    a1: i32,
+    a2: String,
+    #[serde(default="default_a2")]
+    a3: i32,
+}
+
+impl std::default::Default for ContainerDefault {
+    fn default() -> ContainerDefault {
+        ContainerDefault { a1: 1, a2: "a".to_string(), a3: 3 }
+    }
+}
+
+#[test]
+fn test_default_path() {
+    let deserialized_value: DefaultPath = json::from_str(&"{\"a1\":1,\"a2\":2}").unwrap();
+    assert_eq!(deserialized_value, DefaultPath { a1: 1, a2: 2 });
+
+    let deserialized_value: DefaultPath = json::from_str(&"{\"a1\":1}").unwrap();
+    assert_eq!(deserialized_value, DefaultPath { a1: 1, a2: 5 });
+}
+
+#[test]
+fn test_container_default() {
+    let deserialized_value: ContainerDefault = json::from_str(&"{}").unwrap();
+    assert_eq!(deserialized_value, ContainerDefault { a1: 1, a2: "a".to_string(), a3: 5 });
+
+    let deserialized_value: ContainerDefault = json::from_str(&"{\"a2\":\"b\"}").unwrap();
+    assert_eq!(deserialized_value, ContainerDefault { a1: 1, a2: "b".to_string(), a3: 5 });
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DenyUnknown {
+    a1: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum UnknownEnum {
+    Variant {
+        a1: i32,
+    },
+}
+
+#[test]
+fn test_ignore_unknown_fields() {
+    let deserialized_value: Rename = json::from_str(
+        &"{\"a0\":[1,{\"b\":[null,\"c\"]}],\"a1\":1,\"a2\":{},\"a3\":2}").unwrap();
+    assert_eq!(deserialized_value, Rename { a1: 1, a2: 2 });
+
+    let deserialized_value: UnknownEnum = json::from_str(
+        &"{\"Variant\":{\"a0\":true,\"a1\":1}}").unwrap();
+    assert_eq!(deserialized_value, UnknownEnum::Variant { a1: 1 });
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    let deserialized_value: DenyUnknown = json::from_str(&"{\"a1\":1}").unwrap();
+    assert_eq!(deserialized_value, DenyUnknown { a1: 1 });
+
+    let err = json::from_str::<DenyUnknown>(&"{\"a1\":1,\"a2\":2}").unwrap_err();
+    assert!(err.to_string().contains("unknown field \"a2\", expected one of \"a1\""));
+}
+
+#[test]
+fn test_ignored_any() {
+    let values = vec![
+        "null",
+        "true",
+        "-5",
+        "2.5",
+        "\"a\"",
+        "[1,[2,[3]],{\"a\":[]}]",
+        "{\"a\":{\"b\":[null,{}]},\"c\":1}",
+    ];
+
+    for value in values {
+        assert_eq!(json::from_str::<IgnoredAny>(value).unwrap(), IgnoredAny);
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+struct RenameAllCamel {
+    first_field: i32,
+    #[serde(rename="second")]
+    second_field: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="SCREAMING_SNAKE_CASE")]
+struct RenameAllScreaming {
+    first_field: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="kebab-case")]
+enum RenameAllKebab {
+    UnitVariant,
+    #[serde(rename="renamed")]
+    SeqVariant(i32),
+    MapVariant { inner_field: i32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="lowercase")]
+enum RenameAllLower {
+    UnitVariant,
+}
+
+#[test]
+fn test_rename_all() {
+    let value = RenameAllCamel { first_field: 1, second_field: 2 };
+    let s = "{\"firstField\":1,\"second\":2}";
+    assert_eq!(json::to_string(&value).unwrap(), s);
+    assert_eq!(json::from_str::<RenameAllCamel>(s).unwrap(), value);
+
+    let value = RenameAllScreaming { first_field: 1 };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"FIRST_FIELD\":1}");
+    assert_eq!(json::from_str::<RenameAllScreaming>("{\"FIRST_FIELD\":1}").unwrap(), value);
+
+    let tests = vec![
+        (RenameAllKebab::UnitVariant, "{\"unit-variant\":[]}"),
+        (RenameAllKebab::SeqVariant(1), "{\"renamed\":1}"),
+        (RenameAllKebab::MapVariant { inner_field: 1 }, "{\"map-variant\":{\"inner_field\":1}}"),
+    ];
+
+    for (value, s) in tests {
+        assert_eq!(json::to_string(&value).unwrap(), s);
+        assert_eq!(json::from_str::<RenameAllKebab>(s).unwrap(), value);
+    }
+
+    let value = RenameAllLower::UnitVariant;
+    assert_eq!(json::to_string(&value).unwrap(), "{\"unitvariant\":[]}");
+    assert_eq!(json::from_str::<RenameAllLower>("{\"unitvariant\":[]}").unwrap(), value);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct RenameDirections {
+    #[serde(rename(serialize="a1_out", deserialize="a1_in"))]
+    a1: i32,
+    #[serde(rename(serialize="a2_out"))]
+    a2: i32,
+    #[serde(rename="a3_new", alias="a3_old", alias="a3_older")]
+    a3: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum AliasEnum {
+    #[serde(rename(serialize="new", deserialize="old"), alias="older")]
+    Variant,
+}
+
+#[test]
+fn test_rename_directions() {
+    let value = RenameDirections { a1: 1, a2: 2, a3: 3 };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"a1_out\":1,\"a2_out\":2,\"a3_new\":3}");
+
+    let deserialized_value: RenameDirections = json::from_str(
+        &"{\"a1_in\":1,\"a2\":2,\"a3_new\":3}").unwrap();
+    assert_eq!(deserialized_value, value);
+
+    // The serialized names aren't accepted unless they're also deserialized names.
+    assert!(json::from_str::<RenameDirections>(&"{\"a1_out\":1,\"a2\":2,\"a3_new\":3}").is_err());
+
+    assert_eq!(json::to_string(&AliasEnum::Variant).unwrap(), "{\"new\":[]}");
+    assert_eq!(json::from_str::<AliasEnum>(&"{\"old\":[]}").unwrap(), AliasEnum::Variant);
+    assert_eq!(json::from_str::<AliasEnum>(&"{\"older\":[]}").unwrap(), AliasEnum::Variant);
+}
+
+#[test]
+fn test_alias() {
+    for key in vec!["a3_new", "a3_old", "a3_older"] {
+        let s = format!("{{\"a1_in\":1,\"a2\":2,\"{}\":3}}", key);
+        let deserialized_value: RenameDirections = json::from_str(&s).unwrap();
+        assert_eq!(deserialized_value, RenameDirections { a1: 1, a2: 2, a3: 3 });
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag="type")]
+enum InternallyTagged {
+    Unit,
+    Struct { a: i32, b: Option<String>, c: Vec<i32> },
+    #[serde(rename="renamed")]
+    Renamed { a: i32 },
+}
+
+#[test]
+fn test_internally_tagged() {
+    let tests = vec![
+        (InternallyTagged::Unit, "{\"type\":\"Unit\"}"),
+        (
+            InternallyTagged::Struct { a: 1, b: Some("x".to_string()), c: vec![2, 3] },
+            "{\"type\":\"Struct\",\"a\":1,\"b\":\"x\",\"c\":[2,3]}",
+        ),
+        (InternallyTagged::Renamed { a: 1 }, "{\"type\":\"renamed\",\"a\":1}"),
+    ];
+
+    for (value, s) in tests {
+        assert_eq!(json::to_string(&value).unwrap(), s);
+        assert_eq!(json::from_str::<InternallyTagged>(s).unwrap(), value);
+    }
+
+    // The tag doesn't have to be the first entry.
+    let deserialized_value: InternallyTagged = json::from_str(
+        &"{\"a\":1,\"b\":null,\"type\":\"Struct\",\"c\":[]}").unwrap();
+    assert_eq!(deserialized_value, InternallyTagged::Struct { a: 1, b: None, c: vec![] });
+
+    // Missing `Option` fields are still `None` after buffering.
+    let deserialized_value: InternallyTagged = json::from_str(
+        &"{\"c\":[],\"a\":1,\"type\":\"Struct\"}").unwrap();
+    assert_eq!(deserialized_value, InternallyTagged::Struct { a: 1, b: None, c: vec![] });
+
+    assert!(json::from_str::<InternallyTagged>(&"{\"a\":1}").is_err());
+    let err = json::from_str::<InternallyTagged>(&"{\"type\":\"Other\"}").unwrap_err();
+    assert!(err.to_string().contains(
+        "unknown variant \"Other\", expected one of \"Unit\", \"Struct\", \"renamed\""));
+    assert!(json::from_str::<InternallyTagged>(&"{\"type\":\"Unit\",\"type\":\"Unit\"}").is_err());
+    assert!(json::from_str::<InternallyTagged>(&"{\"type\":\"Struct\",\"a\":1}").is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag="type", deny_unknown_fields)]
+enum InternallyTaggedDenyUnknown {
+    Unit,
+    Struct { a: i32 },
+}
+
+#[test]
+fn test_internally_tagged_deny_unknown_fields() {
+    let deserialized_value: InternallyTaggedDenyUnknown = json::from_str(
+        &"{\"type\":\"Unit\"}").unwrap();
+    assert_eq!(deserialized_value, InternallyTaggedDenyUnknown::Unit);
+
+    let err = json::from_str::<InternallyTaggedDenyUnknown>(
+        &"{\"type\":\"Unit\",\"a\":1}").unwrap_err();
+    assert!(err.to_string().contains("unknown field \"a\", there are no fields"));
+
+    let err = json::from_str::<InternallyTaggedDenyUnknown>(
+        &"{\"type\":\"Struct\",\"a\":1,\"b\":2}").unwrap_err();
+    assert!(err.to_string().contains("unknown field \"b\", expected one of \"a\""));
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag="t", content="c")]
+enum AdjacentlyTagged {
+    Unit,
+    Newtype(i32),
+    Tuple(i32, String),
+    Struct { a: i32 },
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    let tests = vec![
+        (AdjacentlyTagged::Unit, "{\"t\":\"Unit\"}"),
+        (AdjacentlyTagged::Newtype(1), "{\"t\":\"Newtype\",\"c\":1}"),
+        (AdjacentlyTagged::Tuple(1, "x".to_string()), "{\"t\":\"Tuple\",\"c\":[1,\"x\"]}"),
+        (AdjacentlyTagged::Struct { a: 1 }, "{\"t\":\"Struct\",\"c\":{\"a\":1}}"),
+    ];
+
+    for (value, s) in tests {
+        assert_eq!(json::to_string(&value).unwrap(), s);
+        assert_eq!(json::from_str::<AdjacentlyTagged>(s).unwrap(), value);
+    }
+
+    // The content may come before the tag, and other keys are ignored.
+    let deserialized_value: AdjacentlyTagged = json::from_str(
+        &"{\"c\":[1,\"x\"],\"other\":0,\"t\":\"Tuple\"}").unwrap();
+    assert_eq!(deserialized_value, AdjacentlyTagged::Tuple(1, "x".to_string()));
+
+    let deserialized_value: AdjacentlyTagged = json::from_str(
+        &"{\"c\":[],\"t\":\"Unit\"}").unwrap();
+    assert_eq!(deserialized_value, AdjacentlyTagged::Unit);
+
+    assert!(json::from_str::<AdjacentlyTagged>(&"{\"c\":[1]}").is_err());
+    let err = json::from_str::<AdjacentlyTagged>(&"{\"t\":\"Other\"}").unwrap_err();
+    assert!(err.to_string().contains("unknown variant \"Other\""));
+    assert!(json::from_str::<AdjacentlyTagged>(&"{\"t\":\"Newtype\"}").is_err());
+    assert!(json::from_str::<AdjacentlyTagged>(
+        &"{\"t\":\"Newtype\",\"c\":1,\"c\":2}").is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Untagged {
+    Unit,
+    Number(i64),
+    String(String),
+    Pair(i64, String),
+    Object { a: i32, b: Option<String> },
+}
+
+#[test]
+fn test_untagged() {
+    let tests = vec![
+        (Untagged::Unit, "null"),
+        (Untagged::Number(1), "1"),
+        (Untagged::String("x".to_string()), "\"x\""),
+        (Untagged::Pair(1, "x".to_string()), "[1,\"x\"]"),
+        (Untagged::Object { a: 1, b: Some("x".to_string()) }, "{\"a\":1,\"b\":\"x\"}"),
+    ];
+
+    for (value, s) in tests {
+        assert_eq!(json::to_string(&value).unwrap(), s);
+        assert_eq!(json::from_str::<Untagged>(s).unwrap(), value);
+    }
+
+    let deserialized_value: Untagged = json::from_str(&"{\"a\":1}").unwrap();
+    assert_eq!(deserialized_value, Untagged::Object { a: 1, b: None });
+
+    let err = json::from_str::<Untagged>(&"[1]").unwrap_err();
+    assert!(err.to_string().contains("did not match any variant of untagged enum Untagged"));
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Header {
+    id: i32,
+    source: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Event {
+    #[serde(flatten)]
+    header: Header,
+    kind: String,
+    #[serde(flatten)]
+    extra: BTreeMap<String, json::Value>,
+}
+
+#[test]
+fn test_flatten() {
+    let mut extra = BTreeMap::new();
+    extra.insert("level".to_string(), json::Value::U64(3));
+
+    let value = Event {
+        header: Header { id: 1, source: Some("x".to_string()) },
+        kind: "click".to_string(),
+        extra: extra,
+    };
+
+    let s = "{\"id\":1,\"source\":\"x\",\"kind\":\"click\",\"level\":3}";
+    assert_eq!(json::to_string(&value).unwrap(), s);
+    assert_eq!(json::from_str::<Event>(s).unwrap(), value);
+
+    // The flattened struct's keys don't end up in the flattened map, whatever their order.
+    let deserialized_value: Event = json::from_str(
+        &"{\"level\":3,\"kind\":\"click\",\"id\":1}").unwrap();
+    assert_eq!(deserialized_value.header, Header { id: 1, source: None });
+    assert_eq!(deserialized_value.extra.keys().collect::<Vec<_>>(), vec!["level"]);
+
+    assert!(json::from_str::<Event>(&"{\"kind\":\"click\"}").is_err());
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Note {
+    note: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Place {
+    name: String,
+    #[serde(flatten)]
+    point: Option<Point>,
+    #[serde(flatten)]
+    note: Option<Note>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, json::Value>,
+}
+
+#[test]
+fn test_flatten_option() {
+    let value: Place = json::from_str(&"{\"name\":\"a\",\"x\":1,\"y\":2,\"note\":\"b\"}").unwrap();
+    assert_eq!(value.point, Some(Point { x: 1, y: 2 }));
+    assert_eq!(value.note, Some(Note { note: Some("b".to_string()) }));
+    assert!(value.extra.is_empty());
+
+    // None of the keys are present.
+    let value: Place = json::from_str(&"{\"name\":\"a\"}").unwrap();
+    assert_eq!(value.point, None);
+    assert_eq!(value.note, None);
+
+    // The point can't be built, so its entry is left for the flattened map.
+    let value: Place = json::from_str(&"{\"name\":\"a\",\"x\":1,\"z\":3}").unwrap();
+    assert_eq!(value.point, None);
+    assert_eq!(value.extra.keys().collect::<Vec<_>>(), vec!["x", "z"]);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Email {
+    address: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Scores(Vec<i32>);
+
+#[test]
+fn test_transparent() {
+    let value = Email { address: "a@example.com".to_string() };
+    assert_eq!(json::to_string(&value).unwrap(), "\"a@example.com\"");
+    assert_eq!(json::from_str::<Email>("\"a@example.com\"").unwrap(), value);
+
+    let value = Scores(vec![1, 2]);
+    assert_eq!(json::to_string(&value).unwrap(), "[1,2]");
+    assert_eq!(json::from_str::<Scores>("[1,2]").unwrap(), value);
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+struct Lowercase(String);
+
+impl From<String> for Lowercase {
+    fn from(s: String) -> Lowercase {
+        Lowercase(s.to_lowercase())
+    }
+}
+
+impl From<Lowercase> for String {
+    fn from(s: Lowercase) -> String {
+        s.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+struct Port(u16);
+
+impl TryFrom<u16> for Port {
+    type Err = String;
+
+    fn try_from(port: u16) -> Result<Port, String> {
+        if port == 0 {
+            Err("port must not be 0".to_string())
+        } else {
+            Ok(Port(port))
+        }
+    }
+}
+
+impl From<Port> for u16 {
+    fn from(port: Port) -> u16 {
+        port.0
+    }
+}
+
+#[test]
+fn test_from_into() {
+    assert_eq!(json::to_string(&Lowercase("abc".to_string())).unwrap(), "\"abc\"");
+    assert_eq!(json::from_str::<Lowercase>("\"ABC\"").unwrap(), Lowercase("abc".to_string()));
+
+    assert_eq!(json::to_string(&Port(80)).unwrap(), "80");
+    assert_eq!(json::from_str::<Port>("80").unwrap(), Port(80));
+    assert_eq!(json::from_value::<Port>(json::to_value(&Port(80)).unwrap()).unwrap(), Port(80));
+
+    let err = json::from_str::<Port>("0").unwrap_err();
+    assert!(err.to_string().contains("port must not be 0"));
+}
+
+/// Neither `Serialize` nor `Deserialize`, which a container converted to and from another type
+/// doesn't need.
+#[derive(Clone, Debug, PartialEq)]
+struct Opaque;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+struct Named<T> {
+    name: String,
+    marker: PhantomData<T>,
+}
+
+impl<T> From<String> for Named<T> {
+    fn from(name: String) -> Named<T> {
+        Named { name: name, marker: PhantomData }
+    }
+}
+
+impl<T> From<Named<T>> for String {
+    fn from(named: Named<T>) -> String {
+        named.name
+    }
+}
+
+#[test]
+fn test_from_into_generic() {
+    let value: Named<Opaque> = Named { name: "a".to_string(), marker: PhantomData };
+    assert_eq!(json::to_string(&value).unwrap(), "\"a\"");
+    assert_eq!(json::from_str::<Named<Opaque>>("\"a\"").unwrap(), value);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(repr = "u8")]
+enum Protocol {
+    Unknown,
+    Http10,
+    Http11 = 5,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(as_str, rename = "Cache", rename_all = "lowercase")]
+enum CacheStatus {
+    Miss,
+    #[serde(alias = "found")]
+    Hit,
+}
+
+#[test]
+fn test_c_like_enum() {
+    let tests = vec![
+        (Protocol::Unknown, "0"),
+        (Protocol::Http10, "1"),
+        (Protocol::Http11, "5"),
+    ];
+
+    for (value, s) in tests {
+        assert_eq!(json::to_string(&value).unwrap(), s);
+        assert_eq!(json::from_str::<Protocol>(s).unwrap(), value);
+    }
+
+    assert_eq!(json::from_str::<Protocol>("\"Http11\"").unwrap(), Protocol::Http11);
+    assert_eq!(json::from_value::<Protocol>(json::to_value(&Protocol::Http11).unwrap()).unwrap(),
+               Protocol::Http11);
+
+    assert!(json::from_str::<Protocol>("2").is_err());
+    assert!(json::from_str::<Protocol>("256").is_err());
+    assert!(json::from_str::<Protocol>("-1").is_err());
+    assert!(json::from_str::<Protocol>("\"Http20\"").is_err());
+
+    assert_eq!(json::to_string(&CacheStatus::Hit).unwrap(), "\"hit\"");
+    assert_eq!(json::from_str::<CacheStatus>("\"hit\"").unwrap(), CacheStatus::Hit);
+    assert_eq!(json::from_str::<CacheStatus>("\"found\"").unwrap(), CacheStatus::Hit);
+    assert_eq!(json::from_str::<CacheStatus>("0").unwrap(), CacheStatus::Miss);
+
+    let err = json::from_str::<Protocol>("2").unwrap_err();
+    assert!(err.to_string().contains("2 is not a discriminant of enum Protocol"));
+
+    // Errors use the renamed name of the enum.
+    let err = json::from_str::<CacheStatus>("\"stale\"").unwrap_err();
+    assert!(err.to_string().contains("unknown variant `stale` of enum Cache"));
+}
@@ -21,6 +21,10 @@ impl serde::de::Error for Error {
     fn missing_field_error(_field: &'static str) -> Error { Error }
 }
 
+impl serde::ser::Error for Error {
+    fn custom<T: Into<String>>(_msg: T) -> Error { Error }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
 struct BytesSerializer {
@@ -6,19 +6,33 @@ extern crate serde;
 
 use std::fmt::Debug;
 use std::collections::BTreeMap;
+use std::io;
+use std::iter::repeat;
 
 use serde::de;
 use serde::ser;
 
 use serde::json::{
     self,
+    IoRead,
+    Map,
+    SliceRead,
+    StreamDeserializer,
     Value,
     from_str,
     from_value,
     to_value,
 };
 
-use serde::json::error::{Error, ErrorCode};
+use serde::json::error::{Error, ErrorCode, Path, PathSegment};
+
+macro_rules! map {
+    ($($k:expr => $v:expr),*) => ({
+        let mut _m = Map::new();
+        $(_m.insert($k, $v);)*
+        _m
+    })
+}
 
 macro_rules! treemap {
     ($($k:expr => $v:expr),*) => ({
@@ -57,7 +71,7 @@ fn test_encode_ok<T>(errors: &[(T, &str)])
         let s = json::to_string(value).unwrap();
         assert_eq!(s, out);
 
-        let v = to_value(&value);
+        let v = to_value(&value).unwrap();
         let s = json::to_string(&v).unwrap();
         assert_eq!(s, out);
     }
@@ -72,7 +86,7 @@ fn test_pretty_encode_ok<T>(errors: &[(T, &str)])
         let s = json::to_string_pretty(value).unwrap();
         assert_eq!(s, out);
 
-        let v = to_value(&value);
+        let v = to_value(&value).unwrap();
         let s = json::to_string_pretty(&v).unwrap();
         assert_eq!(s, out);
     }
@@ -435,10 +449,10 @@ fn test_write_object() {
         ),
     ]);
 
-    let complex_obj = Value::Object(treemap!(
+    let complex_obj = Value::Object(map!(
         "b".to_string() => Value::Array(vec![
-            Value::Object(treemap!("c".to_string() => Value::String("\x0c\r".to_string()))),
-            Value::Object(treemap!("d".to_string() => Value::String("".to_string())))
+            Value::Object(map!("c".to_string() => Value::String("\x0c\r".to_string()))),
+            Value::Object(map!("d".to_string() => Value::String("".to_string())))
         ])
     ));
 
@@ -622,6 +636,62 @@ fn test_write_option() {
     ]);
 }
 
+#[test]
+fn test_write_custom_error() {
+    struct Finite(f64);
+
+    impl ser::Serialize for Finite {
+        fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+            where S: ser::Serializer,
+        {
+            if self.0.is_finite() {
+                serializer.visit_f64(self.0)
+            } else {
+                Err(ser::Error::custom("value must be finite"))
+            }
+        }
+    }
+
+    assert_eq!(json::to_string(&Finite(1.5)).unwrap(), "1.5");
+
+    match json::to_string(&vec![Finite(1.5), Finite(std::f64::NAN)]) {
+        Err(Error::SerializeError(ErrorCode::Custom(ref msg))) => {
+            assert_eq!(msg, "value must be finite");
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    let mut map = BTreeMap::new();
+    map.insert(1, 2);
+
+    let mut ser = json::value::Serializer::new();
+    match ser::Serialize::serialize(&map, &mut ser) {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString)) => { }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_write_non_string_key() {
+    let mut map = BTreeMap::new();
+    map.insert(1, 2);
+
+    match json::to_string(&map) {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString)) => { }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    match to_value(&map) {
+        Err(Error::SerializeError(ErrorCode::KeyMustBeAString)) => { }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    let mut map = BTreeMap::new();
+    map.insert('a', 2);
+
+    assert_eq!(json::to_string(&map).unwrap(), "{\"a\":2}");
+}
+
 fn test_parse_ok<T>(errors: Vec<(&'static str, T)>)
     where T: Clone + Debug + PartialEq + ser::Serialize + de::Deserialize,
 {
@@ -629,9 +699,16 @@ fn test_parse_ok<T>(errors: Vec<(&'static str, T)>)
         let v: T = from_str(s).unwrap();
         assert_eq!(v, value.clone());
 
+        // Make sure the other kinds of input agree.
+        let v: T = json::de::from_reader(s.as_bytes()).unwrap();
+        assert_eq!(v, value.clone());
+
+        let v: T = json::de::from_iter(s.bytes().map(Ok)).unwrap();
+        assert_eq!(v, value.clone());
+
         // Make sure we can deserialize into a `Value`.
         let json_value: Value = from_str(s).unwrap();
-        assert_eq!(json_value, to_value(&value));
+        assert_eq!(json_value, to_value(&value).unwrap());
 
         // Make sure we can deserialize from a `Value`.
         let v: T = from_value(json_value.clone()).unwrap();
@@ -648,19 +725,34 @@ fn test_parse_err<T>(errors: Vec<(&'static str, Error)>)
     where T: Debug + PartialEq + de::Deserialize,
 {
     for (s, err) in errors {
-        match (err, from_str::<T>(s).unwrap_err()) {
-            (
-                Error::SyntaxError(expected_code, expected_line, expected_col),
-                Error::SyntaxError(actual_code, actual_line, actual_col),
-            ) => {
-                assert_eq!(
-                    (expected_code, expected_line, expected_col),
-                    (actual_code, actual_line, actual_col)
-                )
-            }
-            (expected_err, actual_err) => {
-                panic!("unexpected errors {} != {}", expected_err, actual_err)
-            }
+        assert_error_eq(&err, from_str::<T>(s).unwrap_err());
+
+        // Make sure the other kinds of input agree.
+        assert_error_eq(&err, json::de::from_reader::<_, T>(s.as_bytes()).unwrap_err());
+        assert_error_eq(&err, json::de::from_iter::<_, T>(s.bytes().map(Ok)).unwrap_err());
+    }
+}
+
+fn assert_error_eq(expected: &Error, actual: Error) {
+    match (expected, actual) {
+        (
+            &Error::SyntaxError(ref expected_code, expected_line, expected_col),
+            Error::SyntaxError(actual_code, actual_line, actual_col),
+        ) => {
+            assert_eq!(
+                (expected_code, expected_line, expected_col),
+                (&actual_code, actual_line, actual_col)
+            )
+        }
+        (
+            &Error::PathError(ref expected_path, ref expected_err),
+            Error::PathError(actual_path, actual_err),
+        ) => {
+            assert_eq!(expected_path, &actual_path);
+            assert_error_eq(expected_err, *actual_err);
+        }
+        (expected_err, actual_err) => {
+            panic!("unexpected errors {} != {}", expected_err, actual_err)
         }
     }
 }
@@ -870,9 +962,12 @@ fn test_parse_object() {
 #[test]
 fn test_parse_struct() {
     test_parse_err::<Outer>(vec![
-        ("5", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 2)),
-        ("\"hello\"", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 8)),
-        ("{\"inner\": true}", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 15)),
+        ("5", Error::SyntaxError(ErrorCode::InvalidType("struct Outer", de::Type::U64), 1, 2)),
+        ("\"hello\"", Error::SyntaxError(ErrorCode::InvalidType("struct Outer", de::Type::Str), 1, 8)),
+        ("{\"inner\": true}", Error::PathError(
+            Path::new(vec![PathSegment::Key("inner".to_string())]),
+            Box::new(Error::SyntaxError(
+                ErrorCode::InvalidType("sequence", de::Type::Bool), 1, 15)))),
     ]);
 
     test_parse_ok(vec![
@@ -935,11 +1030,20 @@ fn test_parse_enum_errors() {
         ("{}", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 2)),
         ("{\"Dog\":", Error::SyntaxError(ErrorCode::EOFWhileParsingValue, 1, 8)),
         ("{\"Dog\":}", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 8)),
-        ("{\"unknown\":[]}", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 11)),
-        ("{\"Dog\":{}}", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 9)),
-        ("{\"Frog\":{}}", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 10)),
-        ("{\"Cat\":[]}", Error::SyntaxError(ErrorCode::ExpectedSomeValue, 1, 9)),
+        ("{\"unknown\":[]}", Error::SyntaxError(
+            ErrorCode::UnknownVariant("unknown".to_string(), &["Dog", "Frog", "Cat"]), 1, 11)),
+        ("{\"Dog\":{}}", Error::SyntaxError(ErrorCode::InvalidType("unit", de::Type::Map), 1, 9)),
+        ("{\"Frog\":{}}", Error::SyntaxError(
+            ErrorCode::InvalidType("tuple variant Animal::Frog", de::Type::Map), 1, 10)),
+        ("{\"Cat\":[]}", Error::SyntaxError(
+            ErrorCode::InvalidType("struct variant Animal::Cat", de::Type::Seq), 1, 9)),
     ]);
+
+    let err = from_str::<Animal>("{\"unknown\":[]}").unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "unknown variant \"unknown\", expected one of \"Dog\", \"Frog\", \"Cat\" \
+         at line 1 column 11");
 }
 
 #[test]
@@ -981,6 +1085,29 @@ fn test_parse_enum() {
     ]);
 }
 
+#[test]
+fn test_parse_type_errors() {
+    test_parse_err::<u32>(vec![
+        ("\"a\"", Error::SyntaxError(ErrorCode::InvalidType("u32", de::Type::Str), 1, 4)),
+        ("[]", Error::SyntaxError(ErrorCode::InvalidType("u32", de::Type::Seq), 1, 2)),
+        ("-1", Error::SyntaxError(
+            ErrorCode::InvalidValue("-1 is out of range for u32".to_string()), 1, 3)),
+    ]);
+
+    test_parse_err::<(u64, u64)>(vec![
+        ("[1]", Error::SyntaxError(
+            ErrorCode::InvalidLength(1, "a tuple of length 2".to_string()), 1, 3)),
+    ]);
+
+    let err = from_str::<(u64, u64)>("[1]").unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "invalid length 1, expected a tuple of length 2 at line 1 column 3");
+
+    let err = from_str::<u32>("\"a\"").unwrap_err();
+    assert_eq!(format!("{}", err), "expected u32, found string at line 1 column 4");
+}
+
 #[test]
 fn test_parse_trailing_whitespace() {
     test_parse_ok(vec![
@@ -998,6 +1125,119 @@ fn test_multiline_errors() {
     ]);
 }
 
+#[test]
+fn test_parse_error_path() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Tls {
+        cert_path: String,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Server {
+        tls: Tls,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Config {
+        servers: Vec<Server>,
+    }
+
+    let path = Path::new(vec![
+        PathSegment::Key("servers".to_string()),
+        PathSegment::Index(1),
+        PathSegment::Key("tls".to_string()),
+        PathSegment::Key("cert_path".to_string()),
+    ]);
+
+    let s = "{\"servers\": [{\"tls\": {\"cert_path\": \"a\"}}, {\"tls\": {\"cert_path\": 5}}]}";
+
+    test_parse_err::<Config>(vec![
+        (s, Error::PathError(
+            path.clone(),
+            Box::new(Error::SyntaxError(ErrorCode::InvalidType("string", de::Type::U64), 1, 66)))),
+        ("{\"servers\": [{\"tls\": {}}]}", Error::PathError(
+            Path::new(vec![
+                PathSegment::Key("servers".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Key("tls".to_string()),
+            ]),
+            Box::new(Error::SyntaxError(ErrorCode::MissingField("cert_path"), 1, 23)))),
+    ]);
+
+    let err = from_str::<Config>(s).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        ".servers[1].tls.cert_path: expected string, found u64 at line 1 column 66");
+
+    let value: Value = from_str(s).unwrap();
+    let err = from_value::<Config>(value).unwrap_err();
+    assert_eq!(err.path(), Some(&path));
+}
+
+#[test]
+fn test_parse_error_path_buffered() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(tag="type")]
+    enum Shape {
+        Circle { radius: u32 },
+        Polygon { points: Vec<u32> },
+    }
+
+    // The enum is buffered before its variant is known, so the path inside it is recorded as
+    // the buffered content is replayed.
+    let s = concat!(
+        "[{\"type\": \"Circle\", \"radius\": 1}, ",
+        "{\"type\": \"Polygon\", \"points\": [1, true]}]");
+    let path = Path::new(vec![
+        PathSegment::Index(1),
+        PathSegment::Key("points".to_string()),
+        PathSegment::Index(1),
+    ]);
+
+    let err = from_str::<Vec<Shape>>(s).unwrap_err();
+    assert_eq!(err.path(), Some(&path));
+    assert_eq!(
+        format!("{}", err),
+        "[1].points[1]: expected u32, found bool at line 1 column 75");
+}
+
+#[test]
+fn test_error_path_display() {
+    let path = Path::new(vec![
+        PathSegment::Key("servers".to_string()),
+        PathSegment::Key("a.b".to_string()),
+        PathSegment::Index(0),
+        PathSegment::Key("[x]".to_string()),
+        PathSegment::Key("".to_string()),
+    ]);
+
+    assert_eq!(format!("{}", path), ".servers[\"a.b\"][0][\"[x]\"][\"\"]");
+}
+
+#[test]
+fn test_parse_error_path_keys() {
+    // Keys are borrowed from in-memory input unless they contain escapes, and are always copied
+    // from readers, so check each kind of input reports the same path.
+    let tests = vec![
+        ("{\"a\": 1, \"plain\": true}", "plain"),
+        ("{\"a\": 1, \"esc\\\"aped\": true}", "esc\"aped"),
+        ("{\"a\": 1, \"\": true}", ""),
+    ];
+
+    for (s, key) in tests {
+        let path = Path::new(vec![PathSegment::Key(key.to_string())]);
+
+        let err = from_str::<BTreeMap<String, u32>>(s).unwrap_err();
+        assert_eq!(err.path(), Some(&path));
+
+        let err = json::de::from_reader::<_, BTreeMap<String, u32>>(s.as_bytes()).unwrap_err();
+        assert_eq!(err.path(), Some(&path));
+
+        let err = json::de::from_iter::<_, BTreeMap<String, u32>>(s.bytes().map(Ok)).unwrap_err();
+        assert_eq!(err.path(), Some(&path));
+    }
+}
+
 #[test]
 fn test_missing_field() {
     #[derive(Debug, PartialEq, Deserialize)]
@@ -1011,11 +1251,210 @@ fn test_missing_field() {
     let value: Foo = from_str("{\"x\": 5}").unwrap();
     assert_eq!(value, Foo { x: Some(5) });
 
-    let value: Foo = from_value(Value::Object(treemap!())).unwrap();
+    let value: Foo = from_value(Value::Object(map!())).unwrap();
     assert_eq!(value, Foo { x: None });
 
-    let value: Foo = from_value(Value::Object(treemap!(
+    let value: Foo = from_value(Value::Object(map!(
         "x".to_string() => Value::I64(5)
     ))).unwrap();
     assert_eq!(value, Foo { x: Some(5) });
 }
+
+#[test]
+fn test_duplicate_field() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        a: u32,
+        #[serde(rename="B")]
+        b: Option<u32>,
+    }
+
+    match from_str::<Foo>("{\"a\":1,\"a\":2}") {
+        Err(Error::SyntaxError(ErrorCode::DuplicateField("a"), _, _)) => { }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    match from_str::<Foo>("{\"a\":1,\"B\":null,\"B\":2}") {
+        Err(Error::SyntaxError(ErrorCode::DuplicateField("B"), _, _)) => { }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_parse_borrowed() {
+    let s = "\"abc\"";
+    let v: &str = from_str(s).unwrap();
+    assert_eq!(v, "abc");
+    assert_eq!(v.as_ptr(), s[1..].as_ptr());
+
+    let v: &[u8] = json::de::from_slice(b"\"abc\"").unwrap();
+    assert_eq!(v, &b"abc"[..]);
+
+    // Escapes have to be decoded, so the string can no longer be borrowed from the input.
+    match from_str::<&str>("\"a\\nb\"") {
+        Err(Error::SyntaxError(ErrorCode::InvalidValue(ref msg), 1, 7)) => {
+            assert!(msg.contains("cannot be borrowed"));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // Owned types still accept both kinds of strings.
+    test_parse_ok(vec![
+        ("\"abc\"", "abc".to_string()),
+        ("\"a\\nb\"", "a\nb".to_string()),
+    ]);
+
+    // Keys with escapes are still reported in error paths.
+    test_parse_err::<BTreeMap<String, u32>>(vec![
+        ("{\"a\\nb\": true}", Error::PathError(
+            Path::new(vec![PathSegment::Key("a\nb".to_string())]),
+            Box::new(Error::SyntaxError(ErrorCode::InvalidType("u32", de::Type::Bool), 1, 14)))),
+    ]);
+}
+
+#[test]
+fn test_parse_reader_buffer_boundary() {
+    // Long enough for strings and whitespace to straddle the reader's buffer.
+    let long = repeat("a\\n").take(5000).collect::<String>();
+    let s = format!("[{}\"{}\", \n{}\"x\"]", repeat(' ').take(5000).collect::<String>(), long,
+                    repeat('\t').take(5000).collect::<String>());
+
+    let expected = vec![long.replace("\\n", "\n"), "x".to_string()];
+
+    let v: Vec<String> = from_str(&s).unwrap();
+    assert_eq!(v, expected);
+
+    let v: Vec<String> = json::de::from_reader(s.as_bytes()).unwrap();
+    assert_eq!(v, expected);
+
+    let err = json::de::from_reader::<_, Vec<String>>(format!("{}1", s).as_bytes()).unwrap_err();
+    assert_error_eq(&Error::SyntaxError(ErrorCode::TrailingCharacters, 2, 5006), err);
+}
+
+#[test]
+fn test_stream_deserializer() {
+    let s = "{\"x\": 1}\n{\"x\": 2} [3]\"four\"\n\n";
+
+    let mut stream = StreamDeserializer::<Value, _>::new(SliceRead::new(s.as_bytes())).unwrap();
+
+    assert_eq!(stream.next().unwrap().unwrap(), Value::Object(map!(
+        "x".to_string() => Value::U64(1)
+    )));
+    assert_eq!(stream.value_offset(), 0);
+
+    assert_eq!(stream.next().unwrap().unwrap(), Value::Object(map!(
+        "x".to_string() => Value::U64(2)
+    )));
+    assert_eq!(stream.value_offset(), 9);
+
+    assert_eq!(stream.next().unwrap().unwrap(), Value::Array(vec![Value::U64(3)]));
+    assert_eq!(stream.value_offset(), 18);
+
+    assert_eq!(stream.next().unwrap().unwrap(), Value::String("four".to_string()));
+    assert_eq!(stream.value_offset(), 21);
+
+    assert!(stream.next().is_none());
+
+    // Iteration stops at the first error.
+    let mut stream = StreamDeserializer::<u64, _>::new(IoRead::new(&b"1 true 2"[..])).unwrap();
+
+    assert_eq!(stream.next().unwrap().unwrap(), 1);
+    assert_error_eq(
+        &Error::SyntaxError(ErrorCode::InvalidType("u64", de::Type::Bool), 1, 7),
+        stream.next().unwrap().unwrap_err());
+    assert_eq!(stream.value_offset(), 2);
+    assert!(stream.next().is_none());
+}
+
+/// A reader that returns its data and then fails with `WouldBlock`, like a non-blocking socket
+/// that is waiting for more input.
+struct WouldBlockReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> io::Read for WouldBlockReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.data.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "no data yet"));
+        }
+
+        let len = if buf.len() < self.data.len() { buf.len() } else { self.data.len() };
+        for (dst, src) in buf.iter_mut().zip(self.data[..len].iter()) {
+            *dst = *src;
+        }
+
+        self.data = &self.data[len..];
+        Ok(len)
+    }
+}
+
+#[test]
+fn test_stream_deserializer_would_block() {
+    // Creating the stream doesn't wait for input.
+    let reader = WouldBlockReader { data: &b""[..] };
+    assert!(StreamDeserializer::<Value, _>::new(IoRead::new(reader)).is_ok());
+
+    // A complete value is returned without reading the byte after it.
+    let docs = vec![
+        ("{\"x\": 1}", Value::Object(map!("x".to_string() => Value::U64(1)))),
+        ("[1, 2]", Value::Array(vec![Value::U64(1), Value::U64(2)])),
+        ("\"a\\nb\"", Value::String("a\nb".to_string())),
+        ("true", Value::Bool(true)),
+        ("null", Value::Null),
+    ];
+
+    for (s, expected) in docs {
+        let reader = WouldBlockReader { data: s.as_bytes() };
+        let mut stream = StreamDeserializer::<Value, _>::new(IoRead::new(reader)).unwrap();
+
+        assert_eq!(stream.next().unwrap().unwrap(), expected);
+
+        match stream.next() {
+            Some(Err(Error::IoError(ref err))) if err.kind() == io::ErrorKind::WouldBlock => { }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    // The same goes for input that ends right after the value.
+    let mut stream = StreamDeserializer::<Value, _>::new(IoRead::new(&b"[1]"[..])).unwrap();
+    assert_eq!(stream.next().unwrap().unwrap(), Value::Array(vec![Value::U64(1)]));
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn test_map_order() {
+    let s = "{\"b\":1,\"a\":2,\"c\":3}";
+    let mut value: Value = from_str(s).unwrap();
+
+    {
+        let keys: Vec<&str> = value.as_object().unwrap().keys().map(|key| &key[..]).collect();
+
+        if cfg!(feature = "preserve_order") {
+            assert_eq!(keys, vec!["b", "a", "c"]);
+            assert_eq!(json::to_string(&value).unwrap(), s);
+        } else {
+            assert_eq!(keys, vec!["a", "b", "c"]);
+            assert_eq!(json::to_string(&value).unwrap(), "{\"a\":2,\"b\":1,\"c\":3}");
+        }
+    }
+
+    let map = value.as_object_mut().unwrap();
+    assert_eq!(map.remove("b"), Some(Value::U64(1)));
+    assert_eq!(map.insert("d".to_string(), Value::U64(4)), None);
+    assert_eq!(map.insert("a".to_string(), Value::U64(5)), Some(Value::U64(2)));
+
+    assert_eq!(map.get("b"), None);
+    assert_eq!(map.get("c"), Some(&Value::U64(3)));
+    assert_eq!(map.get("d"), Some(&Value::U64(4)));
+
+    let entries: Vec<(&str, &Value)> = map.iter().map(|(key, value)| (&key[..], value)).collect();
+    assert_eq!(entries, vec![("a", &Value::U64(5)), ("c", &Value::U64(3)), ("d", &Value::U64(4))]);
+
+    // Equality doesn't depend on the order of the keys.
+    let other: Map = vec![
+        ("d".to_string(), Value::U64(4)),
+        ("c".to_string(), Value::U64(3)),
+        ("a".to_string(), Value::U64(5)),
+    ].into_iter().collect();
+    assert_eq!(*map, other);
+}
@@ -0,0 +1,60 @@
+#[macro_use]
+extern crate serde;
+
+use serde::json::{self, Map, Value};
+
+#[test]
+fn test_json_macro_literals() {
+    assert_eq!(json!(null), Value::Null);
+    assert_eq!(json!(true), Value::Bool(true));
+    assert_eq!(json!(5), Value::U64(5));
+    assert_eq!(json!(-5), Value::I64(-5));
+    assert_eq!(json!(2.5), Value::F64(2.5));
+    assert_eq!(json!("a"), Value::String("a".to_string()));
+    assert_eq!(json!([]), Value::Array(vec![]));
+    assert_eq!(json!({}), Value::Object(Map::new()));
+}
+
+#[test]
+fn test_json_macro_nested() {
+    let x = vec![1u32, 2];
+
+    let value = json!({
+        "a": 1u8,
+        "b": [true, null, x, [], {}],
+        "c": {
+            "d": "e",
+            "f": { "g": null },
+        },
+    });
+
+    let expected: Value = json::from_str(r#"{
+        "a": 1,
+        "b": [true, null, [1, 2], [], {}],
+        "c": {"d": "e", "f": {"g": null}}
+    }"#).unwrap();
+
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn test_json_macro_expressions() {
+    let key = "computed".to_string();
+    let items = vec!["x", "y"];
+
+    let value = json!({
+        key.clone(): items.len() * 2,
+        items[0]: items,
+        "sum": 1u32 + 2,
+        "nested": [items[1], { "k": key == "computed" },],
+    });
+
+    let expected: Value = json::from_str(r#"{
+        "computed": 4,
+        "x": ["x", "y"],
+        "sum": 3,
+        "nested": ["y", {"k": true}]
+    }"#).unwrap();
+
+    assert_eq!(value, expected);
+}
@@ -0,0 +1,213 @@
+extern crate serde;
+
+use serde::json::{self, Value};
+use serde::json::patch::{self, PatchError, PatchErrorKind, PatchOperation};
+
+fn value(s: &str) -> Value {
+    json::from_str(s).unwrap()
+}
+
+fn operations(s: &str) -> Vec<PatchOperation> {
+    json::from_str(s).unwrap()
+}
+
+fn test_patch_ok(tests: Vec<(&'static str, &'static str, &'static str)>) {
+    for (doc, ops, expected) in tests {
+        let mut doc = value(doc);
+        patch::patch(&mut doc, &operations(ops)).unwrap();
+        assert_eq!(doc, value(expected));
+    }
+}
+
+#[test]
+fn test_patch() {
+    // Examples from RFC 6902, appendix A.
+    test_patch_ok(vec![
+        (
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+            r#"{"baz": "qux", "foo": "bar"}"#,
+        ),
+        (
+            r#"{"foo": ["bar", "baz"]}"#,
+            r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+            r#"{"foo": ["bar", "qux", "baz"]}"#,
+        ),
+        (
+            r#"{"baz": "qux", "foo": "bar"}"#,
+            r#"[{"op": "remove", "path": "/baz"}]"#,
+            r#"{"foo": "bar"}"#,
+        ),
+        (
+            r#"{"foo": ["bar", "qux", "baz"]}"#,
+            r#"[{"op": "remove", "path": "/foo/1"}]"#,
+            r#"{"foo": ["bar", "baz"]}"#,
+        ),
+        (
+            r#"{"baz": "qux", "foo": "bar"}"#,
+            r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+            r#"{"baz": "boo", "foo": "bar"}"#,
+        ),
+        (
+            r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+            r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+            r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+        ),
+        (
+            r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+            r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+            r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+        ),
+        (
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]"#,
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+        ),
+        (
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+            r#"{"foo": "bar", "child": {"grandchild": {}}}"#,
+        ),
+        (
+            r#"{"foo": ["bar"]}"#,
+            r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+            r#"{"foo": ["bar", ["abc", "def"]]}"#,
+        ),
+        (
+            r#"{"foo": {"bar": 1}}"#,
+            r#"[{"op": "copy", "from": "/foo", "path": "/baz"}]"#,
+            r#"{"foo": {"bar": 1}, "baz": {"bar": 1}}"#,
+        ),
+    ]);
+}
+
+#[test]
+fn test_patch_error() {
+    let original = value(r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#);
+
+    // A failed patch leaves the document unchanged, even after earlier operations succeeded.
+    let mut doc = original.clone();
+    let err = patch::patch(&mut doc, &operations(r#"[
+        {"op": "add", "path": "/bar", "value": 1},
+        {"op": "test", "path": "/baz", "value": "bar"}
+    ]"#)).unwrap_err();
+
+    assert_eq!(err, PatchError { index: 1, kind: PatchErrorKind::TestFailed("/baz".to_string()) });
+    assert_eq!(err.to_string(), "operation 1: test failed at \"/baz\"");
+    assert_eq!(doc, original);
+
+    let err = patch::patch(&mut doc, &operations(r#"[
+        {"op": "remove", "path": "/foo/3"}
+    ]"#)).unwrap_err();
+    assert_eq!(err, PatchError { index: 0, kind: PatchErrorKind::PathNotFound("/foo/3".to_string()) });
+
+    let err = patch::patch(&mut doc, &operations(r#"[
+        {"op": "add", "path": "/missing/child", "value": 1}
+    ]"#)).unwrap_err();
+    assert_eq!(err, PatchError {
+        index: 0,
+        kind: PatchErrorKind::PathNotFound("/missing/child".to_string()),
+    });
+
+    let err = patch::patch(&mut doc, &operations(r#"[
+        {"op": "move", "from": "/foo", "path": "/foo/0"}
+    ]"#)).unwrap_err();
+    assert_eq!(err, PatchError { index: 0, kind: PatchErrorKind::MoveIntoChild("/foo/0".to_string()) });
+
+    assert_eq!(doc, original);
+}
+
+#[test]
+fn test_patch_test_numbers() {
+    // Numbers are compared by value, however they were parsed.
+    test_patch_ok(vec![
+        (
+            r#"{"a": 1, "b": -2, "c": [1.5, {"d": 3}]}"#,
+            r#"[
+                {"op": "test", "path": "/a", "value": 1.0},
+                {"op": "test", "path": "/b", "value": -2.0},
+                {"op": "test", "path": "/c", "value": [1.5, {"d": 3.0}]}
+            ]"#,
+            r#"{"a": 1, "b": -2, "c": [1.5, {"d": 3}]}"#,
+        ),
+    ]);
+
+    let mut doc = value(r#"{"a": 1}"#);
+    let err = patch::patch(&mut doc, &operations(r#"[
+        {"op": "test", "path": "/a", "value": 1.5}
+    ]"#)).unwrap_err();
+    assert_eq!(err, PatchError { index: 0, kind: PatchErrorKind::TestFailed("/a".to_string()) });
+}
+
+#[test]
+fn test_patch_operation_serialization() {
+    let ops = operations(r#"[
+        {"op": "add", "path": "/a", "value": 1},
+        {"op": "move", "from": "/a", "path": "/b"}
+    ]"#);
+
+    assert_eq!(ops, vec![
+        PatchOperation::Add { path: "/a".to_string(), value: Value::U64(1) },
+        PatchOperation::Move { from: "/a".to_string(), path: "/b".to_string() },
+    ]);
+
+    assert_eq!(
+        json::to_value(&ops).unwrap(),
+        value(r#"[{"op": "add", "path": "/a", "value": 1}, {"op": "move", "from": "/a", "path": "/b"}]"#));
+
+    assert!(json::from_str::<Vec<PatchOperation>>(r#"[{"op": "frob", "path": "/a"}]"#).is_err());
+    assert!(json::from_str::<Vec<PatchOperation>>(r#"[{"op": "add", "path": "/a"}]"#).is_err());
+}
+
+#[test]
+fn test_merge() {
+    // Examples from RFC 7396, appendix A.
+    let tests = vec![
+        (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+        (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+        (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+        (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+        (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+        (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+        (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+        (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+        (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+        (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+        (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+        (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+    ];
+
+    for (doc, patch, expected) in tests {
+        let mut doc = value(doc);
+        patch::merge(&mut doc, &value(patch));
+        assert_eq!(doc, value(expected));
+    }
+}
+
+#[test]
+fn test_diff() {
+    let tests = vec![
+        (r#"{"a": 1}"#, r#"{"a": 1}"#),
+        (r#"{"a": 1, "b": 2}"#, r#"{"a": 3, "c": 4}"#),
+        (r#"{"a/b": {"c~d": [1, 2, 3]}}"#, r#"{"a/b": {"c~d": [1, 5]}}"#),
+        (r#"{"a": [1]}"#, r#"{"a": [1, {"b": 2}, 3]}"#),
+        (r#"[1, 2]"#, r#"{"a": 1}"#),
+    ];
+
+    for (from, to) in tests {
+        let (from, to) = (value(from), value(to));
+        let ops = patch::diff(&from, &to);
+
+        let mut doc = from.clone();
+        patch::patch(&mut doc, &ops).unwrap();
+        assert_eq!(doc, to);
+    }
+
+    assert_eq!(patch::diff(&value(r#"{"a": 1}"#), &value(r#"{"a": 1}"#)), vec![]);
+    assert_eq!(
+        patch::diff(&value(r#"{"a/b": [1, 2]}"#), &value(r#"{"a/b": [1, 3]}"#)),
+        vec![PatchOperation::Replace { path: "/a~1b/1".to_string(), value: Value::U64(3) }]);
+}
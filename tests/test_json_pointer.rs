@@ -0,0 +1,95 @@
+extern crate serde;
+
+use serde::json::{self, Value};
+
+fn rfc_document() -> Value {
+    // The example document from RFC 6901, section 5.
+    json::from_str(r#"{
+        "foo": ["bar", "baz"],
+        "": 0,
+        "a/b": 1,
+        "c%d": 2,
+        "e^f": 3,
+        "g|h": 4,
+        "i\\j": 5,
+        "k\"l": 6,
+        " ": 7,
+        "m~n": 8
+    }"#).unwrap()
+}
+
+#[test]
+fn test_pointer() {
+    let data = rfc_document();
+
+    assert_eq!(data.pointer(""), Some(&data));
+    assert_eq!(data.pointer("/foo"), Some(&json::from_str("[\"bar\", \"baz\"]").unwrap()));
+    assert_eq!(data.pointer("/foo/0"), Some(&Value::String("bar".to_string())));
+    assert_eq!(data.pointer("/"), Some(&Value::U64(0)));
+    assert_eq!(data.pointer("/a~1b"), Some(&Value::U64(1)));
+    assert_eq!(data.pointer("/c%d"), Some(&Value::U64(2)));
+    assert_eq!(data.pointer("/e^f"), Some(&Value::U64(3)));
+    assert_eq!(data.pointer("/g|h"), Some(&Value::U64(4)));
+    assert_eq!(data.pointer("/i\\j"), Some(&Value::U64(5)));
+    assert_eq!(data.pointer("/k\"l"), Some(&Value::U64(6)));
+    assert_eq!(data.pointer("/ "), Some(&Value::U64(7)));
+    assert_eq!(data.pointer("/m~0n"), Some(&Value::U64(8)));
+
+    assert_eq!(data.pointer("foo"), None);
+    assert_eq!(data.pointer("/bar"), None);
+    assert_eq!(data.pointer("/foo/2"), None);
+    assert_eq!(data.pointer("/foo/01"), None);
+    assert_eq!(data.pointer("/foo/-"), None);
+    assert_eq!(data.pointer("/foo/0/x"), None);
+    assert_eq!(data.pointer("/a~01b"), None);
+}
+
+#[test]
+fn test_pointer_mut() {
+    let mut data = rfc_document();
+
+    *data.pointer_mut("/foo/1").unwrap() = Value::Bool(true);
+    *data.pointer_mut("/m~0n").unwrap() = Value::Null;
+
+    assert_eq!(data.pointer("/foo/1"), Some(&Value::Bool(true)));
+    assert_eq!(data.pointer("/m~0n"), Some(&Value::Null));
+    assert!(data.pointer_mut("/foo/2").is_none());
+}
+
+#[test]
+fn test_insert_at_pointer() {
+    let mut data: Value = json::from_str("{\"a\": {\"b\": [1, 2]}}").unwrap();
+
+    assert_eq!(data.insert_at_pointer("/a/c", Value::U64(3)), Ok(None));
+    assert_eq!(data.insert_at_pointer("/a/c", Value::U64(4)), Ok(Some(Value::U64(3))));
+    assert_eq!(data.insert_at_pointer("/a/b/0", Value::U64(0)), Ok(None));
+    assert_eq!(data.insert_at_pointer("/a/b/3", Value::U64(3)), Ok(None));
+    assert_eq!(data.insert_at_pointer("/a/b/-", Value::U64(4)), Ok(None));
+
+    assert_eq!(
+        data,
+        json::from_str("{\"a\": {\"b\": [0, 1, 2, 3, 4], \"c\": 4}}").unwrap());
+
+    assert_eq!(data.insert_at_pointer("/a/b/6", Value::Null), Err(Value::Null));
+    assert_eq!(data.insert_at_pointer("/x/y", Value::Null), Err(Value::Null));
+    assert_eq!(data.insert_at_pointer("/a/c/d", Value::Null), Err(Value::Null));
+    assert_eq!(data.insert_at_pointer("a", Value::Null), Err(Value::Null));
+
+    let old = data.clone();
+    assert_eq!(data.insert_at_pointer("", Value::Null), Ok(Some(old)));
+    assert_eq!(data, Value::Null);
+}
+
+#[test]
+fn test_remove_at_pointer() {
+    let mut data: Value = json::from_str("{\"a\": {\"b\": [1, 2, 3]}, \"c/d\": 4}").unwrap();
+
+    assert_eq!(data.remove_at_pointer("/a/b/1"), Some(Value::U64(2)));
+    assert_eq!(data.remove_at_pointer("/c~1d"), Some(Value::U64(4)));
+
+    assert_eq!(data.remove_at_pointer("/a/b/2"), None);
+    assert_eq!(data.remove_at_pointer("/a/x"), None);
+    assert_eq!(data.remove_at_pointer(""), None);
+
+    assert_eq!(data, json::from_str("{\"a\": {\"b\": [1, 3]}}").unwrap());
+}
@@ -3,16 +3,19 @@
 
 extern crate serde;
 
-use std::collections::BTreeMap;
-use serde::json::{self, Value};
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
 
-macro_rules! btreemap {
+use serde::json::{self, Map, Value};
+
+macro_rules! map {
     () => {
-        BTreeMap::new()
+        Map::new()
     };
     ($($key:expr => $value:expr),+) => {
         {
-            let mut map = BTreeMap::new();
+            let mut map = Map::new();
             $(map.insert($key, $value);)+
             map
         }
@@ -34,6 +37,15 @@ struct SerNamedTuple<'a, 'b, A: 'a, B: 'b, C>(&'a A, &'b mut B, C);
 #[derive(Debug, PartialEq, Deserialize)]
 struct DeNamedTuple<A, B, C>(A, B, C);
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Distance {
+    Meters(f64),
+    Steps(u32, u32),
+}
+
 #[derive(Debug, PartialEq, Serialize)]
 struct SerNamedMap<'a, 'b, A: 'a, B: 'b, C> {
     a: &'a A,
@@ -138,7 +150,7 @@ fn test_named_unit() {
     );
 
     assert_eq!(
-        json::to_value(&named_unit),
+        json::to_value(&named_unit).unwrap(),
         Value::Null
     );
 
@@ -162,7 +174,7 @@ fn test_ser_named_tuple() {
     );
 
     assert_eq!(
-        json::to_value(&named_tuple),
+        json::to_value(&named_tuple).unwrap(),
         Value::Array(vec![Value::U64(5), Value::U64(6), Value::U64(7)])
     );
 }
@@ -186,6 +198,45 @@ fn test_de_named_tuple() {
     );
 }
 
+#[test]
+fn test_newtype_struct() {
+    assert_eq!(json::to_string(&Meters(1.5)).unwrap(), "1.5");
+    assert_eq!(json::to_value(&Meters(1.5)).unwrap(), Value::F64(1.5));
+
+    let v: Meters = json::from_str("1.5").unwrap();
+    assert_eq!(v, Meters(1.5));
+
+    let v: Meters = json::from_value(Value::F64(1.5)).unwrap();
+    assert_eq!(v, Meters(1.5));
+
+    // A one-element sequence is still accepted.
+    let v: Meters = json::from_str("[1.5]").unwrap();
+    assert_eq!(v, Meters(1.5));
+}
+
+#[test]
+fn test_newtype_variant() {
+    let value = Distance::Meters(1.5);
+
+    assert_eq!(json::to_string(&value).unwrap(), "{\"Meters\":1.5}");
+    assert_eq!(
+        json::to_value(&value).unwrap(),
+        Value::Object(map!["Meters".to_string() => Value::F64(1.5)])
+    );
+
+    let v: Distance = json::from_str("{\"Meters\":1.5}").unwrap();
+    assert_eq!(v, value);
+
+    let v: Distance = json::from_value(json::to_value(&value).unwrap()).unwrap();
+    assert_eq!(v, value);
+
+    let value = Distance::Steps(1, 2);
+    assert_eq!(json::to_string(&value).unwrap(), "{\"Steps\":[1,2]}");
+
+    let v: Distance = json::from_str("{\"Steps\":[1,2]}").unwrap();
+    assert_eq!(v, value);
+}
+
 #[test]
 fn test_ser_named_map() {
     let a = 5;
@@ -203,8 +254,8 @@ fn test_ser_named_map() {
     );
 
     assert_eq!(
-        json::to_value(&named_map),
-        Value::Object(btreemap![
+        json::to_value(&named_map).unwrap(),
+        Value::Object(map![
             "a".to_string() => Value::U64(5),
             "b".to_string() => Value::U64(6),
             "c".to_string() => Value::U64(7)
@@ -225,7 +276,7 @@ fn test_de_named_map() {
     ).unwrap();
     assert_eq!(v, v2);
 
-    let v2 = json::from_value(Value::Object(btreemap![
+    let v2 = json::from_value(Value::Object(map![
         "a".to_string() => Value::U64(5),
         "b".to_string() => Value::U64(6),
         "c".to_string() => Value::U64(7)
@@ -241,8 +292,8 @@ fn test_ser_enum_unit() {
     );
 
     assert_eq!(
-        json::to_value(&SerEnum::Unit::<u32, u32, u32>),
-        Value::Object(btreemap!(
+        json::to_value(&SerEnum::Unit::<u32, u32, u32>).unwrap(),
+        Value::Object(map!(
             "Unit".to_string() => Value::Array(vec![]))
         )
     );
@@ -277,8 +328,8 @@ fn test_ser_enum_seq() {
             //d,
             &mut e,
             //e,
-        )),
-        Value::Object(btreemap!(
+        )).unwrap(),
+        Value::Object(map!(
             "Seq".to_string() => Value::Array(vec![
                 Value::U64(1),
                 Value::U64(2),
@@ -320,9 +371,9 @@ fn test_ser_enum_map() {
             //d: d,
             e: &mut e,
             //f: f,
-        }),
-        Value::Object(btreemap!(
-            "Map".to_string() => Value::Object(btreemap![
+        }).unwrap(),
+        Value::Object(map!(
+            "Map".to_string() => Value::Object(map![
                 "a".to_string() => Value::U64(1),
                 "b".to_string() => Value::U64(2),
                 "c".to_string() => Value::U64(3),
@@ -342,7 +393,7 @@ fn test_de_enum_unit() {
         DeEnum::Unit::<u32, u32, u32>
     );
 
-    let v: DeEnum<_, _, _> = json::from_value(Value::Object(btreemap!(
+    let v: DeEnum<_, _, _> = json::from_value(Value::Object(map!(
         "Unit".to_string() => Value::Array(vec![]))
     )).unwrap();
     assert_eq!(
@@ -373,7 +424,7 @@ fn test_de_enum_seq() {
         )
     );
 
-    let v: DeEnum<_, _, _> = json::from_value(Value::Object(btreemap!(
+    let v: DeEnum<_, _, _> = json::from_value(Value::Object(map!(
         "Seq".to_string() => Value::Array(vec![
             Value::U64(1),
             Value::U64(2),
@@ -420,8 +471,8 @@ fn test_de_enum_map() {
         }
     );
 
-    let v: DeEnum<_, _, _> = json::from_value(Value::Object(btreemap!(
-        "Map".to_string() => Value::Object(btreemap![
+    let v: DeEnum<_, _, _> = json::from_value(Value::Object(map!(
+        "Map".to_string() => Value::Object(map![
             "a".to_string() => Value::U64(1),
             "b".to_string() => Value::U64(2),
             "c".to_string() => Value::U64(3),
@@ -443,3 +494,190 @@ fn test_de_enum_map() {
         }
     );
 }
+
+trait Container {
+    type Item: fmt::Debug + PartialEq;
+}
+
+impl Container for Vec<i32> {
+    type Item = i32;
+}
+
+/// Neither `Serialize` nor `Deserialize`.
+#[derive(Debug, Default, PartialEq)]
+struct Opaque;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct AssociatedType<C: Container> {
+    item: C::Item,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Bounds<T, U, V> {
+    value: i32,
+    marker: PhantomData<T>,
+    #[serde(skip_serializing, skip_deserializing)]
+    skipped: U,
+    #[serde(bound(serialize = "V: ::serde::Serialize + Clone",
+                  deserialize = "V: ::serde::Deserialize"))]
+    custom: V,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct NoBounds<T> {
+    marker: PhantomData<T>,
+}
+
+#[test]
+fn test_bound_inference() {
+    let value: AssociatedType<Vec<i32>> = AssociatedType { item: 1 };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"item\":1}");
+    assert_eq!(json::from_str::<AssociatedType<Vec<i32>>>("{\"item\":1}").unwrap(), value);
+
+    let value: Bounds<Opaque, Opaque, i32> = Bounds {
+        value: 1,
+        marker: PhantomData,
+        skipped: Opaque,
+        custom: 2,
+    };
+    let s = "{\"value\":1,\"marker\":null,\"custom\":2}";
+    assert_eq!(json::to_string(&value).unwrap(), s);
+    assert_eq!(json::from_str::<Bounds<Opaque, Opaque, i32>>(s).unwrap(), value);
+
+    let value: NoBounds<Opaque> = NoBounds { marker: PhantomData };
+    assert_eq!(json::to_string(&value).unwrap(), "{\"marker\":null}");
+    assert_eq!(json::from_str::<NoBounds<Opaque>>("{\"marker\":null}").unwrap(), value);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Borrowed<'a> {
+    name: &'a str,
+    bytes: &'a [u8],
+    #[serde(borrow)]
+    text: Cow<'a, str>,
+    owned: String,
+}
+
+#[test]
+fn test_de_borrowed() {
+    let s = "{\"name\":\"a\",\"bytes\":\"b\",\"text\":\"c\",\"owned\":\"d\"}";
+    let v: Borrowed = json::from_str(s).unwrap();
+    assert_eq!(
+        v,
+        Borrowed {
+            name: "a",
+            bytes: b"b",
+            text: Cow::Borrowed("c"),
+            owned: "d".to_string(),
+        }
+    );
+
+    match v.text {
+        Cow::Borrowed(text) => assert_eq!(text, "c"),
+        Cow::Owned(_) => panic!("expected a borrowed string"),
+    }
+
+    // A `#[serde(borrow)]` `Cow` falls back to an owned string when it must be unescaped.
+    let s = "{\"name\":\"a\",\"bytes\":\"b\",\"text\":\"c\\n\",\"owned\":\"d\"}";
+    let v: Borrowed = json::from_str(s).unwrap();
+    match v.text {
+        Cow::Owned(text) => assert_eq!(text, "c\n"),
+        Cow::Borrowed(_) => panic!("expected an owned string"),
+    }
+
+    // A `&str` can't.
+    let s = "{\"name\":\"a\\n\",\"bytes\":\"b\",\"text\":\"c\",\"owned\":\"d\"}";
+    let err = json::from_str::<Borrowed>(s).unwrap_err();
+    assert!(err.to_string().contains("cannot be borrowed"));
+
+    // A `&[u8]` can't be unescaped either.
+    let s = "{\"name\":\"a\",\"bytes\":\"b\\n\",\"text\":\"c\",\"owned\":\"d\"}";
+    let err = json::from_str::<Borrowed>(s).unwrap_err();
+    assert!(err.to_string().contains("cannot be borrowed"));
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct BorrowedBytes<'a> {
+    #[serde(borrow)]
+    bytes: Cow<'a, [u8]>,
+}
+
+#[test]
+fn test_de_borrowed_bytes() {
+    let v: BorrowedBytes = json::from_str("{\"bytes\":\"ab\"}").unwrap();
+    match v.bytes {
+        Cow::Borrowed(bytes) => assert_eq!(bytes, &b"ab"[..]),
+        Cow::Owned(_) => panic!("expected borrowed bytes"),
+    }
+
+    let v: BorrowedBytes = json::from_str("{\"bytes\":\"a\\nb\"}").unwrap();
+    match v.bytes {
+        Cow::Owned(bytes) => assert_eq!(bytes, b"a\nb".to_vec()),
+        Cow::Borrowed(_) => panic!("expected owned bytes"),
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct BorrowedName<'a>(&'a str);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct BorrowedCowName<'a>(#[serde(borrow)] Cow<'a, str>);
+
+#[test]
+fn test_de_borrowed_newtype() {
+    let v: BorrowedName = json::from_str("\"a\"").unwrap();
+    assert_eq!(v, BorrowedName("a"));
+
+    let v: BorrowedCowName = json::from_str("\"a\"").unwrap();
+    match v.0 {
+        Cow::Borrowed(name) => assert_eq!(name, "a"),
+        Cow::Owned(_) => panic!("expected a borrowed string"),
+    }
+
+    let v: BorrowedCowName = json::from_str("\"a\\nb\"").unwrap();
+    assert_eq!(v, BorrowedCowName(Cow::Owned("a\nb".to_string())));
+}
+
+#[test]
+fn test_de_borrowed_from_reader() {
+    // A reader copies every string out of its buffer, so nothing can be borrowed from it.
+    let s = "{\"name\":\"a\",\"bytes\":\"b\",\"text\":\"c\",\"owned\":\"d\"}";
+    let mut de = json::Deserializer::new(json::IoRead::new(s.as_bytes())).unwrap();
+    let result: Result<Borrowed, json::Error> =
+        serde::de::DeserializeBorrowed::deserialize_borrowed(&mut de);
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("cannot be borrowed"));
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct TwoLifetimes<'a, 'b> {
+    name: &'a str,
+    marker: PhantomData<&'b ()>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct OwnedTuple<'a>(Cow<'a, str>, u32);
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum OwnedEnum<'a> {
+    Text(Cow<'a, str>),
+    Number(u32),
+}
+
+#[test]
+fn test_de_lifetime_without_borrow() {
+    // Only the lifetime that `name` borrows for is tied to the input.
+    let v: TwoLifetimes = json::from_str("{\"name\":\"a\",\"marker\":null}").unwrap();
+    assert_eq!(v, TwoLifetimes { name: "a", marker: PhantomData });
+
+    // Nothing borrows here, so these implement `Deserialize` and can be read from a reader.
+    let v: OwnedTuple = json::de::from_reader("[\"a\",1]".as_bytes()).unwrap();
+    assert_eq!(v, OwnedTuple(Cow::Owned("a".to_string()), 1));
+
+    let v: OwnedEnum = json::de::from_reader("{\"Text\":\"a\"}".as_bytes()).unwrap();
+    assert_eq!(v, OwnedEnum::Text(Cow::Owned("a".to_string())));
+
+    let v: OwnedEnum = json::from_str("{\"Number\":1}").unwrap();
+    assert_eq!(v, OwnedEnum::Number(1));
+}
@@ -1,4 +1,4 @@
-#![feature(custom_derive, plugin, test)]
+#![feature(custom_attribute, custom_derive, plugin, test)]
 #![plugin(serde_macros)]
 
 extern crate test;
@@ -46,6 +46,13 @@ pub enum Token<'a> {
     MapEnd,
 }
 
+#[derive(Debug, PartialEq)]
+struct Error;
+
+impl serde::ser::Error for Error {
+    fn custom<T: Into<String>>(_msg: T) -> Error { Error }
+}
+
 struct AssertSerializer<'a> {
     iter: vec::IntoIter<Token<'a>>,
 }
@@ -57,7 +64,7 @@ impl<'a> AssertSerializer<'a> {
         }
     }
 
-    fn visit_sequence<V>(&mut self, mut visitor: V) -> Result<(), ()>
+    fn visit_sequence<V>(&mut self, mut visitor: V) -> Result<(), Error>
         where V: SeqVisitor
     {
         while let Some(()) = try!(visitor.visit(self)) { }
@@ -67,7 +74,7 @@ impl<'a> AssertSerializer<'a> {
         Ok(())
     }
 
-    fn visit_mapping<V>(&mut self, mut visitor: V) -> Result<(), ()>
+    fn visit_mapping<V>(&mut self, mut visitor: V) -> Result<(), Error>
         where V: MapVisitor
     {
         while let Some(()) = try!(visitor.visit(self)) { }
@@ -79,104 +86,104 @@ impl<'a> AssertSerializer<'a> {
 }
 
 impl<'a> Serializer for AssertSerializer<'a> {
-    type Error = ();
+    type Error = Error;
 
-    fn visit_unit(&mut self) -> Result<(), ()> {
+    fn visit_unit(&mut self) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::Unit));
         Ok(())
     }
 
-    fn visit_named_unit(&mut self, name: &str) -> Result<(), ()> {
+    fn visit_named_unit(&mut self, name: &str) -> Result<(), Error> {
         assert_eq!(self.iter.next().unwrap(), Token::NamedUnit(name));
         Ok(())
     }
 
-    fn visit_enum_unit(&mut self, name: &str, variant: &str) -> Result<(), ()> {
+    fn visit_enum_unit(&mut self, name: &str, variant: &str) -> Result<(), Error> {
         assert_eq!(self.iter.next().unwrap(), Token::EnumUnit(name, variant));
         Ok(())
     }
 
-    fn visit_bool(&mut self, v: bool) -> Result<(), ()> {
+    fn visit_bool(&mut self, v: bool) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::Bool(v)));
         Ok(())
     }
 
-    fn visit_isize(&mut self, v: isize) -> Result<(), ()> {
+    fn visit_isize(&mut self, v: isize) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::Isize(v)));
         Ok(())
     }
 
-    fn visit_i8(&mut self, v: i8) -> Result<(), ()> {
+    fn visit_i8(&mut self, v: i8) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::I8(v)));
         Ok(())
     }
 
-    fn visit_i16(&mut self, v: i16) -> Result<(), ()> {
+    fn visit_i16(&mut self, v: i16) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::I16(v)));
         Ok(())
     }
 
-    fn visit_i32(&mut self, v: i32) -> Result<(), ()> {
+    fn visit_i32(&mut self, v: i32) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::I32(v)));
         Ok(())
     }
 
-    fn visit_i64(&mut self, v: i64) -> Result<(), ()> {
+    fn visit_i64(&mut self, v: i64) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::I64(v)));
         Ok(())
     }
 
-    fn visit_usize(&mut self, v: usize) -> Result<(), ()> {
+    fn visit_usize(&mut self, v: usize) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::Usize(v)));
         Ok(())
     }
 
-    fn visit_u8(&mut self, v: u8) -> Result<(), ()> {
+    fn visit_u8(&mut self, v: u8) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::U8(v)));
         Ok(())
     }
 
-    fn visit_u16(&mut self, v: u16) -> Result<(), ()> {
+    fn visit_u16(&mut self, v: u16) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::U16(v)));
         Ok(())
     }
 
-    fn visit_u32(&mut self, v: u32) -> Result<(), ()> {
+    fn visit_u32(&mut self, v: u32) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::U32(v)));
         Ok(())
     }
 
-    fn visit_u64(&mut self, v: u64) -> Result<(), ()> {
+    fn visit_u64(&mut self, v: u64) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::U64(v)));
         Ok(())
     }
 
-    fn visit_f32(&mut self, v: f32) -> Result<(), ()> {
+    fn visit_f32(&mut self, v: f32) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::F32(v)));
         Ok(())
     }
 
-    fn visit_f64(&mut self, v: f64) -> Result<(), ()> {
+    fn visit_f64(&mut self, v: f64) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::F64(v)));
         Ok(())
     }
 
-    fn visit_char(&mut self, v: char) -> Result<(), ()> {
+    fn visit_char(&mut self, v: char) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::Char(v)));
         Ok(())
     }
 
-    fn visit_str(&mut self, v: &str) -> Result<(), ()> {
+    fn visit_str(&mut self, v: &str) -> Result<(), Error> {
         assert_eq!(self.iter.next().unwrap(), Token::Str(v));
         Ok(())
     }
 
-    fn visit_none(&mut self) -> Result<(), ()> {
+    fn visit_none(&mut self) -> Result<(), Error> {
         assert_eq!(self.iter.next(), Some(Token::Option(false)));
         Ok(())
     }
 
-    fn visit_some<V>(&mut self, value: V) -> Result<(), ()>
+    fn visit_some<V>(&mut self, value: V) -> Result<(), Error>
         where V: Serialize,
     {
         assert_eq!(self.iter.next(), Some(Token::Option(true)));
@@ -184,7 +191,7 @@ impl<'a> Serializer for AssertSerializer<'a> {
     }
 
 
-    fn visit_seq<V>(&mut self, visitor: V) -> Result<(), ()>
+    fn visit_seq<V>(&mut self, visitor: V) -> Result<(), Error>
         where V: SeqVisitor
     {
         let len = visitor.len();
@@ -194,7 +201,7 @@ impl<'a> Serializer for AssertSerializer<'a> {
         self.visit_sequence(visitor)
     }
 
-    fn visit_named_seq<V>(&mut self, name: &str, visitor: V) -> Result<(), ()>
+    fn visit_named_seq<V>(&mut self, name: &str, visitor: V) -> Result<(), Error>
         where V: SeqVisitor
     {
         let len = visitor.len();
@@ -207,7 +214,7 @@ impl<'a> Serializer for AssertSerializer<'a> {
     fn visit_enum_seq<V>(&mut self,
                          name: &str,
                          variant: &str,
-                         visitor: V) -> Result<(), ()>
+                         visitor: V) -> Result<(), Error>
         where V: SeqVisitor
     {
         let len = visitor.len();
@@ -217,14 +224,14 @@ impl<'a> Serializer for AssertSerializer<'a> {
         self.visit_sequence(visitor)
     }
 
-    fn visit_seq_elt<T>(&mut self, value: T) -> Result<(), ()>
+    fn visit_seq_elt<T>(&mut self, value: T) -> Result<(), Error>
         where T: Serialize
     {
         assert_eq!(self.iter.next(), Some(Token::SeqSep));
         value.serialize(self)
     }
 
-    fn visit_map<V>(&mut self, visitor: V) -> Result<(), ()>
+    fn visit_map<V>(&mut self, visitor: V) -> Result<(), Error>
         where V: MapVisitor
     {
         let len = visitor.len();
@@ -234,7 +241,7 @@ impl<'a> Serializer for AssertSerializer<'a> {
         self.visit_mapping(visitor)
     }
 
-    fn visit_named_map<V>(&mut self, name: &str, visitor: V) -> Result<(), ()>
+    fn visit_named_map<V>(&mut self, name: &str, visitor: V) -> Result<(), Error>
         where V: MapVisitor
     {
         let len = visitor.len();
@@ -244,7 +251,7 @@ impl<'a> Serializer for AssertSerializer<'a> {
         self.visit_mapping(visitor)
     }
 
-    fn visit_enum_map<V>(&mut self, name: &str, variant: &str, visitor: V) -> Result<(), ()>
+    fn visit_enum_map<V>(&mut self, name: &str, variant: &str, visitor: V) -> Result<(), Error>
         where V: MapVisitor
     {
         let len = visitor.len();
@@ -254,7 +261,7 @@ impl<'a> Serializer for AssertSerializer<'a> {
         self.visit_mapping(visitor)
     }
 
-    fn visit_map_elt<K, V>(&mut self, key: K, value: V) -> Result<(), ()>
+    fn visit_map_elt<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
         where K: Serialize,
               V: Serialize,
     {
@@ -287,6 +294,27 @@ enum Enum {
     Map { a: i32, b: i32 },
 }
 
+#[derive(Serialize)]
+#[serde(rename="Unit")]
+struct RenamedUnit;
+
+#[derive(Serialize)]
+#[serde(rename="Map", rename_all="camelCase")]
+struct RenamedMap {
+    first_field: i32,
+    #[serde(rename="second")]
+    second_field: i32,
+}
+
+#[derive(Serialize)]
+#[serde(rename="Renamed", rename_all="snake_case")]
+enum RenamedEnum {
+    UnitVariant,
+    #[serde(rename="seq")]
+    SeqVariant(i32),
+    MapVariant { inner_field: i32 },
+}
+
 //////////////////////////////////////////////////////////////////////////
 
 macro_rules! btreemap {
@@ -525,4 +553,32 @@ declare_tests! {
             Token::MapEnd,
         ],
     }
+    test_rename {
+        RenamedUnit => vec![Token::NamedUnit("Unit")],
+        RenamedMap { first_field: 1, second_field: 2 } => vec![
+            Token::NamedMapStart("Map", Some(2)),
+                Token::MapSep,
+                Token::Str("firstField"),
+                Token::I32(1),
+
+                Token::MapSep,
+                Token::Str("second"),
+                Token::I32(2),
+            Token::MapEnd,
+        ],
+        RenamedEnum::UnitVariant => vec![Token::EnumUnit("Renamed", "unit_variant")],
+        RenamedEnum::SeqVariant(1) => vec![
+            Token::EnumSeqStart("Renamed", "seq", Some(1)),
+                Token::SeqSep,
+                Token::I32(1),
+            Token::SeqEnd,
+        ],
+        RenamedEnum::MapVariant { inner_field: 1 } => vec![
+            Token::EnumMapStart("Renamed", "map_variant", Some(1)),
+                Token::MapSep,
+                Token::Str("inner_field"),
+                Token::I32(1),
+            Token::MapEnd,
+        ],
+    }
 }